@@ -0,0 +1,466 @@
+//! Charts rendered as inline SVG on the server, so they work without any
+//! client-side JavaScript.
+
+use maud::{Markup, html};
+
+use super::Render;
+
+const PALETTE: [&str; 10] = [
+    "#2563eb", "#16a34a", "#ea580c", "#9333ea", "#dc2626", "#0891b2", "#ca8a04", "#db2777",
+    "#4b5563", "#65a30d",
+];
+
+fn color(index: usize) -> &'static str {
+    PALETTE[index % PALETTE.len()]
+}
+
+fn percent(value: i32, total: i32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        f64::from(value) / f64::from(total) * 100.0
+    }
+}
+
+pub struct Slice {
+    pub label: String,
+    pub value: i32,
+}
+
+/// A donut chart with a legend, showing the share of each slice of the total
+pub struct Donut {
+    pub title: &'static str,
+    pub unit: &'static str,
+    pub slices: Vec<Slice>,
+}
+
+impl Render for Donut {
+    fn render(&self) -> Markup {
+        let total: i32 = self.slices.iter().map(|slice| slice.value).sum();
+
+        // The circle has a circumference of 100, so each slice is simply drawn
+        // as a dash of its percentage. The offset of 25 starts at the top.
+        let mut offset = 25.0;
+        let mut arcs = vec![];
+        for (index, slice) in self.slices.iter().enumerate() {
+            if slice.value > 0 {
+                let share = percent(slice.value, total);
+                arcs.push((index, slice, share, offset));
+                offset -= share;
+            }
+        }
+
+        html!(
+            figure ."flex" ."flex-col" ."gap-2" {
+                figcaption ."font-bold" { (self.title) }
+                @if total == 0 {
+                    p ."text-gray-400" { "[No data]" }
+                } @else {
+                    div ."flex" ."flex-row" ."items-center" ."gap-4" {
+                        svg
+                            viewBox="0 0 42 42"
+                            width="160"
+                            height="160"
+                            role="img"
+                            aria-label=(self.title)
+                            ."shrink-0"
+                        {
+                            circle cx="21" cy="21" r="15.915" fill="transparent" stroke="#e5e7eb" stroke-width="6" {}
+                            @for (index, slice, share, offset) in &arcs {
+                                circle
+                                    cx="21"
+                                    cy="21"
+                                    r="15.915"
+                                    fill="transparent"
+                                    stroke=(color(*index))
+                                    stroke-width="6"
+                                    stroke-dasharray=(format!("{share:.3} {:.3}", 100.0 - share))
+                                    stroke-dashoffset=(format!("{offset:.3}"))
+                                {
+                                    title { (slice.label) ": " (slice.value) " " (self.unit) }
+                                }
+                            }
+                            text
+                                x="21"
+                                y="22.5"
+                                text-anchor="middle"
+                                font-size="5"
+                                font-weight="bold"
+                            {
+                                (total)
+                            }
+                            text x="21" y="27" text-anchor="middle" font-size="3" fill="#6b7280" {
+                                (self.unit)
+                            }
+                        }
+                        ul ."text-sm" {
+                            @for (index, slice, share, _) in &arcs {
+                                li ."flex" ."flex-row" ."items-center" ."gap-2" {
+                                    svg width="10" height="10" ."shrink-0" {
+                                        rect width="10" height="10" fill=(color(*index)) {}
+                                    }
+                                    span ."grow" { (slice.label) }
+                                    span ."text-gray-500" ."whitespace-nowrap" {
+                                        (slice.value) " " (self.unit)
+                                        (format!(" ({share:.0}%)"))
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct Bar {
+    pub label: String,
+    /// One value per series of the chart
+    pub values: Vec<i32>,
+    pub highlight: bool,
+}
+
+/// Horizontal bars, each of them stacked from the values of all series
+pub struct StackedBars {
+    pub title: &'static str,
+    pub unit: &'static str,
+    pub series: Vec<&'static str>,
+    pub bars: Vec<Bar>,
+}
+
+const BAR_HEIGHT: f64 = 18.0;
+const BAR_GAP: f64 = 6.0;
+const LABEL_WIDTH: f64 = 140.0;
+const VALUE_WIDTH: f64 = 80.0;
+const CHART_WIDTH: f64 = 600.0;
+
+impl Render for StackedBars {
+    fn render(&self) -> Markup {
+        let max: i32 = self
+            .bars
+            .iter()
+            .map(|bar| bar.values.iter().sum::<i32>())
+            .max()
+            .unwrap_or(0);
+
+        let bar_area = CHART_WIDTH - LABEL_WIDTH - VALUE_WIDTH;
+
+        let mut rows = vec![];
+        let mut y = 0.0;
+        for bar in &self.bars {
+            let mut x = LABEL_WIDTH;
+            let mut segments = vec![];
+            for (index, value) in bar.values.iter().enumerate() {
+                let width = percent(*value, max) / 100.0 * bar_area;
+                segments.push((x, width, index, *value));
+                x += width;
+            }
+            rows.push((y, bar, segments));
+            y += BAR_HEIGHT + BAR_GAP;
+        }
+        let height = y.max(BAR_HEIGHT);
+
+        html!(
+            figure ."flex" ."flex-col" ."gap-2" {
+                figcaption ."font-bold" { (self.title) }
+                @if max == 0 {
+                    p ."text-gray-400" { "[No data]" }
+                } @else {
+                    @if self.series.len() > 1 {
+                        ul ."flex" ."flex-row" ."gap-4" ."text-sm" {
+                            @for (index, series) in self.series.iter().enumerate() {
+                                li ."flex" ."flex-row" ."items-center" ."gap-2" {
+                                    svg width="10" height="10" {
+                                        rect width="10" height="10" fill=(color(index)) {}
+                                    }
+                                    span { (series) }
+                                }
+                            }
+                        }
+                    }
+                    svg
+                        viewBox=(format!("0 0 {CHART_WIDTH} {height}"))
+                        role="img"
+                        aria-label=(self.title)
+                        ."w-full"
+                        ."max-w-2xl"
+                    {
+                        @for (y, bar, segments) in &rows {
+                            text
+                                x=(LABEL_WIDTH - 6.0)
+                                y=(y + BAR_HEIGHT - 5.0)
+                                text-anchor="end"
+                                font-size="12"
+                                font-weight=[bar.highlight.then_some("bold")]
+                            {
+                                (bar.label)
+                            }
+                            @for (x, width, index, value) in segments {
+                                rect
+                                    x=(format!("{x:.2}"))
+                                    y=(y)
+                                    width=(format!("{width:.2}"))
+                                    height=(BAR_HEIGHT)
+                                    fill=(color(*index))
+                                    fill-opacity=(if bar.highlight { "1" } else { "0.6" })
+                                {
+                                    title {
+                                        (self.series.get(*index).copied().unwrap_or_default())
+                                        ": " (value) " " (self.unit)
+                                    }
+                                }
+                            }
+                            text
+                                x=(CHART_WIDTH - VALUE_WIDTH + 6.0)
+                                y=(y + BAR_HEIGHT - 5.0)
+                                font-size="12"
+                                fill="#4b5563"
+                                font-weight=[bar.highlight.then_some("bold")]
+                            {
+                                (bar.values.iter().sum::<i32>()) " " (self.unit)
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A line with the area below it filled, e.g. an elevation profile. The y
+/// axis starts at the lowest value instead of zero to show the differences.
+pub struct Area {
+    pub title: &'static str,
+    pub x_unit: &'static str,
+    pub y_unit: &'static str,
+    pub points: Vec<Point>,
+}
+
+const AREA_HEIGHT: f64 = 150.0;
+const AXIS_WIDTH: f64 = 60.0;
+const AXIS_HEIGHT: f64 = 20.0;
+
+impl Render for Area {
+    fn render(&self) -> Markup {
+        let (min_x, max_x, min_y, max_y) = self.points.iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(min_x, max_x, min_y, max_y), point| {
+                (
+                    min_x.min(point.x),
+                    max_x.max(point.x),
+                    min_y.min(point.y),
+                    max_y.max(point.y),
+                )
+            },
+        );
+
+        let plot_width = CHART_WIDTH - AXIS_WIDTH;
+        // avoid dividing by zero for a flat or single point line
+        let scale_x = plot_width / (max_x - min_x).max(f64::EPSILON);
+        let scale_y = AREA_HEIGHT / (max_y - min_y).max(1.0);
+
+        let line = self
+            .points
+            .iter()
+            .map(|point| {
+                format!(
+                    "{:.1},{:.1}",
+                    (point.x - min_x).mul_add(scale_x, AXIS_WIDTH),
+                    (point.y - min_y).mul_add(-scale_y, AREA_HEIGHT),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let area = format!("{AXIS_WIDTH},{AREA_HEIGHT} {line} {CHART_WIDTH},{AREA_HEIGHT}");
+
+        html!(
+            figure ."flex" ."flex-col" ."gap-2" {
+                figcaption ."font-bold" { (self.title) }
+                @if self.points.len() < 2 {
+                    p ."text-gray-400" { "[No data]" }
+                } @else {
+                    svg
+                        viewBox=(format!("0 0 {CHART_WIDTH} {}", AREA_HEIGHT + AXIS_HEIGHT))
+                        role="img"
+                        aria-label=(self.title)
+                        ."w-full"
+                        ."max-w-2xl"
+                    {
+                        polygon points=(area) fill=(color(0)) fill-opacity="0.2" {}
+                        polyline points=(line) fill="none" stroke=(color(0)) stroke-width="1.5" {}
+                        line x1=(AXIS_WIDTH) y1="0" x2=(AXIS_WIDTH) y2=(AREA_HEIGHT) stroke="#9ca3af" {}
+                        line x1=(AXIS_WIDTH) y1=(AREA_HEIGHT) x2=(CHART_WIDTH) y2=(AREA_HEIGHT) stroke="#9ca3af" {}
+                        text x=(AXIS_WIDTH - 6.0) y="10" text-anchor="end" font-size="12" fill="#4b5563" {
+                            (format!("{max_y:.0}")) " " (self.y_unit)
+                        }
+                        text x=(AXIS_WIDTH - 6.0) y=(AREA_HEIGHT) text-anchor="end" font-size="12" fill="#4b5563" {
+                            (format!("{min_y:.0}")) " " (self.y_unit)
+                        }
+                        text x=(AXIS_WIDTH) y=(AREA_HEIGHT + AXIS_HEIGHT - 4.0) font-size="12" fill="#4b5563" {
+                            (format!("{min_x:.0}")) " " (self.x_unit)
+                        }
+                        text
+                            x=(CHART_WIDTH)
+                            y=(AREA_HEIGHT + AXIS_HEIGHT - 4.0)
+                            text-anchor="end"
+                            font-size="12"
+                            fill="#4b5563"
+                        {
+                            (format!("{max_x:.1}")) " " (self.x_unit)
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn donut_draws_one_arc_per_nonempty_slice() {
+        let donut = Donut {
+            title: "Weight",
+            unit: "g",
+            slices: vec![
+                Slice {
+                    label: "Clothing".to_owned(),
+                    value: 30,
+                },
+                Slice {
+                    label: "Empty".to_owned(),
+                    value: 0,
+                },
+                Slice {
+                    label: "Kitchen".to_owned(),
+                    value: 10,
+                },
+            ],
+        };
+        let svg = donut.render().into_string();
+
+        assert!(svg.contains(r#"stroke-dasharray="75.000 25.000" stroke-dashoffset="25.000""#));
+        assert!(svg.contains(r#"stroke-dasharray="25.000 75.000" stroke-dashoffset="-50.000""#));
+        assert_eq!(svg.matches("stroke-dasharray").count(), 2);
+        assert!(!svg.contains("Empty"));
+        // the empty slice keeps its color, so colors match across charts
+        assert!(svg.contains(&format!(r#"stroke="{}""#, color(2))));
+    }
+
+    #[test]
+    fn donut_without_values_has_no_data() {
+        let donut = Donut {
+            title: "Weight",
+            unit: "g",
+            slices: vec![Slice {
+                label: "Empty".to_owned(),
+                value: 0,
+            }],
+        };
+        let svg = donut.render().into_string();
+
+        assert!(svg.contains("[No data]"));
+        assert!(!svg.contains("<svg"));
+    }
+
+    #[test]
+    fn stacked_bars_scale_to_the_largest_bar() {
+        let bars = StackedBars {
+            title: "Categories",
+            unit: "g",
+            series: vec!["Packed", "Worn"],
+            bars: vec![
+                Bar {
+                    label: "Clothing".to_owned(),
+                    values: vec![100, 50],
+                    highlight: true,
+                },
+                Bar {
+                    label: "Kitchen".to_owned(),
+                    values: vec![50, 0],
+                    highlight: false,
+                },
+            ],
+        };
+        let svg = bars.render().into_string();
+
+        // 380 units between the label and the value columns, 150 is the maximum
+        assert!(svg.contains(r#"viewBox="0 0 600 48""#));
+        assert!(svg.contains(r#"x="140.00" y="0" width="253.33""#));
+        assert!(svg.contains(r#"x="393.33" y="0" width="126.67""#));
+        assert!(svg.contains(r#"x="140.00" y="24" width="126.67""#));
+        assert!(svg.contains(r#"x="266.67" y="24" width="0.00""#));
+        assert!(svg.contains("150 g"));
+    }
+
+    #[test]
+    fn stacked_bars_without_values_have_no_data() {
+        let bars = StackedBars {
+            title: "Categories",
+            unit: "g",
+            series: vec!["Packed"],
+            bars: vec![],
+        };
+
+        assert!(bars.render().into_string().contains("[No data]"));
+    }
+
+    #[test]
+    fn area_starts_the_y_axis_at_the_lowest_point() {
+        let area = Area {
+            title: "Elevation",
+            x_unit: "km",
+            y_unit: "m",
+            points: vec![
+                Point { x: 0.0, y: 100.0 },
+                Point { x: 5.0, y: 200.0 },
+                Point { x: 10.0, y: 150.0 },
+            ],
+        };
+        let svg = area.render().into_string();
+
+        assert!(svg.contains(r#"<polyline points="60.0,150.0 330.0,0.0 600.0,75.0""#));
+        assert!(
+            svg.contains(r#"<polygon points="60,150 60.0,150.0 330.0,0.0 600.0,75.0 600,150""#)
+        );
+        assert!(svg.contains("200 m"));
+        assert!(svg.contains("100 m"));
+        assert!(svg.contains("10.0 km"));
+    }
+
+    #[test]
+    fn area_handles_flat_and_single_point_lines() {
+        let flat = Area {
+            title: "Elevation",
+            x_unit: "km",
+            y_unit: "m",
+            points: vec![Point { x: 0.0, y: 100.0 }, Point { x: 10.0, y: 100.0 }],
+        };
+        assert!(
+            flat.render()
+                .into_string()
+                .contains(r#"<polyline points="60.0,150.0 600.0,150.0""#)
+        );
+
+        let single = Area {
+            title: "Elevation",
+            x_unit: "km",
+            y_unit: "m",
+            points: vec![Point { x: 0.0, y: 100.0 }],
+        };
+        assert!(single.render().into_string().contains("[No data]"));
+    }
+}
@@ -1,8 +1,10 @@
+pub mod chart;
 pub mod infobox;
 pub mod listtable;
 pub mod text;
 pub mod types;
 
+pub use chart::{Area, Donut, StackedBars};
 pub use infobox::InfoBox;
 pub use listtable::TextListWithDate;
 pub use text::Text;
@@ -70,6 +70,9 @@ impl Render for Currency {
     fn render(&self) -> Markup {
         PreEscaped(match self.0 {
             crate::models::Currency::Eur(amount) => format!("{amount}€"),
+            crate::models::Currency::Usd(amount) => format!("${amount}"),
+            crate::models::Currency::Chf(amount) => format!("CHF {amount}"),
+            crate::models::Currency::Gbp(amount) => format!("£{amount}"),
         })
     }
 }
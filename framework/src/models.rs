@@ -1,4 +1,18 @@
 #[derive(Debug, Clone)]
 pub enum Currency {
     Eur(rust_decimal::Decimal),
+    Usd(rust_decimal::Decimal),
+    Chf(rust_decimal::Decimal),
+    Gbp(rust_decimal::Decimal),
+}
+
+impl Currency {
+    #[must_use]
+    pub const fn amount(&self) -> rust_decimal::Decimal {
+        match self {
+            Self::Eur(amount) | Self::Usd(amount) | Self::Chf(amount) | Self::Gbp(amount) => {
+                *amount
+            }
+        }
+    }
 }
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS "trip_state_history" (
+    id uuid NOT NULL,
+    trip_id uuid NOT NULL,
+    from_state trip_state,
+    to_state trip_state NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    user_id uuid NOT NULL,
+    PRIMARY KEY (id),
+    -- the history is meaningless without its trip
+    FOREIGN KEY (trip_id) REFERENCES "trips" (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS trip_state_history_trip_id ON "trip_state_history" (trip_id, changed_at);
+
+-- existing trips get their current state as a starting point, as we do not
+-- know anything about earlier transitions
+INSERT INTO trip_state_history
+    (id, trip_id, from_state, to_state, changed_at, user_id)
+SELECT
+    gen_random_uuid(), id, NULL, state, now(), user_id
+FROM trips;
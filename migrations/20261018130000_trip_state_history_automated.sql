@@ -0,0 +1,3 @@
+-- transitions done by the server itself (based on the trip date) instead of
+-- a user. `user_id` is still set to the owner of the trip.
+ALTER TABLE "trip_state_history" ADD COLUMN automated BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,31 @@
+CREATE TYPE accommodation_type AS ENUM ('tent', 'hut', 'hotel');
+
+CREATE TABLE IF NOT EXISTS "trip_stages" (
+    id uuid NOT NULL,
+    trip_id uuid NOT NULL,
+    position INTEGER NOT NULL,
+    date daterange NOT NULL,
+    location TEXT,
+    accommodation accommodation_type,
+    temp_min INTEGER,
+    temp_max INTEGER,
+    CHECK (temp_min <= temp_max),
+    CONSTRAINT date_range_lower_not_infinite CHECK (NOT lower_inf(date)),
+    CONSTRAINT date_range_lower_inclusive CHECK (lower_inc(date)),
+    CONSTRAINT date_range_upper_not_infinite CHECK (NOT upper_inf(date)),
+    CONSTRAINT date_range_upper_exclusive CHECK (NOT upper_inc(date)),
+    CONSTRAINT date_range_not_empty CHECK (NOT isempty(date)),
+    PRIMARY KEY (id),
+    FOREIGN KEY (trip_id) REFERENCES "trips" (id)
+);
+
+-- items that are only needed for some stages of a trip. items without any
+-- entry here are needed for the whole trip
+CREATE TABLE IF NOT EXISTS "trip_items_stages" (
+    item_id uuid NOT NULL,
+    trip_id uuid NOT NULL,
+    stage_id uuid NOT NULL,
+    PRIMARY KEY (item_id, stage_id),
+    FOREIGN KEY (item_id, trip_id) REFERENCES "trip_items" (item_id, trip_id),
+    FOREIGN KEY (stage_id) REFERENCES "trip_stages" (id)
+);
@@ -0,0 +1 @@
+ALTER TABLE trip_items ADD COLUMN worn BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,5 @@
+CREATE TYPE trip_item_review AS ENUM ('used', 'unused', 'missing', 'broken');
+
+ALTER TABLE trip_items ADD COLUMN review trip_item_review;
+
+ALTER TABLE trips ADD COLUMN lesson TEXT;
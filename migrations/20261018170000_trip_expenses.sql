@@ -0,0 +1,26 @@
+CREATE TYPE expense_category AS ENUM ('transport', 'food', 'lodging');
+
+CREATE TYPE currency_code AS ENUM ('eur', 'usd', 'chf', 'gbp');
+
+CREATE TABLE IF NOT EXISTS "trip_participants" (
+    trip_id uuid NOT NULL,
+    name TEXT NOT NULL,
+    CHECK (name != ''),
+    PRIMARY KEY (trip_id, name),
+    FOREIGN KEY (trip_id) REFERENCES "trips" (id)
+);
+
+CREATE TABLE IF NOT EXISTS "trip_expenses" (
+    id uuid NOT NULL,
+    trip_id uuid NOT NULL,
+    description TEXT,
+    amount money NOT NULL,
+    currency currency_code NOT NULL,
+    payer TEXT NOT NULL,
+    category expense_category NOT NULL,
+    date date NOT NULL,
+    CHECK (amount > 0::money),
+    PRIMARY KEY (id),
+    FOREIGN KEY (trip_id) REFERENCES "trips" (id),
+    FOREIGN KEY (trip_id, payer) REFERENCES "trip_participants" (trip_id, name)
+);
@@ -0,0 +1,25 @@
+CREATE TYPE todo_order AS ENUM ('manual', 'due');
+
+ALTER TABLE "trips"
+    ADD COLUMN todo_order todo_order NOT NULL DEFAULT 'manual';
+
+ALTER TABLE "trip_todos"
+    ADD COLUMN position INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN due_date date,
+    -- relative to the start of the trip, negative values are after the start
+    ADD COLUMN due_days_before INTEGER,
+    -- one of the participants of the trip
+    ADD COLUMN assignee TEXT,
+    ADD CONSTRAINT due_either_absolute_or_relative
+        CHECK (due_date IS NULL OR due_days_before IS NULL),
+    ADD FOREIGN KEY (trip_id, assignee) REFERENCES "trip_participants" (trip_id, name)
+        ON DELETE SET NULL (assignee);
+
+-- keep the previous, arbitrary order stable
+UPDATE trip_todos
+SET position = numbered.position
+FROM (
+    SELECT id, row_number() OVER (PARTITION BY trip_id ORDER BY description) - 1 AS position
+    FROM trip_todos
+) AS numbered
+WHERE trip_todos.id = numbered.id;
@@ -0,0 +1,18 @@
+-- todos that are added to a trip as soon as the type is added to it
+CREATE TABLE IF NOT EXISTS "trip_type_todos" (
+    id uuid NOT NULL,
+    trip_type_id uuid NOT NULL,
+    position INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    -- relative to the start of the trip, negative values are after the start
+    due_days_before INTEGER,
+    CHECK (description != ''),
+    PRIMARY KEY (id),
+    FOREIGN KEY (trip_type_id) REFERENCES "trip_types" (id)
+);
+
+-- remembers where a todo came from, so it is only instantiated once per trip
+ALTER TABLE "trip_todos"
+    ADD COLUMN trip_type_todo_id uuid,
+    ADD FOREIGN KEY (trip_type_todo_id) REFERENCES "trip_type_todos" (id) ON DELETE SET NULL,
+    ADD UNIQUE (trip_id, trip_type_todo_id);
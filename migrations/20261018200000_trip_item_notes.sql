@@ -0,0 +1,2 @@
+ALTER TABLE "trip_items"
+    ADD COLUMN note TEXT;
@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS "trip_containers" (
+    id uuid NOT NULL,
+    trip_id uuid NOT NULL,
+    user_id uuid NOT NULL,
+    name TEXT NOT NULL,
+    -- the bag itself, its weight counts towards the container
+    item_id uuid,
+    -- in grams
+    weight_limit INTEGER,
+    -- in liters
+    volume INTEGER,
+    CHECK (name != ''),
+    CHECK (weight_limit > 0),
+    CHECK (volume > 0),
+    PRIMARY KEY (id),
+    UNIQUE (trip_id, id),
+    UNIQUE (trip_id, name),
+    FOREIGN KEY (trip_id) REFERENCES "trips" (id),
+    FOREIGN KEY (user_id) REFERENCES "users" (id),
+    FOREIGN KEY (item_id) REFERENCES "inventory_items" (id)
+        ON DELETE SET NULL
+);
+
+ALTER TABLE "trip_items"
+    ADD COLUMN container_id uuid,
+    ADD FOREIGN KEY (trip_id, container_id) REFERENCES "trip_containers" (trip_id, id)
+        ON DELETE SET NULL (container_id);
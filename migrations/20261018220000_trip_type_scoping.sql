@@ -0,0 +1,5 @@
+-- names only need to be unique for each user
+ALTER TABLE "trip_types"
+    DROP CONSTRAINT trip_types_name_key,
+    ADD UNIQUE (user_id, name),
+    ADD FOREIGN KEY (user_id) REFERENCES "users" (id);
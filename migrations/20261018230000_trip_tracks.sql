@@ -0,0 +1,36 @@
+ALTER TABLE "trip_stages"
+    ADD UNIQUE (trip_id, id);
+
+-- GPX tracks of a trip, optionally belonging to a single stage. only the
+-- statistics and a downsampled elevation profile are kept, not the GPX itself
+CREATE TABLE IF NOT EXISTS "trip_tracks" (
+    id uuid NOT NULL,
+    trip_id uuid NOT NULL,
+    user_id uuid NOT NULL,
+    stage_id uuid,
+    name TEXT NOT NULL,
+    -- in meters
+    distance INTEGER NOT NULL,
+    elevation_gain INTEGER NOT NULL,
+    elevation_loss INTEGER NOT NULL,
+    -- NULL if the GPX does not contain any elevation data
+    max_elevation INTEGER,
+    min_lat DOUBLE PRECISION NOT NULL,
+    min_lon DOUBLE PRECISION NOT NULL,
+    max_lat DOUBLE PRECISION NOT NULL,
+    max_lon DOUBLE PRECISION NOT NULL,
+    -- pairs of distance from the start and elevation, both in meters
+    profile_distance INTEGER[] NOT NULL,
+    profile_elevation INTEGER[] NOT NULL,
+    CHECK (distance >= 0),
+    CHECK (elevation_gain >= 0),
+    CHECK (elevation_loss >= 0),
+    CHECK (min_lat <= max_lat),
+    CHECK (min_lon <= max_lon),
+    CHECK (cardinality(profile_distance) = cardinality(profile_elevation)),
+    PRIMARY KEY (id),
+    FOREIGN KEY (trip_id) REFERENCES "trips" (id),
+    FOREIGN KEY (user_id) REFERENCES "users" (id),
+    FOREIGN KEY (trip_id, stage_id) REFERENCES "trip_stages" (trip_id, id)
+        ON DELETE SET NULL (stage_id)
+);
@@ -0,0 +1,16 @@
+-- trip items used to be synced with the inventory lazily when viewing a trip.
+-- now they are added when the inventory item is created, so catch up once
+-- for all trips that were not viewed since their inventory changed
+INSERT INTO trip_items (item_id, trip_id, pick, pack, ready, new, user_id)
+SELECT
+    i_item.id,
+    trip.id,
+    false,
+    false,
+    false,
+    trip.state < 'active',
+    trip.user_id
+FROM trips AS trip
+INNER JOIN inventory_items AS i_item
+    ON i_item.user_id = trip.user_id
+ON CONFLICT (item_id, trip_id) DO NOTHING;
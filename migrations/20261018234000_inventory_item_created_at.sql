@@ -0,0 +1,4 @@
+-- items that existed before do not have a creation time, they count as
+-- created when the migration ran
+ALTER TABLE inventory_items
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
@@ -0,0 +1,16 @@
+-- PHC string of the argon2 hash. users without a password cannot log in
+-- with the built-in authentication
+ALTER TABLE users
+    ADD COLUMN password_hash TEXT;
+
+CREATE TABLE sessions (
+    -- SHA-256 of the session token, the token itself is only known to the
+    -- browser
+    id TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
+CREATE INDEX sessions_expires_at_idx ON sessions (expires_at);
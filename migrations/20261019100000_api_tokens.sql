@@ -0,0 +1,15 @@
+CREATE TYPE api_token_scope AS ENUM ('read', 'readwrite');
+
+CREATE TABLE api_tokens (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- SHA-256 of the token, the token itself is only shown once on creation
+    token_hash TEXT NOT NULL UNIQUE,
+    scope api_token_scope NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_used_at TIMESTAMPTZ,
+    -- tokens without expiry are valid until they are revoked
+    expires_at TIMESTAMPTZ,
+    UNIQUE (user_id, name)
+);
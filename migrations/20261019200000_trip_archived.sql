@@ -0,0 +1,6 @@
+-- archiving is independent of the state, a trip can be archived in any state
+-- and unarchived without changing it
+ALTER TABLE trips ADD COLUMN archived BOOLEAN NOT NULL DEFAULT false;
+
+-- until now, all done trips were hidden as archived
+UPDATE trips SET archived = true WHERE state = 'done';
@@ -0,0 +1,2 @@
+ALTER TABLE "trip_items"
+    ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1 CHECK (quantity > 0);
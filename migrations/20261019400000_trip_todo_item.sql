@@ -0,0 +1,7 @@
+-- remembers the item a maintenance todo was created for, so an item that is
+-- marked as broken again does not get a second todo
+ALTER TABLE "trip_todos"
+    ADD COLUMN item_id uuid,
+    ADD FOREIGN KEY (item_id, trip_id) REFERENCES "trip_items" (item_id, trip_id)
+        ON DELETE SET NULL (item_id),
+    ADD UNIQUE (trip_id, item_id);
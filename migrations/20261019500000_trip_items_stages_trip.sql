@@ -0,0 +1,9 @@
+-- an item can only be limited to the stages of its own trip. rows that point
+-- to a stage of another trip cannot be shown anywhere, so they are dropped
+DELETE FROM "trip_items_stages" AS item_stage
+USING "trip_stages" AS stage
+WHERE stage.id = item_stage.stage_id AND stage.trip_id != item_stage.trip_id;
+
+ALTER TABLE "trip_items_stages"
+    DROP CONSTRAINT "trip_items_stages_stage_id_fkey",
+    ADD FOREIGN KEY (trip_id, stage_id) REFERENCES "trip_stages" (trip_id, id);
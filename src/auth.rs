@@ -1,20 +1,171 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString},
+};
 use axum::{
-    extract::{Request, State},
-    middleware::Next,
-    response::IntoResponse,
+    Extension, Form, Router,
+    extract::{ConnectInfo, Query, Request, State},
+    handler::Handler,
+    http::{
+        HeaderValue, Method, StatusCode,
+        header::{self, HeaderMap, HeaderName},
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Redirect},
+    routing::{MethodRouter, get},
 };
 use futures::FutureExt;
+use ipnet::IpNet;
+use serde::Deserialize;
 use tracing::Instrument;
 
-use crate::models::user::User;
+use crate::models::{
+    api_token::{self, Authenticated, Scope, Secret},
+    session::{self, Token},
+    user::{Authentication, Credentials, NewUser, User},
+};
+use crate::{DatabaseError, QueryError, RequestError, htmx, view};
 
 use super::models;
 use super::{AppState, AuthError, RunError};
 
 #[derive(Clone, Debug)]
 pub enum Config {
-    Enabled,
-    Disabled { assume_user: String },
+    /// The reverse proxy authenticates users and sends the username in a
+    /// header
+    Enabled(HeaderConfig),
+    Disabled {
+        assume_user: String,
+    },
+    /// Users log in with username and password and get a session cookie
+    Password {
+        session_lifetime: Duration,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct HeaderConfig {
+    pub username_header: HeaderName,
+    /// Only requests from these addresses may authenticate with the header.
+    /// Everyone else could simply set the header themselves.
+    pub trusted_proxies: Vec<IpNet>,
+    /// Create users on their first request instead of rejecting them
+    pub provisioning: Option<Provisioning>,
+}
+
+impl HeaderConfig {
+    fn trusts(&self, source: Option<IpAddr>) -> bool {
+        source.is_some_and(|source| {
+            // IPv4 clients show up as mapped addresses when listening on IPv6
+            let source = source.to_canonical();
+            self.trusted_proxies
+                .iter()
+                .any(|proxy| proxy.contains(&source))
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Provisioning {
+    /// The header with the full name of new users. The username is used if
+    /// the header is missing.
+    pub fullname_header: HeaderName,
+}
+
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut argon2::password_hash::rand_core::OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Checked against when the user does not exist or has no password, so the
+/// response time does not reveal which usernames exist
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("").expect("hashing the dummy password failed"));
+
+fn verify_password(password_hash: &str, password: &str) -> bool {
+    PasswordHash::new(password_hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+fn session_token(headers: &HeaderMap) -> Option<Token> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|cookie| {
+            cookie
+                .trim()
+                .strip_prefix(session::COOKIE)?
+                .strip_prefix('=')
+        })
+        .map(Token::from_cookie)
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<Secret> {
+    let (scheme, token) = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then(|| Secret::from_header(token.trim()))
+}
+
+fn session_cookie(token: &Token, lifetime: Duration) -> String {
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; Secure; SameSite=Lax",
+        session::COOKIE,
+        token.as_str(),
+        lifetime.as_secs()
+    )
+}
+
+fn expired_session_cookie() -> String {
+    format!(
+        "{}=; Path=/; Max-Age=0; HttpOnly; Secure; SameSite=Lax",
+        session::COOKIE
+    )
+}
+
+/// Only redirect to paths on this host after login
+fn local_path(redirect: Option<&str>) -> &str {
+    match redirect {
+        Some(path) if path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') => {
+            path
+        }
+        _ => "/",
+    }
+}
+
+fn track(auth: &Result<User, AuthError>) {
+    metrics::counter!(
+        format!("packager_auth_{}_total", {
+            match auth {
+                Ok(_) => "success".to_string(),
+                Err(e) => {
+                    format!("failure_{}", e.to_prom_metric_name())
+                }
+            }
+        }),
+        &match auth {
+            Ok(user) => vec![("username", user.username.clone())],
+            Err(e) => e.to_prom_labels(),
+        }
+    )
+    .increment(1);
 }
 
 #[tracing::instrument(name = "check_auth", skip(state, request, next))]
@@ -31,11 +182,21 @@ pub async fn authorize(
     // Result<impl IntoResponse, Error>> {authorize}, AppState, Route, _>
     //
     // I am honestly not sure about the reason
-    let username_header = request.headers().get("x-auth-username");
+    let headers = request.headers();
+    let source = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let session_token = session_token(headers);
+    let bearer = bearer_token(headers);
+    let method = request.method();
+    // clients using tokens are not browsers that could follow redirects
+    let redirect_to_login =
+        matches!(state.auth_config, Config::Password { .. }) && bearer.is_none();
 
     let user = async {
-        let auth: Result<Result<User, AuthError>, RunError> = match state.auth_config {
-            Config::Disabled { assume_user } => {
+        let auth: Result<Result<User, AuthError>, RunError> = match (state.auth_config, bearer) {
+            (Config::Disabled { assume_user }, _) => {
                 let user =
                     match models::user::User::find_by_name(&state.database_pool, &assume_user)
                         .await?
@@ -47,23 +208,29 @@ pub async fn authorize(
                     };
                 Ok(user)
             }
-            Config::Enabled => match username_header {
-                None => Ok(Err(AuthError::AuthenticationHeaderMissing)),
-                Some(username) => match username.to_str() {
-                    Err(e) => Ok(Err(AuthError::AuthenticationHeaderInvalid {
-                        message: e.to_string(),
-                    })),
-                    Ok(username) => {
-                        match models::user::User::find_by_name(&state.database_pool, username)
-                            .await?
-                        {
-                            Some(user) => Ok(Ok(user)),
-                            None => Ok(Err(AuthError::AuthenticationUserNotFound {
-                                username: username.to_string(),
-                            })),
-                        }
+            // API tokens are an alternative to the configured authentication
+            (Config::Enabled(_) | Config::Password { .. }, Some(secret)) => {
+                authorize_token(&state.database_pool, &secret, method).await
+            }
+            (Config::Enabled(config), None) => {
+                authorize_header(&state.database_pool, &config, headers, source).await
+            }
+            (Config::Password { .. }, None) => match session_token {
+                None => Ok(Err(AuthError::SessionMissing)),
+                Some(token) => {
+                    match models::user::User::find_by_session(
+                        &state.database_pool,
+                        &token.session_id(),
+                    )
+                    .await?
+                    {
+                        Some(user) => Ok(Ok(User {
+                            authentication: Authentication::Session,
+                            ..user
+                        })),
+                        None => Ok(Err(AuthError::SessionInvalid)),
                     }
-                },
+                }
             },
         };
         auth
@@ -75,32 +242,319 @@ pub async fn authorize(
                 Ok(user) => tracing::debug!(?user, "auth successful"),
                 Err(e) => e.trace(),
             }
+            track(auth);
         }
     })
-    .map(|r| {
-        r.map(|auth| {
-            metrics::counter!(
-                format!("packager_auth_{}_total", {
-                    match auth {
-                        Ok(_) => "success".to_string(),
-                        Err(ref e) => {
-                            format!("failure_{}", e.to_prom_metric_name())
-                        }
-                    }
-                }),
-                &match &auth {
-                    Ok(user) => vec![("username", user.username.clone())],
-                    Err(e) => e.to_prom_labels(),
-                }
-            )
-            .increment(1);
-            auth
-        })
-    })
     // outer result: failure of the process, e.g. database connection failed
     // inner result: auth rejected, with AuthError
-    .await??;
+    .await?;
+
+    let user = match user {
+        Ok(user) => user,
+        // send browsers to the login page instead of showing an error
+        Err(_) if redirect_to_login => {
+            return Ok(if htmx::is_htmx(request.headers()) {
+                // reloading the page ends up on the login page, with the
+                // current page as redirect target
+                (
+                    [(HeaderName::from(htmx::ResponseHeaders::Refresh), "true")],
+                    StatusCode::UNAUTHORIZED,
+                )
+                    .into_response()
+            } else if request.method() == Method::GET {
+                let path = request
+                    .uri()
+                    .path_and_query()
+                    .map_or("/", http::uri::PathAndQuery::as_str);
+                Redirect::to(&format!(
+                    "/login?redirect={}",
+                    url::form_urlencoded::byte_serialize(path.as_bytes()).collect::<String>()
+                ))
+                .into_response()
+            } else {
+                Redirect::to("/login").into_response()
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     request.extensions_mut().insert(user);
     Ok::<http::Response<axum::body::Body>, RunError>(next.run(request).await)
 }
+
+async fn authorize_token(
+    pool: &database::Pool,
+    secret: &Secret,
+    method: &Method,
+) -> Result<Result<User, AuthError>, RunError> {
+    match api_token::authenticate(pool, secret).await? {
+        None => Ok(Err(AuthError::TokenInvalid)),
+        Some(Authenticated { user, scope }) if !scope.allows(method) => {
+            Ok(Err(AuthError::TokenScopeInsufficient {
+                username: user.username,
+            }))
+        }
+        Some(Authenticated { user, scope: _ }) => Ok(Ok(user)),
+    }
+}
+
+/// Marks routes that change data although they are reachable with GET, e.g.
+/// as the fallback of links for browsers without JavaScript. Read-only tokens
+/// cannot use them.
+pub async fn mutating(
+    Extension(user): Extension<User>,
+    request: Request,
+    next: Next,
+) -> Result<impl IntoResponse, RunError> {
+    if user.authentication == Authentication::Token(Scope::Read) {
+        return Err(denied(user));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Marks routes that only users themselves may use. No token can use them,
+/// whatever its scope, e.g. so a token cannot create broader tokens.
+pub async fn no_tokens(
+    Extension(user): Extension<User>,
+    request: Request,
+    next: Next,
+) -> Result<impl IntoResponse, RunError> {
+    if matches!(user.authentication, Authentication::Token(_)) {
+        return Err(denied(user));
+    }
+    Ok(next.run(request).await)
+}
+
+/// A GET route that changes data, see [`mutating`]
+pub fn get_mutating<H, T>(handler: H) -> MethodRouter<AppState>
+where
+    H: Handler<T, AppState>,
+    T: 'static,
+{
+    get(handler).route_layer(middleware::from_fn(mutating))
+}
+
+fn denied(user: User) -> RunError {
+    let error = AuthError::TokenScopeInsufficient {
+        username: user.username,
+    };
+    error.trace();
+    error.into()
+}
+
+async fn authorize_header(
+    pool: &database::Pool,
+    config: &HeaderConfig,
+    headers: &HeaderMap,
+    source: Option<IpAddr>,
+) -> Result<Result<User, AuthError>, RunError> {
+    let Some(username) = headers.get(&config.username_header) else {
+        return Ok(Err(AuthError::AuthenticationHeaderMissing));
+    };
+
+    if !config.trusts(source) {
+        return Ok(Err(AuthError::UntrustedSource { source }));
+    }
+
+    let username = match username.to_str() {
+        Ok(username) => username,
+        Err(e) => {
+            return Ok(Err(AuthError::AuthenticationHeaderInvalid {
+                message: e.to_string(),
+            }));
+        }
+    };
+
+    if let Some(user) = models::user::User::find_by_name(pool, username).await? {
+        return Ok(Ok(user));
+    }
+
+    match &config.provisioning {
+        None => Ok(Err(AuthError::AuthenticationUserNotFound {
+            username: username.to_string(),
+        })),
+        Some(provisioning) => {
+            provision(
+                pool,
+                username,
+                &provisioning.fullname_header,
+                headers.get(&provisioning.fullname_header),
+            )
+            .await
+        }
+    }
+}
+
+#[tracing::instrument(skip(pool))]
+async fn provision(
+    pool: &database::Pool,
+    username: &str,
+    fullname_header: &HeaderName,
+    fullname: Option<&HeaderValue>,
+) -> Result<Result<User, AuthError>, RunError> {
+    // names are not restricted to ASCII, so `HeaderValue::to_str()` does not
+    // work here
+    let fullname = match fullname.map(|fullname| std::str::from_utf8(fullname.as_bytes())) {
+        None => username,
+        Some(Ok(fullname)) => fullname,
+        Some(Err(e)) => {
+            return Ok(Err(AuthError::ProvisioningHeaderInvalid {
+                header: fullname_header.to_string(),
+                message: e.to_string(),
+            }));
+        }
+    };
+
+    match models::user::create(pool, NewUser { username, fullname }).await {
+        Ok(id) => {
+            tracing::info!(username, %id, "provisioned new user");
+            Ok(Ok(User {
+                id,
+                username: username.to_string(),
+                fullname: fullname.to_string(),
+                authentication: Authentication::External,
+            }))
+        }
+        // a concurrent request of the same user was faster
+        Err(DatabaseError::Query(QueryError::Duplicate { description: _ })) => {
+            Ok(models::user::User::find_by_name(pool, username)
+                .await?
+                .ok_or_else(|| AuthError::AuthenticationUserNotFound {
+                    username: username.to_string(),
+                }))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn session_lifetime(state: &AppState) -> Result<Duration, RunError> {
+    match state.auth_config {
+        Config::Password { session_lifetime } => Ok(session_lifetime),
+        Config::Enabled(_) | Config::Disabled { .. } => {
+            Err(RunError::Request(RequestError::NotFound {
+                message: "login is only available with password authentication".to_string(),
+            }))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct LoginQuery {
+    redirect: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginForm {
+    username: String,
+    password: String,
+    redirect: Option<String>,
+}
+
+// never print the password
+impl std::fmt::Debug for LoginForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoginForm")
+            .field("username", &self.username)
+            .field("redirect", &self.redirect)
+            .finish_non_exhaustive()
+    }
+}
+
+#[tracing::instrument]
+async fn login_page(
+    State(state): State<AppState>,
+    Query(login_query): Query<LoginQuery>,
+) -> Result<impl IntoResponse, RunError> {
+    session_lifetime(&state)?;
+    Ok(view::login::LoginPage::build(
+        None,
+        local_path(login_query.redirect.as_deref()),
+    ))
+}
+
+#[tracing::instrument]
+async fn login(
+    State(state): State<AppState>,
+    Form(login_form): Form<LoginForm>,
+) -> Result<impl IntoResponse, RunError> {
+    let lifetime = session_lifetime(&state)?;
+    let redirect = local_path(login_form.redirect.as_deref()).to_owned();
+
+    let credentials = Credentials::find(&state.database_pool, &login_form.username).await?;
+
+    // hashing is deliberately expensive, keep it off the async workers
+    let user = tokio::task::spawn_blocking(move || {
+        let password_hash = credentials
+            .as_ref()
+            .and_then(|credentials| credentials.password_hash.as_deref());
+        // no short circuit, the hash is verified in any case
+        let valid = password_hash.is_some()
+            & verify_password(
+                password_hash.unwrap_or(&DUMMY_PASSWORD_HASH),
+                &login_form.password,
+            );
+
+        credentials
+            .filter(|_| valid)
+            .map(|credentials| credentials.user)
+            .ok_or(AuthError::InvalidCredentials {
+                username: login_form.username,
+            })
+    })
+    .await
+    .expect("password verification panicked");
+
+    if let Err(e) = &user {
+        e.trace();
+    }
+    track(&user);
+
+    let Ok(user) = user else {
+        return Ok((
+            StatusCode::UNAUTHORIZED,
+            view::login::LoginPage::build(Some("Invalid username or password"), &redirect),
+        )
+            .into_response());
+    };
+
+    let expires_at = time::OffsetDateTime::now_utc() + lifetime;
+    let token = session::create(&state.database_pool, user.id, expires_at).await?;
+
+    Ok((
+        [(header::SET_COOKIE, session_cookie(&token, lifetime))],
+        Redirect::to(&redirect),
+    )
+        .into_response())
+}
+
+#[tracing::instrument]
+async fn logout_page(State(state): State<AppState>) -> Result<impl IntoResponse, RunError> {
+    session_lifetime(&state)?;
+    Ok(view::login::LogoutPage::build())
+}
+
+#[tracing::instrument(skip(headers))]
+async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, RunError> {
+    session_lifetime(&state)?;
+
+    if let Some(token) = session_token(&headers) {
+        session::delete(&state.database_pool, &token).await?;
+    }
+
+    Ok((
+        [(header::SET_COOKIE, expired_session_cookie())],
+        Redirect::to("/login"),
+    ))
+}
+
+/// Routes that must be reachable without being logged in
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/login", get(login_page).post(login))
+        .route("/logout", get(logout_page).post(logout))
+}
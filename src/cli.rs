@@ -1,6 +1,13 @@
-use crate::StartError;
+use crate::{
+    StartError,
+    domains::trips::{ExportFormat, GuardPolicy},
+};
+
+use std::net::IpAddr;
 
 use clap::{Parser, Subcommand, ValueEnum};
+use http::HeaderName;
+use ipnet::IpNet;
 
 #[derive(ValueEnum, Clone, Copy, Debug)]
 pub enum BoolArg {
@@ -34,6 +41,54 @@ impl From<BoolArg> for clap::builder::OsStr {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum GuardArg {
+    Allow,
+    Confirm,
+    Refuse,
+}
+
+impl From<GuardArg> for GuardPolicy {
+    fn from(arg: GuardArg) -> Self {
+        match arg {
+            GuardArg::Allow => Self::Allow,
+            GuardArg::Confirm => Self::Confirm,
+            GuardArg::Refuse => Self::Refuse,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum AuthArg {
+    /// Trust the username sent by the reverse proxy
+    Header,
+    /// Log in with username and password
+    Password,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormatArg {
+    Md,
+    Txt,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(arg: ExportFormatArg) -> Self {
+        match arg {
+            ExportFormatArg::Md => Self::Markdown,
+            ExportFormatArg::Txt => Self::Text,
+        }
+    }
+}
+
+fn parse_network(value: &str) -> Result<IpNet, String> {
+    // a single address is a network of its own
+    value
+        .parse()
+        .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| format!("\"{value}\" is neither an IP address nor a network"))
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
@@ -65,6 +120,8 @@ pub enum Command {
     #[command(subcommand)]
     Admin(Admin),
     Migrate,
+    /// Print a trip as a checklist
+    Export(Export),
 }
 
 #[derive(Parser, Debug)]
@@ -75,6 +132,56 @@ pub struct Serve {
     pub bind: String,
     #[arg(long, name = "USERNAME")]
     pub disable_auth_and_assume_user: Option<String>,
+    /// How users are authenticated. Ignored with `--disable-auth-and-assume-user`
+    #[arg(long, value_enum, default_value_t = AuthArg::Header)]
+    pub auth: AuthArg,
+    /// How long a login is valid with password authentication, in seconds
+    #[arg(long, default_value_t = 30 * 24 * 3600)]
+    pub session_lifetime: u64,
+    /// The header that contains the username with header authentication
+    #[arg(long, default_value = "x-auth-username")]
+    pub auth_header: HeaderName,
+    /// Address or network (CIDR) of a reverse proxy that may send the auth
+    /// header. Can be given multiple times.
+    #[arg(
+        long = "trusted-proxy",
+        name = "NETWORK",
+        default_values = ["127.0.0.0/8", "::1/128"],
+        value_parser = parse_network,
+    )]
+    pub trusted_proxies: Vec<IpNet>,
+    /// Create unknown users on their first request with header authentication
+    #[arg(long, value_enum, default_value_t = BoolArg::False)]
+    pub auth_provision_users: BoolArg,
+    /// The header that contains the full name of provisioned users
+    #[arg(long, default_value = "x-auth-fullname")]
+    pub auth_fullname_header: HeaderName,
+    /// What to do when a trip is started while picked items are not packed yet
+    #[arg(long, value_enum, default_value_t = GuardArg::Confirm)]
+    pub guard_unpacked_items: GuardArg,
+    /// What to do when a trip is started while todos are still open
+    #[arg(long, value_enum, default_value_t = GuardArg::Confirm)]
+    pub guard_open_todos: GuardArg,
+    /// Move trips to `Active` and `Review` automatically based on their dates
+    #[arg(long, value_enum, default_value_t = BoolArg::False)]
+    pub enable_auto_state_progression: BoolArg,
+    /// How often to check for trips that need to progress, in seconds
+    #[arg(long, default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..))]
+    pub auto_state_progression_interval: u64,
+}
+
+#[derive(Parser, Debug)]
+pub struct Export {
+    /// The user that owns the trip
+    #[arg(long)]
+    pub username: String,
+    #[arg(long)]
+    pub trip_id: uuid::Uuid,
+    #[arg(long, value_enum, default_value_t = ExportFormatArg::Md)]
+    pub format: ExportFormatArg,
+    /// Write the export to this file instead of stdout
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -86,6 +193,9 @@ pub enum Admin {
 #[derive(Subcommand, Debug)]
 pub enum UserCommand {
     Create(UserCreate),
+    /// Set the password for password authentication. The password is read
+    /// from stdin.
+    SetPassword(UserSetPassword),
 }
 
 #[derive(Parser, Debug)]
@@ -96,6 +206,12 @@ pub struct UserCreate {
     pub fullname: String,
 }
 
+#[derive(Parser, Debug)]
+pub struct UserSetPassword {
+    #[arg(long)]
+    pub username: String,
+}
+
 impl Args {
     pub fn get() -> Result<Self, StartError> {
         let args = Self::parse();
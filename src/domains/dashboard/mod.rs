@@ -0,0 +1,450 @@
+//! The dashboard on the home page. Each widget is loaded as a separate htmx
+//! fragment, so a slow widget does not hold back the others.
+
+pub mod view;
+
+use axum::{Extension, Router, extract::State, response::IntoResponse, routing::get};
+use uuid::Uuid;
+
+use crate::{AppState, Context, RunError, domains::trips::TripState, models::User};
+
+/// How many of the most recently added inventory items are shown
+const RECENT_ITEMS: i64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Widget {
+    NextTrip,
+    Todos,
+    Review,
+    RecentItems,
+    Inventory,
+}
+
+impl Widget {
+    pub const ALL: [Self; 5] = [
+        Self::NextTrip,
+        Self::Todos,
+        Self::Review,
+        Self::RecentItems,
+        Self::Inventory,
+    ];
+
+    fn slug(self) -> &'static str {
+        match self {
+            Self::NextTrip => "next-trip",
+            Self::Todos => "todos",
+            Self::Review => "review",
+            Self::RecentItems => "recent-items",
+            Self::Inventory => "inventory",
+        }
+    }
+
+    #[must_use]
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::NextTrip => "Next trip",
+            Self::Todos => "Open todos",
+            Self::Review => "Waiting for review",
+            Self::RecentItems => "Recently added items",
+            Self::Inventory => "Inventory",
+        }
+    }
+
+    #[must_use]
+    pub fn id(self) -> String {
+        format!("dashboard-{}", self.slug())
+    }
+
+    #[must_use]
+    pub fn path(self) -> String {
+        format!("/dashboard/{}", self.slug())
+    }
+}
+
+#[derive(Debug)]
+pub struct NextTrip {
+    pub id: Uuid,
+    pub name: String,
+    pub state: TripState,
+    pub start: time::Date,
+    pub days_left: i32,
+    pub picked: i64,
+    pub packed: i64,
+}
+
+struct NextTripRow {
+    id: Uuid,
+    name: String,
+    state: TripState,
+    start: time::Date,
+    days_left: i32,
+    picked: i64,
+    packed: i64,
+}
+
+impl TryFrom<NextTripRow> for NextTrip {
+    type Error = RunError;
+
+    fn try_from(row: NextTripRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            state: row.state,
+            start: row.start,
+            days_left: row.days_left,
+            picked: row.picked,
+            packed: row.packed,
+        })
+    }
+}
+
+impl NextTrip {
+    /// The trip with the earliest start that has not started yet
+    #[tracing::instrument]
+    pub async fn find(ctx: &Context, pool: &database::Pool) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            NextTripRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    trip.id AS id,
+                    trip.name AS name,
+                    trip.state AS "state: _",
+                    lower(trip.date) AS "start!",
+                    lower(trip.date) - CURRENT_DATE AS "days_left!",
+                    COUNT(t_item.item_id) FILTER (WHERE t_item.pick) AS "picked!",
+                    COUNT(t_item.item_id) FILTER (WHERE t_item.pick AND t_item.pack) AS "packed!"
+                FROM trips AS trip
+                LEFT JOIN trip_items AS t_item
+                    ON t_item.trip_id = trip.id
+                WHERE
+                    trip.user_id = $1
+                    AND lower(trip.date) >= CURRENT_DATE
+                GROUP BY trip.id
+                ORDER BY lower(trip.date) ASC
+                LIMIT 1
+            "#,
+            ctx.user.id
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct OpenTodo {
+    pub trip_id: Uuid,
+    pub trip_name: String,
+    pub description: String,
+    pub due_at: Option<time::Date>,
+    pub overdue: bool,
+}
+
+struct OpenTodoRow {
+    trip_id: Uuid,
+    trip_name: String,
+    description: String,
+    due_at: Option<time::Date>,
+    overdue: bool,
+}
+
+impl TryFrom<OpenTodoRow> for OpenTodo {
+    type Error = RunError;
+
+    fn try_from(row: OpenTodoRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            trip_id: row.trip_id,
+            trip_name: row.trip_name,
+            description: row.description,
+            due_at: row.due_at,
+            overdue: row.overdue,
+        })
+    }
+}
+
+impl OpenTodo {
+    /// All todos that are not done yet, of all trips that are not done yet.
+    /// Todos that are due first come first.
+    #[tracing::instrument]
+    pub async fn findall(ctx: &Context, pool: &database::Pool) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Todo,
+            },
+            pool,
+            OpenTodoRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    trip.id AS trip_id,
+                    trip.name AS trip_name,
+                    todo.description AS description,
+                    COALESCE(todo.due_date, lower(trip.date) - todo.due_days_before) AS due_at,
+                    COALESCE(
+                        COALESCE(todo.due_date, lower(trip.date) - todo.due_days_before) < CURRENT_DATE,
+                        false
+                    ) AS "overdue!"
+                FROM trip_todos AS todo
+                INNER JOIN trips AS trip
+                    ON trip.id = todo.trip_id
+                WHERE
+                    trip.user_id = $1
+                    AND trip.state != 'done'
+                    AND NOT todo.done
+                ORDER BY
+                    COALESCE(todo.due_date, lower(trip.date) - todo.due_days_before) ASC NULLS LAST,
+                    lower(trip.date) ASC,
+                    todo.position ASC
+            "#,
+            ctx.user.id
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct ReviewTrip {
+    pub id: Uuid,
+    pub name: String,
+    pub end: time::Date,
+    pub days_since_end: i32,
+}
+
+struct ReviewTripRow {
+    id: Uuid,
+    name: String,
+    end: time::Date,
+    days_since_end: i32,
+}
+
+impl TryFrom<ReviewTripRow> for ReviewTrip {
+    type Error = RunError;
+
+    fn try_from(row: ReviewTripRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            end: row.end,
+            days_since_end: row.days_since_end,
+        })
+    }
+}
+
+impl ReviewTrip {
+    /// Trips in the review state, the ones that ended longest ago first
+    #[tracing::instrument]
+    pub async fn findall(ctx: &Context, pool: &database::Pool) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ReviewTripRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    trip.id AS id,
+                    trip.name AS name,
+                    -- the upper bound of the range is exclusive
+                    upper(trip.date) - 1 AS "end!",
+                    CURRENT_DATE - (upper(trip.date) - 1) AS "days_since_end!"
+                FROM trips AS trip
+                WHERE
+                    trip.user_id = $1
+                    AND trip.state = 'review'
+                ORDER BY upper(trip.date) ASC
+            "#,
+            ctx.user.id
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct RecentItem {
+    pub id: Uuid,
+    pub name: String,
+    pub weight: i32,
+    pub category_name: String,
+    pub created_at: time::OffsetDateTime,
+}
+
+struct RecentItemRow {
+    id: Uuid,
+    name: String,
+    weight: i32,
+    category_name: String,
+    created_at: time::OffsetDateTime,
+}
+
+impl TryFrom<RecentItemRow> for RecentItem {
+    type Error = RunError;
+
+    fn try_from(row: RecentItemRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            weight: row.weight,
+            category_name: row.category_name,
+            created_at: row.created_at,
+        })
+    }
+}
+
+impl RecentItem {
+    #[tracing::instrument]
+    pub async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        limit: i64,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Inventory,
+            },
+            pool,
+            RecentItemRow,
+            Self,
+            RunError,
+            "
+                SELECT
+                    item.id AS id,
+                    item.name AS name,
+                    item.weight AS weight,
+                    category.name AS category_name,
+                    item.created_at AS created_at
+                FROM inventory_items AS item
+                INNER JOIN inventory_items_categories AS category
+                    ON category.id = item.category_id
+                WHERE item.user_id = $1
+                ORDER BY item.created_at DESC, item.name ASC
+                LIMIT $2
+            ",
+            ctx.user.id,
+            limit
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct InventoryTotals {
+    pub items: i64,
+    pub weight: i64,
+}
+
+struct InventoryTotalsRow {
+    items: i64,
+    weight: i64,
+}
+
+impl TryFrom<InventoryTotalsRow> for InventoryTotals {
+    type Error = RunError;
+
+    fn try_from(row: InventoryTotalsRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            items: row.items,
+            weight: row.weight,
+        })
+    }
+}
+
+impl InventoryTotals {
+    #[tracing::instrument]
+    pub async fn find(ctx: &Context, pool: &database::Pool) -> Result<Self, RunError> {
+        let totals = database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Inventory,
+            },
+            pool,
+            InventoryTotalsRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    COUNT(*) AS "items!",
+                    COALESCE(SUM(item.weight), 0) AS "weight!"
+                FROM inventory_items AS item
+                WHERE item.user_id = $1
+            "#,
+            ctx.user.id
+        )
+        .await?;
+
+        // an aggregate without GROUP BY always returns a row
+        Ok(totals.expect("aggregate query returned no row"))
+    }
+}
+
+#[tracing::instrument]
+async fn next_trip(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let trip = NextTrip::find(&ctx, &state.database_pool).await?;
+    Ok(view::NextTripWidget::build(trip.as_ref()))
+}
+
+#[tracing::instrument]
+async fn todos(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let todos = OpenTodo::findall(&ctx, &state.database_pool).await?;
+    Ok(view::TodosWidget::build(&todos))
+}
+
+#[tracing::instrument]
+async fn review(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let trips = ReviewTrip::findall(&ctx, &state.database_pool).await?;
+    Ok(view::ReviewWidget::build(&trips))
+}
+
+#[tracing::instrument]
+async fn recent_items(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let items = RecentItem::findall(&ctx, &state.database_pool, RECENT_ITEMS).await?;
+    Ok(view::RecentItemsWidget::build(&items))
+}
+
+#[tracing::instrument]
+async fn inventory(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let totals = InventoryTotals::find(&ctx, &state.database_pool).await?;
+    Ok(view::InventoryWidget::build(&totals))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/dashboard",
+        Router::new()
+            .route("/next-trip", get(next_trip))
+            .route("/todos", get(todos))
+            .route("/review", get(review))
+            .route("/recent-items", get(recent_items))
+            .route("/inventory", get(inventory)),
+    )
+}
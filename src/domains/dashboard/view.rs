@@ -0,0 +1,267 @@
+use maud::{Markup, html};
+
+use super::{InventoryTotals, NextTrip, OpenTodo, RecentItem, ReviewTrip, Widget};
+
+/// How many open todos are listed, the rest only counts
+const TODOS_SHOWN: usize = 10;
+
+fn frame(widget: Widget, content: &Markup) -> Markup {
+    html!(
+        section
+            id=(widget.id())
+            ."border-2"
+            ."border-gray-200"
+            ."p-4"
+            ."flex"
+            ."flex-col"
+            ."gap-3"
+        {
+            h2 ."text-lg" { (widget.title()) }
+            (content)
+        }
+    )
+}
+
+fn empty(text: &str) -> Markup {
+    html!(
+        p ."text-gray-400" { "[" (text) "]" }
+    )
+}
+
+/// Stands in for a widget until htmx loaded it
+pub struct Placeholder;
+
+impl Placeholder {
+    #[tracing::instrument]
+    pub fn build(widget: Widget) -> Markup {
+        html!(
+            section
+                id=(widget.id())
+                hx-get=(widget.path())
+                hx-trigger="load"
+                hx-swap="outerHTML"
+                ."border-2"
+                ."border-gray-200"
+                ."p-4"
+                ."flex"
+                ."flex-col"
+                ."gap-3"
+            {
+                h2 ."text-lg" { (widget.title()) }
+                p ."text-gray-400" { "Loading…" }
+            }
+        )
+    }
+}
+
+pub struct NextTripWidget;
+
+impl NextTripWidget {
+    #[tracing::instrument]
+    pub fn build(trip: Option<&NextTrip>) -> Markup {
+        frame(
+            Widget::NextTrip,
+            &html!(
+                @match trip {
+                    None => (empty("No upcoming trip")),
+                    Some(trip) => {
+                        div ."flex" ."flex-row" ."items-baseline" ."gap-3" {
+                            a
+                                href={ "/trips/" (trip.id) "/" }
+                                ."text-xl"
+                                ."text-blue-600"
+                                ."hover:underline"
+                            {
+                                (trip.name)
+                            }
+                            span ."text-gray-500" { (trip.state) }
+                        }
+                        p {
+                            span ."mdi" ."mdi-calendar-clock" ."mr-1" {}
+                            @match trip.days_left {
+                                0 => "Starts today",
+                                1 => "Starts tomorrow",
+                                days => { "Starts in " (days) " days" },
+                            }
+                            span ."text-gray-500" ."ml-2" { (trip.start) }
+                        }
+                        @if trip.picked == 0 {
+                            p ."text-gray-500" { "No items picked yet" }
+                        } @else {
+                            div ."flex" ."flex-col" ."gap-1" {
+                                p { (trip.packed) " of " (trip.picked) " items packed" }
+                                div ."w-full" ."h-2" ."bg-gray-200" {
+                                    div
+                                        ."h-2"
+                                        ."bg-green-400"
+                                        style=(
+                                            format!(
+                                                "width: {width}%",
+                                                width = trip.packed * 100 / trip.picked
+                                            )
+                                        )
+                                    {}
+                                }
+                            }
+                        }
+                    }
+                }
+            ),
+        )
+    }
+}
+
+pub struct TodosWidget;
+
+impl TodosWidget {
+    #[tracing::instrument]
+    pub fn build(todos: &[OpenTodo]) -> Markup {
+        let overdue = todos.iter().filter(|todo| todo.overdue).count();
+
+        frame(
+            Widget::Todos,
+            &html!(
+                @if todos.is_empty() {
+                    (empty("No open todos"))
+                } @else {
+                    p {
+                        (todos.len()) " open"
+                        @if overdue > 0 {
+                            ", "
+                            span ."text-red-700" ."font-bold" { (overdue) " overdue" }
+                        }
+                    }
+                    ul ."flex" ."flex-col" ."gap-1" {
+                        @for todo in todos.iter().take(TODOS_SHOWN) {
+                            li ."flex" ."flex-row" ."gap-2" ."items-baseline" {
+                                span
+                                    ."grow"
+                                    ."text-red-700"[todo.overdue]
+                                {
+                                    (todo.description)
+                                }
+                                @if let Some(due_at) = todo.due_at {
+                                    span
+                                        ."text-sm"
+                                        ."text-gray-500"[!todo.overdue]
+                                        ."text-red-700"[todo.overdue]
+                                    {
+                                        (due_at)
+                                    }
+                                }
+                                a
+                                    href={ "/trips/" (todo.trip_id) "/" }
+                                    ."text-sm"
+                                    ."text-blue-600"
+                                    ."hover:underline"
+                                {
+                                    (todo.trip_name)
+                                }
+                            }
+                        }
+                    }
+                    @if todos.len() > TODOS_SHOWN {
+                        p ."text-sm" ."text-gray-500" {
+                            "and " (todos.len() - TODOS_SHOWN) " more"
+                        }
+                    }
+                }
+            ),
+        )
+    }
+}
+
+pub struct ReviewWidget;
+
+impl ReviewWidget {
+    #[tracing::instrument]
+    pub fn build(trips: &[ReviewTrip]) -> Markup {
+        frame(
+            Widget::Review,
+            &html!(
+                @if trips.is_empty() {
+                    (empty("No trips waiting for review"))
+                } @else {
+                    ul ."flex" ."flex-col" ."gap-1" {
+                        @for trip in trips {
+                            li ."flex" ."flex-row" ."gap-2" ."items-baseline" {
+                                a
+                                    href={ "/trips/" (trip.id) "/" }
+                                    ."grow"
+                                    ."text-blue-600"
+                                    ."hover:underline"
+                                {
+                                    (trip.name)
+                                }
+                                span ."text-sm" ."text-gray-500" title=(trip.end) {
+                                    @match trip.days_since_end {
+                                        0 => "ended today",
+                                        1 => "ended yesterday",
+                                        days => { "ended " (days) " days ago" },
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            ),
+        )
+    }
+}
+
+pub struct RecentItemsWidget;
+
+impl RecentItemsWidget {
+    #[tracing::instrument]
+    pub fn build(items: &[RecentItem]) -> Markup {
+        frame(
+            Widget::RecentItems,
+            &html!(
+                @if items.is_empty() {
+                    (empty("No items"))
+                } @else {
+                    ul ."flex" ."flex-col" ."gap-1" {
+                        @for item in items {
+                            li ."flex" ."flex-row" ."gap-2" ."items-baseline" {
+                                a
+                                    href={ "/inventory/item/" (item.id) "/" }
+                                    ."grow"
+                                    ."text-blue-600"
+                                    ."hover:underline"
+                                {
+                                    (item.name)
+                                }
+                                span ."text-sm" ."text-gray-500" { (item.category_name) }
+                                span ."text-sm" { (item.weight) " g" }
+                                span ."text-sm" ."text-gray-500" { (item.created_at.date()) }
+                            }
+                        }
+                    }
+                }
+            ),
+        )
+    }
+}
+
+pub struct InventoryWidget;
+
+impl InventoryWidget {
+    #[tracing::instrument]
+    pub fn build(totals: &InventoryTotals) -> Markup {
+        frame(
+            Widget::Inventory,
+            &html!(
+                div ."flex" ."flex-row" ."gap-6" {
+                    a href="/inventory/" ."hover:underline" {
+                        span ."mdi" ."mdi-bag-personal" ."mr-1" {}
+                        (totals.items) " items"
+                    }
+                    span title="Total weight" {
+                        span ."mdi" ."mdi-weight" ."mr-1" {}
+                        (format!("{}.{} kg", totals.weight / 1000, totals.weight % 1000 / 100))
+                    }
+                }
+            ),
+        )
+    }
+}
@@ -486,12 +486,16 @@ impl InventoryItem {
         let id = Uuid::new_v4();
         let weight = i32::try_from(weight).unwrap();
 
+        let mut transaction = pool.begin().await?;
+
+        crate::domains::trips::lock_trip_items(ctx, &mut transaction).await?;
+
         database::execute!(
             &database::QueryClassification {
                 query_type: database::QueryType::Insert,
                 component: crate::Component::Inventory,
             },
-            pool,
+            &mut *transaction,
             RunError,
             "INSERT INTO inventory_items
                 (id, name, description, weight, category_id, user_id)
@@ -506,6 +510,10 @@ impl InventoryItem {
         )
         .await?;
 
+        crate::domains::trips::add_inventory_item_to_trips(ctx, &mut transaction, id).await?;
+
+        transaction.commit().await?;
+
         Ok(id)
     }
 
@@ -542,7 +550,7 @@ impl InventoryItem {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Item {
     pub id: Uuid,
     pub name: String,
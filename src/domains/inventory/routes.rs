@@ -12,6 +12,7 @@ use crate::view::Component;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::auth;
 use crate::htmx;
 use crate::models;
 use crate::{AppState, Context, RunError, RequestError, TopLevelPage};
@@ -350,7 +351,7 @@ pub fn router() -> Router<AppState> {
             .route("/item/", post(create_item))
             .route("/item/{id}/", get(item))
             .route("/item/{id}/cancel", get(item_cancel))
-            .route("/item/{id}/delete", get(item_delete))
+            .route("/item/{id}/delete", auth::get_mutating(item_delete))
             .route("/item/{id}/edit", post(item_edit))
             .route("/item/name/validate", post(item_validate_name)),
     )
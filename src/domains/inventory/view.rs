@@ -2,7 +2,7 @@ use maud::{Markup, html};
 
 use crate::ClientState;
 use framework::{
-    components::types::Url,
+    components::{Donut, Render as _, chart::Slice, types::Url},
     elements::{
         self,
         list::{self, Action, List},
@@ -33,6 +33,7 @@ impl Inventory {
                         h1 ."text-2xl" ."text-center" { "Categories" }
                         (InventoryCategoryList::build(active_category, categories))
                         (InventoryNewCategoryForm::build())
+                        (InventoryWeightChart::build(categories))
                     }
                     div ."col-span-2" ."flex" ."flex-col" ."gap-8" {
                         h1 ."text-2xl" ."text-center" { "Items" }
@@ -47,6 +48,31 @@ impl Inventory {
     }
 }
 
+pub struct InventoryWeightChart;
+
+impl InventoryWeightChart {
+    #[tracing::instrument(
+        target = "packager::html::build",
+        name = "build_inventory_weight_chart",
+        fields(component = "InventoryWeightChart"),
+        skip(categories)
+    )]
+    pub fn build(categories: &[model::Category]) -> Markup {
+        Donut {
+            title: "Weight by category",
+            unit: "g",
+            slices: categories
+                .iter()
+                .map(|category| Slice {
+                    label: category.name.clone(),
+                    value: category.total_weight(),
+                })
+                .collect(),
+        }
+        .render()
+    }
+}
+
 pub struct InventoryCategoryList;
 
 impl InventoryCategoryList {
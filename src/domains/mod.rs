@@ -1,5 +1,7 @@
+pub mod dashboard;
 pub mod inventory;
 pub mod products;
+pub mod settings;
 pub mod trips;
 
 pub mod crud {
@@ -6,7 +6,7 @@ use axum::{
 };
 use uuid::Uuid;
 
-use crate::{AppState, Context, RunError, RequestError, TopLevelPage, models};
+use crate::{AppState, Context, RunError, RequestError, TopLevelPage, auth, models};
 
 #[tracing::instrument]
 pub async fn comment_create(
@@ -95,7 +95,7 @@ pub async fn comment_edit_save(
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/new", post(comment_create))
-        .route("/{id}/delete", get(comment_delete))
+        .route("/{id}/delete", auth::get_mutating(comment_delete))
         .route("/{id}/edit", get(comment_edit))
         .route("/{id}/edit/save", post(comment_edit_save))
 }
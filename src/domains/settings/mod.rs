@@ -0,0 +1,130 @@
+//! Account settings of the current user. For now, these are only the personal
+//! API tokens.
+
+pub mod view;
+
+use axum::{
+    Extension, Form, Router,
+    extract::{Path, State},
+    middleware,
+    response::{IntoResponse, Redirect},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState, Context, RequestError, RunError, auth,
+    models::{
+        User,
+        api_token::{ApiToken, NewApiToken, Scope},
+    },
+    routing::date_or_empty,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewTokenForm {
+    #[serde(rename = "token-name")]
+    name: String,
+    #[serde(rename = "token-scope")]
+    scope: Scope,
+    /// The token is valid until the end of this day (UTC)
+    #[serde(rename = "token-expires", deserialize_with = "date_or_empty")]
+    expires: Option<time::Date>,
+}
+
+#[tracing::instrument]
+async fn tokens(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let tokens = ApiToken::findall(&ctx, &state.database_pool).await?;
+
+    Ok(crate::view::Root::build(
+        &ctx,
+        &view::Tokens::build(&tokens, None),
+        None,
+    ))
+}
+
+#[tracing::instrument]
+async fn token_create(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Form(new_token): Form<NewTokenForm>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+
+    let name = new_token.name.trim();
+    if name.is_empty() {
+        return Err(RunError::Request(RequestError::EmptyFormElement {
+            name: "token-name".to_string(),
+        }));
+    }
+
+    if new_token
+        .expires
+        .is_some_and(|expires| expires < time::OffsetDateTime::now_utc().date())
+    {
+        return Err(RunError::Request(RequestError::InvalidFormElement {
+            name: "token-expires".to_string(),
+            message: "date is in the past".to_string(),
+        }));
+    }
+
+    let secret = ApiToken::create(
+        &ctx,
+        &state.database_pool,
+        NewApiToken {
+            name,
+            scope: new_token.scope,
+            expires_at: new_token.expires.map(|expires| {
+                expires
+                    .next_day()
+                    .unwrap_or(expires)
+                    .midnight()
+                    .assume_utc()
+            }),
+        },
+    )
+    .await?;
+
+    let tokens = ApiToken::findall(&ctx, &state.database_pool).await?;
+
+    // no redirect, the secret is shown only this one time
+    Ok(crate::view::Root::build(
+        &ctx,
+        &view::Tokens::build(&tokens, Some((name, &secret))),
+        None,
+    ))
+}
+
+#[tracing::instrument]
+async fn token_revoke(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(token_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+
+    if ApiToken::revoke(&ctx, &state.database_pool, token_id).await? {
+        Ok(Redirect::to("/settings/tokens/"))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("token with id {token_id} not found"),
+        }))
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest(
+        "/settings",
+        Router::new()
+            .route("/tokens/", get(tokens).post(token_create))
+            .route("/tokens/{id}/revoke", post(token_revoke))
+            // a token could otherwise create a broader one, or revoke others
+            .route_layer(middleware::from_fn(auth::no_tokens)),
+    )
+}
@@ -0,0 +1,172 @@
+use maud::{Markup, html};
+
+use crate::models::api_token::{ApiToken, Scope, Secret};
+
+fn timestamp(value: Option<time::OffsetDateTime>, none: &str) -> Markup {
+    html!(
+        @match value {
+            Some(value) => span title=(value) { (value.date()) },
+            None => span ."text-gray-400" { (none) },
+        }
+    )
+}
+
+pub struct Tokens;
+
+impl Tokens {
+    /// `created` is the token that was just created, its secret cannot be
+    /// shown again later
+    #[tracing::instrument(skip(created))]
+    pub fn build(tokens: &[ApiToken], created: Option<(&str, &Secret)>) -> Markup {
+        html!(
+            div
+                id="tokens"
+                ."p-8"
+                ."flex"
+                ."flex-col"
+                ."gap-8"
+            {
+                div ."flex" ."flex-col" ."gap-2" {
+                    h1 ."text-2xl" { "API tokens" }
+                    p ."text-gray-500" {
+                        "Scripts and other clients can use a token instead of logging in, by sending it
+                        in the "
+                        code { "Authorization: Bearer <token>" }
+                        " header. Read-only tokens cannot change anything."
+                    }
+                }
+                @if let Some((name, secret)) = created {
+                    div
+                        ."p-4"
+                        ."border-2"
+                        ."border-green-500"
+                        ."bg-green-100"
+                        ."flex"
+                        ."flex-col"
+                        ."gap-2"
+                    {
+                        p {
+                            "Token " b { (name) } " created. Copy it now, it will not be shown again:"
+                        }
+                        input
+                            type="text"
+                            readonly
+                            value=(secret.as_str())
+                            onfocus="this.select()"
+                            ."p-2"
+                            ."font-mono"
+                            ."bg-white"
+                            ."w-full"
+                        {}
+                    }
+                }
+                @if tokens.is_empty() {
+                    p ."text-gray-400" { "[No tokens]" }
+                } @else {
+                    table
+                        ."table"
+                        ."table-auto"
+                        ."border-collapse"
+                        ."w-full"
+                    {
+                        thead {
+                            tr ."bg-gray-200" {
+                                th ."p-2" ."text-left" { "Name" }
+                                th ."p-2" ."text-left" { "Scope" }
+                                th ."p-2" ."text-left" { "Created" }
+                                th ."p-2" ."text-left" { "Last used" }
+                                th ."p-2" ."text-left" { "Expires" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            @for token in tokens {
+                                tr
+                                    ."border-b-2"
+                                    ."text-gray-400"[token.expired()]
+                                {
+                                    td ."p-2" { (token.name) }
+                                    td ."p-2" { (token.scope) }
+                                    td ."p-2" { (timestamp(Some(token.created_at), "")) }
+                                    td ."p-2" { (timestamp(token.last_used_at, "never")) }
+                                    td ."p-2" {
+                                        (timestamp(token.expires_at, "never"))
+                                        @if token.expired() {
+                                            " (expired)"
+                                        }
+                                    }
+                                    td ."p-2" ."text-right" {
+                                        form
+                                            action={ "/settings/tokens/" (token.id) "/revoke" }
+                                            method="post"
+                                        {
+                                            button
+                                                type="submit"
+                                                ."py-1"
+                                                ."px-2"
+                                                ."bg-red-200"
+                                                ."hover:bg-red-300"
+                                            {
+                                                span ."mdi" ."mdi-delete" ."mr-1" {}
+                                                "Revoke"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                form
+                    action="/settings/tokens/"
+                    method="post"
+                    ."flex"
+                    ."flex-row"
+                    ."flex-wrap"
+                    ."items-center"
+                    ."gap-3"
+                {
+                    input
+                        type="text"
+                        name="token-name"
+                        placeholder="Name"
+                        required
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {}
+                    select
+                        name="token-scope"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {
+                        @for scope in Scope::ALL {
+                            option value=(scope.id()) { (scope) }
+                        }
+                    }
+                    label ."flex" ."flex-row" ."items-center" ."gap-1" {
+                        span { "Valid until" }
+                        input
+                            type="date"
+                            name="token-expires"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                            ."p-1"
+                        {}
+                    }
+                    button
+                        type="submit"
+                        ."p-1"
+                        ."px-3"
+                        ."border-2"
+                        ."border-gray-500"
+                        ."hover:bg-gray-200"
+                    {
+                        "Create token"
+                    }
+                }
+            }
+        )
+    }
+}
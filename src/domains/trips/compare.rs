@@ -0,0 +1,456 @@
+//! Side-by-side comparison of the picked items of two trips.
+//!
+//! The trip in the path is the target, the one given via `?with=` is the
+//! source. Differences can be copied from the source into the target.
+
+use std::fmt;
+
+use axum::{
+    extract::{Extension, Form, Path, Query, State},
+    response::{IntoResponse, Redirect},
+    routing::{get, post},
+    Router,
+};
+use serde::{de, Deserialize};
+use uuid::Uuid;
+
+use crate::{
+    domains::inventory, routing::uuid_or_empty, AppState, Context, RequestError, RunError,
+    TopLevelPage,
+};
+
+use super::{model, view};
+use crate::models::User;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Only picked in the target trip
+    Target,
+    /// Only picked in the source trip
+    Source,
+    Both,
+}
+
+#[derive(Debug)]
+pub struct ComparedItem {
+    pub item: inventory::Item,
+    pub side: Side,
+    /// Picked weight in the target trip, taking the quantity into account
+    pub target_weight: i32,
+    /// Picked weight in the source trip, taking the quantity into account
+    pub source_weight: i32,
+}
+
+impl ComparedItem {
+    pub const fn delta(&self) -> i32 {
+        self.target_weight - self.source_weight
+    }
+}
+
+#[derive(Debug)]
+pub struct CategoryComparison {
+    pub category: inventory::Category,
+    pub items: Vec<ComparedItem>,
+    pub target_weight: i32,
+    pub source_weight: i32,
+}
+
+impl CategoryComparison {
+    pub const fn delta(&self) -> i32 {
+        self.target_weight - self.source_weight
+    }
+}
+
+#[derive(Debug)]
+pub struct Comparison {
+    pub categories: Vec<CategoryComparison>,
+}
+
+impl Comparison {
+    /// Compares the picked items of both trips. Requires the categories of both
+    /// trips to be loaded.
+    pub fn new(target: &model::Trip, source: &model::Trip) -> Self {
+        Self::from_categories(target.categories(), source.categories())
+    }
+
+    /// Goes through the categories and items of both sides, as each side can
+    /// contain items that are missing on the other, e.g. when an item was
+    /// deleted from the inventory or its category was changed.
+    fn from_categories(target: &[model::TripCategory], source: &[model::TripCategory]) -> Self {
+        fn find_category(
+            categories: &[model::TripCategory],
+            category_id: Uuid,
+        ) -> Option<&model::TripCategory> {
+            categories
+                .iter()
+                .find(|category| category.category.id == category_id)
+        }
+
+        fn items_of(category: Option<&model::TripCategory>) -> &[model::TripItem] {
+            category
+                .and_then(|category| category.items.as_deref())
+                .unwrap_or_default()
+        }
+
+        let mut category_ids: Vec<Uuid> = vec![];
+        for category in target.iter().chain(source) {
+            if !category_ids.contains(&category.category.id) {
+                category_ids.push(category.category.id);
+            }
+        }
+
+        let mut categories = vec![];
+
+        for category_id in category_ids {
+            let target_category = find_category(target, category_id);
+            let source_category = find_category(source, category_id);
+            let target_items = items_of(target_category);
+            let source_items = items_of(source_category);
+
+            let picked = |items: &[model::TripItem], item_id: Uuid| {
+                items
+                    .iter()
+                    .find(|item| item.item.id == item_id && item.picked)
+                    .map(model::TripItem::weight)
+            };
+
+            let mut items: Vec<ComparedItem> = vec![];
+            for trip_item in target_items.iter().chain(source_items) {
+                if items.iter().any(|item| item.item.id == trip_item.item.id) {
+                    continue;
+                }
+                let target_weight = picked(target_items, trip_item.item.id);
+                let source_weight = picked(source_items, trip_item.item.id);
+                let side = match (target_weight, source_weight) {
+                    (Some(_), Some(_)) => Side::Both,
+                    (Some(_), None) => Side::Target,
+                    (None, Some(_)) => Side::Source,
+                    (None, None) => continue,
+                };
+                items.push(ComparedItem {
+                    item: trip_item.item.clone(),
+                    side,
+                    target_weight: target_weight.unwrap_or(0),
+                    source_weight: source_weight.unwrap_or(0),
+                });
+            }
+
+            if items.is_empty() {
+                continue;
+            }
+
+            let category = target_category
+                .or(source_category)
+                .expect("category ids are taken from both sides");
+
+            categories.push(CategoryComparison {
+                category: inventory::Category {
+                    id: category.category.id,
+                    name: category.category.name.clone(),
+                    items: None,
+                },
+                target_weight: items.iter().map(|item| item.target_weight).sum(),
+                source_weight: items.iter().map(|item| item.source_weight).sum(),
+                items,
+            });
+        }
+
+        Self { categories }
+    }
+
+    pub fn target_weight(&self) -> i32 {
+        self.categories
+            .iter()
+            .map(|category| category.target_weight)
+            .sum()
+    }
+
+    pub fn source_weight(&self) -> i32 {
+        self.categories
+            .iter()
+            .map(|category| category.source_weight)
+            .sum()
+    }
+
+    pub fn delta(&self) -> i32 {
+        self.target_weight() - self.source_weight()
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CompareQuery {
+    #[serde(default, deserialize_with = "uuid_or_empty")]
+    with: Option<Uuid>,
+}
+
+#[tracing::instrument]
+async fn load_trip(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+) -> Result<model::Trip, RunError> {
+    let mut trip = model::Trip::find(ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    trip.load_categories(ctx, &state.database_pool).await?;
+
+    Ok(trip)
+}
+
+#[tracing::instrument]
+pub async fn compare(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    Query(compare_query): Query<CompareQuery>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+
+    let target = load_trip(&ctx, &state, trip_id).await?;
+
+    let source = match compare_query.with {
+        Some(source_id) => Some(load_trip(&ctx, &state, source_id).await?),
+        None => None,
+    };
+
+    let comparison = source
+        .as_ref()
+        .map(|source| Comparison::new(&target, source));
+
+    let trips = model::Trip::all(&ctx, &state.database_pool).await?;
+
+    Ok(crate::view::Root::build(
+        &ctx,
+        &view::compare::TripComparison::build(
+            &target,
+            source.as_ref().zip(comparison.as_ref()),
+            &trips,
+        ),
+        Some(&TopLevelPage::Trips),
+    ))
+}
+
+/// The form of [`copy`]. It contains one `item` field per selected checkbox,
+/// which the derived `Deserialize` would reject as duplicate fields.
+#[derive(Debug)]
+pub struct CopyPicks {
+    with: Uuid,
+    items: Vec<Uuid>,
+}
+
+impl<'de> Deserialize<'de> for CopyPicks {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct CopyPicksVisitor;
+
+        impl<'de> de::Visitor<'de> for CopyPicksVisitor {
+            type Value = CopyPicks;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a source trip and the items to copy")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut with = None;
+                let mut items = vec![];
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "with" => with = Some(map.next_value()?),
+                        "item" => items.push(map.next_value()?),
+                        other => return Err(de::Error::unknown_field(other, &["with", "item"])),
+                    }
+                }
+                Ok(CopyPicks {
+                    with: with.ok_or_else(|| de::Error::missing_field("with"))?,
+                    items,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(CopyPicksVisitor)
+    }
+}
+
+/// Copies the picked state of the selected items from the source trip into
+/// the target trip.
+#[tracing::instrument]
+pub async fn copy(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    Form(copy_picks): Form<CopyPicks>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let CopyPicks {
+        with: source_id,
+        items: item_ids,
+    } = copy_picks;
+
+    model::Trip::copy_picks(&ctx, &state.database_pool, trip_id, source_id, &item_ids).await?;
+
+    Ok(Redirect::to(&format!(
+        "/trips/{trip_id}/compare/?with={source_id}"
+    )))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(compare))
+        .route("/copy", post(copy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLOTHING: Uuid = Uuid::from_u128(1);
+    const KITCHEN: Uuid = Uuid::from_u128(2);
+
+    fn item(id: u128, category_id: Uuid, weight: i32, picked: bool) -> model::TripItem {
+        model::TripItem {
+            item: inventory::Item {
+                id: Uuid::from_u128(id),
+                name: format!("item {id}"),
+                description: None,
+                weight,
+                category_id,
+            },
+            picked,
+            packed: false,
+            ready: false,
+            worn: false,
+            new: false,
+            note: None,
+            quantity: 1,
+            container_id: None,
+        }
+    }
+
+    fn category(id: Uuid, items: Vec<model::TripItem>) -> model::TripCategory {
+        model::TripCategory {
+            category: inventory::Category {
+                id,
+                name: format!("category {id}"),
+                items: None,
+            },
+            items: Some(items),
+        }
+    }
+
+    fn sides(comparison: &Comparison) -> Vec<(Uuid, Vec<(Uuid, Side)>)> {
+        comparison
+            .categories
+            .iter()
+            .map(|category| {
+                (
+                    category.category.id,
+                    category
+                        .items
+                        .iter()
+                        .map(|item| (item.item.id, item.side))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn items_on_both_sides() {
+        let target = [category(
+            CLOTHING,
+            vec![
+                item(1, CLOTHING, 100, true),
+                item(2, CLOTHING, 200, true),
+                item(3, CLOTHING, 300, false),
+            ],
+        )];
+        let source = [category(
+            CLOTHING,
+            vec![
+                item(1, CLOTHING, 100, true),
+                item(2, CLOTHING, 200, false),
+                item(3, CLOTHING, 300, true),
+            ],
+        )];
+
+        let comparison = Comparison::from_categories(&target, &source);
+
+        assert_eq!(
+            sides(&comparison),
+            vec![(
+                CLOTHING,
+                vec![
+                    (Uuid::from_u128(1), Side::Both),
+                    (Uuid::from_u128(2), Side::Target),
+                    (Uuid::from_u128(3), Side::Source),
+                ]
+            )]
+        );
+        assert_eq!(comparison.target_weight(), 300);
+        assert_eq!(comparison.source_weight(), 400);
+        assert_eq!(comparison.delta(), -100);
+    }
+
+    #[test]
+    fn items_only_in_the_target() {
+        let target = [
+            category(CLOTHING, vec![item(1, CLOTHING, 100, true)]),
+            category(KITCHEN, vec![item(2, KITCHEN, 200, true)]),
+        ];
+        let source = [category(CLOTHING, vec![])];
+
+        let comparison = Comparison::from_categories(&target, &source);
+
+        assert_eq!(
+            sides(&comparison),
+            vec![
+                (CLOTHING, vec![(Uuid::from_u128(1), Side::Target)]),
+                (KITCHEN, vec![(Uuid::from_u128(2), Side::Target)]),
+            ]
+        );
+        assert_eq!(comparison.delta(), 300);
+    }
+
+    #[test]
+    fn items_only_in_the_source() {
+        let target = [category(CLOTHING, vec![])];
+        let source = [
+            category(CLOTHING, vec![item(1, CLOTHING, 100, true)]),
+            category(KITCHEN, vec![item(2, KITCHEN, 200, true)]),
+        ];
+
+        let comparison = Comparison::from_categories(&target, &source);
+
+        assert_eq!(
+            sides(&comparison),
+            vec![
+                (CLOTHING, vec![(Uuid::from_u128(1), Side::Source)]),
+                (KITCHEN, vec![(Uuid::from_u128(2), Side::Source)]),
+            ]
+        );
+        assert_eq!(
+            comparison.categories[1].category.name,
+            format!("category {KITCHEN}")
+        );
+        assert_eq!(comparison.delta(), -300);
+    }
+
+    #[test]
+    fn quantities_count_towards_the_weight() {
+        let mut picked_twice = item(1, CLOTHING, 100, true);
+        picked_twice.quantity = 2;
+        let target = [category(CLOTHING, vec![picked_twice])];
+        let source = [category(CLOTHING, vec![item(1, CLOTHING, 100, true)])];
+
+        let comparison = Comparison::from_categories(&target, &source);
+
+        assert_eq!(comparison.categories[0].items[0].delta(), 100);
+        assert_eq!(comparison.categories[0].delta(), 100);
+    }
+}
@@ -0,0 +1,405 @@
+//! Bags and other containers the items of a trip are packed into, e.g. a
+//! backpack or an airline carry-on. A container can have a weight limit, and
+//! the weight of the bag itself is taken from its inventory item.
+
+pub mod view;
+
+use axum::{
+    Extension, Router,
+    extract::{Form, Path, State},
+    response::{IntoResponse, Redirect},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState, Context, RequestError, RunError,
+    models::User,
+    routing::{parse_or_empty, uuid_or_empty},
+};
+
+use super::model::{Trip, TripParts};
+
+/// The inventory item that is used as the container
+#[derive(Debug)]
+pub struct ContainerItem {
+    pub id: Uuid,
+    pub name: String,
+    pub weight: i32,
+}
+
+#[derive(Debug)]
+pub struct Container {
+    pub id: Uuid,
+    pub name: String,
+    pub item: Option<ContainerItem>,
+    /// In grams
+    pub weight_limit: Option<i32>,
+    /// In liters
+    pub volume: Option<i32>,
+    /// Weight of the picked items packed into the container
+    pub content_weight: i32,
+    pub item_count: i64,
+}
+
+struct ContainerRow {
+    id: Uuid,
+    name: String,
+    item_id: Option<Uuid>,
+    item_name: Option<String>,
+    item_weight: Option<i32>,
+    weight_limit: Option<i32>,
+    volume: Option<i32>,
+    content_weight: i32,
+    item_count: i64,
+}
+
+impl TryFrom<ContainerRow> for Container {
+    type Error = RunError;
+
+    fn try_from(row: ContainerRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            item: match (row.item_id, row.item_name, row.item_weight) {
+                (Some(id), Some(name), Some(weight)) => Some(ContainerItem { id, name, weight }),
+                _ => None,
+            },
+            weight_limit: row.weight_limit,
+            volume: row.volume,
+            content_weight: row.content_weight,
+            item_count: row.item_count,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ContainerNew {
+    pub name: String,
+    pub item_id: Option<Uuid>,
+    pub weight_limit: Option<i32>,
+    pub volume: Option<i32>,
+}
+
+impl Container {
+    /// Total weight including the container itself
+    #[must_use]
+    pub fn weight(&self) -> i32 {
+        self.content_weight + self.item.as_ref().map_or(0, |item| item.weight)
+    }
+
+    /// By how much the weight limit is exceeded, if at all
+    #[must_use]
+    pub fn excess_weight(&self) -> Option<i32> {
+        self.weight_limit
+            .map(|limit| self.weight() - limit)
+            .filter(|excess| *excess > 0)
+    }
+
+    #[tracing::instrument]
+    pub async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ContainerRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    container.id AS id,
+                    container.name AS name,
+                    i_item.id AS "item_id?",
+                    i_item.name AS "item_name?",
+                    i_item.weight AS "item_weight?",
+                    container.weight_limit AS weight_limit,
+                    container.volume AS volume,
+                    CAST(COALESCE(SUM(content.weight * t_item.quantity), 0) AS INTEGER) AS "content_weight!",
+                    COUNT(content.id) AS "item_count!"
+                FROM trip_containers AS container
+                LEFT JOIN inventory_items AS i_item
+                    ON i_item.id = container.item_id
+                LEFT JOIN trip_items AS t_item
+                    ON t_item.trip_id = container.trip_id
+                    AND t_item.container_id = container.id
+                    AND t_item.pick = true
+                LEFT JOIN inventory_items AS content
+                    ON content.id = t_item.item_id
+                WHERE container.trip_id = $1 AND container.user_id = $2
+                GROUP BY container.id, i_item.id
+                ORDER BY container.name
+            "#,
+            trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    pub async fn create(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        container: ContainerNew,
+    ) -> Result<Uuid, RunError> {
+        if let Some(item_id) = container.item_id {
+            let exists: bool = database::query_exists!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Select,
+                    component: crate::Component::Trips,
+                },
+                pool,
+                "SELECT item_id
+                FROM trip_items
+                WHERE trip_id = $1 AND item_id = $2 AND user_id = $3",
+                trip_id,
+                item_id,
+                ctx.user.id
+            )
+            .await
+            .map_err(|e: sqlx::Error| -> RunError { e.into() })?;
+
+            if !exists {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: "container-item-id".to_owned(),
+                    message: format!("item with id {item_id} is not part of the trip"),
+                }));
+            }
+        }
+
+        let id = Uuid::new_v4();
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "INSERT INTO trip_containers
+                (id, trip_id, user_id, name, item_id, weight_limit, volume)
+            SELECT $1, id, user_id, $2, $3, $4, $5
+            FROM trips
+            WHERE id = $6 AND user_id = $7",
+            id,
+            container.name,
+            container.item_id,
+            container.weight_limit,
+            container.volume,
+            trip_id,
+            ctx.user.id
+        )
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RunError::Request(RequestError::NotFound {
+                message: format!("trip with id {trip_id} not found"),
+            }));
+        }
+
+        Ok(id)
+    }
+
+    #[tracing::instrument]
+    pub async fn delete(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        id: Uuid,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "DELETE FROM trip_containers
+            WHERE id = $1 AND trip_id = $2 AND user_id = $3",
+            id,
+            trip_id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Packs the item into the container, or takes it out of any container
+    /// if `container_id` is `None`
+    #[tracing::instrument]
+    pub async fn assign(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Uuid,
+        container_id: Option<Uuid>,
+    ) -> Result<bool, RunError> {
+        if let Some(container_id) = container_id {
+            let Some(container) = Self::findall(ctx, pool, trip_id)
+                .await?
+                .into_iter()
+                .find(|container| container.id == container_id)
+            else {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: "container-id".to_owned(),
+                    message: format!("container with id {container_id} not found for trip"),
+                }));
+            };
+
+            if container.item.is_some_and(|item| item.id == item_id) {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: "container-id".to_owned(),
+                    message: "a container cannot be packed into itself".to_owned(),
+                }));
+            }
+        }
+
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trip_items
+                SET container_id = $1
+                WHERE trip_id = $2
+                AND item_id = $3
+                AND user_id = $4",
+            container_id,
+            trip_id,
+            item_id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewContainer {
+    #[serde(rename = "container-name")]
+    name: String,
+    #[serde(
+        rename = "container-item-id",
+        default,
+        deserialize_with = "uuid_or_empty"
+    )]
+    item_id: Option<Uuid>,
+    #[serde(
+        rename = "container-weight-limit",
+        default,
+        deserialize_with = "parse_or_empty"
+    )]
+    weight_limit: Option<i32>,
+    #[serde(
+        rename = "container-volume",
+        default,
+        deserialize_with = "parse_or_empty"
+    )]
+    volume: Option<i32>,
+}
+
+#[tracing::instrument]
+pub async fn list(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let trip = Trip::load(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        TripParts {
+            categories: true,
+            containers: true,
+            ..TripParts::NONE
+        },
+    )
+    .await?
+    .ok_or_else(|| {
+        RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        })
+    })?;
+
+    Ok(view::ContainerSection::build(&trip))
+}
+
+#[tracing::instrument]
+pub async fn create(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    Form(form): Form<NewContainer>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Err(RunError::Request(RequestError::EmptyFormElement {
+            name: "container-name".to_owned(),
+        }));
+    }
+
+    for (name, value) in [
+        ("container-weight-limit", form.weight_limit),
+        ("container-volume", form.volume),
+    ] {
+        if value.is_some_and(|value| value <= 0) {
+            return Err(RunError::Request(RequestError::InvalidFormElement {
+                name: name.to_owned(),
+                message: "must be positive".to_owned(),
+            }));
+        }
+    }
+
+    Container::create(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        ContainerNew {
+            name: name.to_owned(),
+            item_id: form.item_id,
+            weight_limit: form.weight_limit,
+            volume: form.volume,
+        },
+    )
+    .await?;
+
+    Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+}
+
+#[tracing::instrument]
+pub async fn delete(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, container_id)): Path<(Uuid, Uuid)>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+
+    if Container::delete(&ctx, &state.database_pool, trip_id, container_id).await? {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("container with id {container_id} not found"),
+        }))
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list))
+        .route("/new", post(create))
+        .route("/{id}/delete", post(delete))
+}
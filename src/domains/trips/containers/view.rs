@@ -0,0 +1,268 @@
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use super::Container;
+use crate::domains::trips::model::{Trip, TripItem};
+use crate::htmx;
+
+pub struct ContainerWeight;
+
+impl ContainerWeight {
+    #[tracing::instrument]
+    pub fn build(container: &Container) -> Markup {
+        html!(
+            span
+                ."flex"
+                ."flex-row"
+                ."items-center"
+                ."gap-1"
+                ."text-red-600"[container.excess_weight().is_some()]
+            {
+                @if let Some(excess) = container.excess_weight() {
+                    span
+                        ."mdi"
+                        ."mdi-alert"
+                        ."text-xl"
+                        title={ (excess) " g over the limit" }
+                    {}
+                }
+                span {
+                    (container.weight())
+                    @if let Some(limit) = container.weight_limit {
+                        " / " (limit)
+                    }
+                    " g"
+                }
+            }
+        )
+    }
+}
+
+pub struct ContainerSection;
+
+impl ContainerSection {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip) -> Markup {
+        let containers = trip.containers();
+        let picked_items = trip
+            .categories()
+            .iter()
+            .flat_map(|category| category.items.as_ref().unwrap())
+            .filter(|item| item.picked)
+            .collect::<Vec<_>>();
+
+        html!(
+            div
+                #containers
+                hx-trigger={
+                    (htmx::Event::TripItemEdited.to_str()) " from:body"
+                }
+                hx-get={ "/trips/" (trip.id) "/containers/" }
+                hx-swap="outerHTML"
+                ."flex"
+                ."flex-col"
+                ."gap-5"
+            {
+                h1 ."text-xl" { "Containers" }
+                @for container in containers.iter().filter(|container| container.excess_weight().is_some()) {
+                    p ."text-red-600" {
+                        span ."mdi" ."mdi-alert" ."mr-2" {}
+                        (container.name) " is "
+                        (container.excess_weight().unwrap_or_default())
+                        " g over its limit of "
+                        (container.weight_limit.unwrap_or_default())
+                        " g"
+                    }
+                }
+                table
+                    ."table"
+                    ."table-auto"
+                    ."border-collapse"
+                    ."border-spacing-0"
+                    ."border"
+                    ."w-full"
+                {
+                    thead ."bg-gray-200" {
+                        tr ."h-10" {
+                            th ."border" ."p-2" ."w-8" {}
+                            th ."border" ."p-2" { "Name" }
+                            th ."border" ."p-2" { "Bag" }
+                            th ."border" ."p-2" { "Weight limit (g)" }
+                            th ."border" ."p-2" { "Volume (l)" }
+                            th ."border" ."p-2" { "Items" }
+                            th ."border" ."p-2" { "Weight" }
+                        }
+                    }
+                    tbody {
+                        @for container in containers {
+                            tr ."h-10" {
+                                td ."border" ."p-0" {
+                                    form
+                                        action={ "/trips/" (trip.id) "/containers/" (container.id) "/delete" }
+                                        method="post"
+                                        ."flex"
+                                        ."h-full"
+                                    {
+                                        button
+                                            type="submit"
+                                            title="Delete container"
+                                            ."w-full"
+                                            ."h-full"
+                                            ."p-2"
+                                            ."bg-red-200"
+                                            ."hover:bg-red-400"
+                                        {
+                                            span ."mdi" ."mdi-delete" ."text-xl" {}
+                                        }
+                                    }
+                                }
+                                td ."border" ."p-2" { (container.name) }
+                                td ."border" ."p-2" {
+                                    @match container.item {
+                                        Some(ref item) => { (item.name) " (" (item.weight) " g)" },
+                                        None => span ."text-gray-400" { "[None]" },
+                                    }
+                                }
+                                td ."border" ."p-2" {
+                                    @match container.weight_limit {
+                                        Some(limit) => (limit),
+                                        None => span ."text-gray-400" { "[None]" },
+                                    }
+                                }
+                                td ."border" ."p-2" {
+                                    @match container.volume {
+                                        Some(volume) => (volume),
+                                        None => span ."text-gray-400" { "[None]" },
+                                    }
+                                }
+                                td ."border" ."p-2" { (container.item_count) }
+                                td
+                                    ."border"
+                                    ."p-2"
+                                    ."bg-red-100"[container.excess_weight().is_some()]
+                                {
+                                    (ContainerWeight::build(container))
+                                }
+                            }
+                        }
+                        tr ."h-10" {
+                            td ."border" ."p-0" {
+                                form
+                                    id="new-container"
+                                    action={ "/trips/" (trip.id) "/containers/new" }
+                                    method="post"
+                                {}
+                                button
+                                    type="submit"
+                                    form="new-container"
+                                    title="Add container"
+                                    ."w-full"
+                                    ."h-full"
+                                    ."p-2"
+                                    ."bg-green-200"
+                                    ."hover:bg-green-300"
+                                {
+                                    span ."mdi" ."mdi-plus" ."text-xl" {}
+                                }
+                            }
+                            td ."border" ."p-1" {
+                                input
+                                    type="text"
+                                    form="new-container"
+                                    name="container-name"
+                                    placeholder="Carry-on"
+                                    required
+                                    ."w-full"
+                                    ."bg-blue-100"
+                                    ."hover:bg-white"
+                                {}
+                            }
+                            td ."border" ."p-1" {
+                                select
+                                    form="new-container"
+                                    name="container-item-id"
+                                    ."w-full"
+                                    ."bg-blue-100"
+                                    ."hover:bg-white"
+                                {
+                                    option value="" { "[None]" }
+                                    @for item in &picked_items {
+                                        option value=(item.item.id) { (item.item.name) }
+                                    }
+                                }
+                            }
+                            td ."border" ."p-1" {
+                                input
+                                    type="number"
+                                    form="new-container"
+                                    name="container-weight-limit"
+                                    min="1"
+                                    ."w-full"
+                                    ."bg-blue-100"
+                                    ."hover:bg-white"
+                                {}
+                            }
+                            td ."border" ."p-1" {
+                                input
+                                    type="number"
+                                    form="new-container"
+                                    name="container-volume"
+                                    min="1"
+                                    ."w-full"
+                                    ."bg-blue-100"
+                                    ."hover:bg-white"
+                                {}
+                            }
+                            td ."border" ."p-2" {}
+                            td ."border" ."p-2" {}
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+/// Selection of the container an item is packed into, shown in the item list
+pub struct ContainerSelect;
+
+impl ContainerSelect {
+    #[tracing::instrument]
+    pub fn build(trip_id: Uuid, item: &TripItem, containers: &[Container]) -> Markup {
+        html!(
+            form
+                action={ "/trips/" (trip_id) "/items/" (item.item.id) "/container" }
+                target="_self"
+                method="post"
+                hx-post={ "/trips/" (trip_id) "/items/" (item.item.id) "/container" }
+                hx-target="closest tr"
+                hx-swap="outerHTML"
+                hx-trigger="change"
+                ."px-2"
+                ."pb-1"
+            {
+                select
+                    name="container-id"
+                    aria-label="Container"
+                    ."text-sm"
+                    ."text-gray-500"
+                    ."bg-transparent"
+                    ."hover:bg-white"
+                {
+                    option value="" { "[No container]" }
+                    @for container in containers {
+                        // the bag itself cannot be packed into itself
+                        @if container.item.as_ref().is_none_or(|bag| bag.id != item.item.id) {
+                            option
+                                value=(container.id)
+                                selected[item.container_id == Some(container.id)]
+                            {
+                                (container.name)
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
@@ -0,0 +1,365 @@
+use maud::{Markup, html};
+
+use framework::components::{Render as _, types::Currency};
+
+use super::{Category, CurrencyCode, Expense, Expenses};
+use crate::domains::trips::model::Trip;
+use crate::domains::view;
+
+fn amount(amount: framework::models::Currency) -> Markup {
+    Currency(amount).render()
+}
+
+#[derive(Debug)]
+pub struct List<'a> {
+    pub trip: &'a Trip,
+    pub expenses: &'a Expenses,
+}
+
+#[derive(Debug)]
+pub struct BuildInput {}
+
+impl view::View for List<'_> {
+    type Input = BuildInput;
+
+    #[tracing::instrument]
+    fn build(&self, _input: Self::Input) -> Markup {
+        html!(
+            div #expenselist ."flex" ."flex-col" ."gap-5" {
+                h1 ."text-xl" { "Expenses" }
+                (Participants::build(self.trip, &self.expenses.participants))
+                @if !self.expenses.participants.is_empty() {
+                    table
+                        ."table"
+                        ."table-auto"
+                        ."border-collapse"
+                        ."border-spacing-0"
+                        ."border"
+                        ."w-full"
+                    {
+                        thead ."bg-gray-200" {
+                            tr ."h-10" {
+                                th ."border" ."p-2" ."w-8" {}
+                                th ."border" ."p-2" { "Date" }
+                                th ."border" ."p-2" { "Description" }
+                                th ."border" ."p-2" { "Category" }
+                                th ."border" ."p-2" { "Paid by" }
+                                th ."border" ."p-2" { "Amount" }
+                            }
+                        }
+                        tbody {
+                            @for expense in &self.expenses.expenses {
+                                (ExpenseRow::build(self.trip, expense))
+                            }
+                            (NewExpense::build(self.trip, &self.expenses.participants))
+                        }
+                    }
+                }
+                @if !self.expenses.expenses.is_empty() {
+                    div ."flex" ."flex-row" ."gap-10" {
+                        (Totals::build(self.expenses))
+                        (Settlement::build(self.expenses))
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct Participants;
+
+impl Participants {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip, participants: &[String]) -> Markup {
+        html!(
+            div ."flex" ."flex-row" ."flex-wrap" ."items-center" ."gap-3" {
+                span { "Participants:" }
+                @for participant in participants {
+                    form
+                        action={ "/trips/" (trip.id) "/expenses/participants/delete" }
+                        target="_self"
+                        method="post"
+                        hx-post={ "/trips/" (trip.id) "/expenses/participants/delete" }
+                        hx-target="#expenselist"
+                        hx-swap="outerHTML"
+                        ."flex"
+                        ."flex-row"
+                        ."items-center"
+                        ."gap-1"
+                        ."px-2"
+                        ."py-1"
+                        ."bg-gray-100"
+                        ."rounded"
+                    {
+                        input type="hidden" name="participant-name" value=(participant) {}
+                        span { (participant) }
+                        button
+                            type="submit"
+                            title={ "Remove " (participant) }
+                            ."hover:text-red-600"
+                        {
+                            span ."mdi" ."mdi-close" {}
+                        }
+                    }
+                }
+                form
+                    action={ "/trips/" (trip.id) "/expenses/participants/new" }
+                    target="_self"
+                    method="post"
+                    hx-post={ "/trips/" (trip.id) "/expenses/participants/new" }
+                    hx-target="#expenselist"
+                    hx-swap="outerHTML"
+                    ."flex"
+                    ."flex-row"
+                    ."gap-1"
+                {
+                    input
+                        type="text"
+                        name="participant-name"
+                        placeholder="Name"
+                        required
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {}
+                    button
+                        type="submit"
+                        title="Add participant"
+                        ."px-2"
+                        ."bg-green-200"
+                        ."hover:bg-green-300"
+                    {
+                        span ."mdi" ."mdi-account-plus" {}
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct ExpenseRow;
+
+impl ExpenseRow {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip, expense: &Expense) -> Markup {
+        html!(
+            tr ."h-10" {
+                td ."border" ."p-0" {
+                    form
+                        action={ "/trips/" (trip.id) "/expenses/" (expense.id) "/delete" }
+                        target="_self"
+                        method="post"
+                        hx-post={ "/trips/" (trip.id) "/expenses/" (expense.id) "/delete" }
+                        hx-target="#expenselist"
+                        hx-swap="outerHTML"
+                        ."flex"
+                        ."h-full"
+                    {
+                        button
+                            type="submit"
+                            title="Delete expense"
+                            ."w-full"
+                            ."h-full"
+                            ."p-2"
+                            ."bg-red-200"
+                            ."hover:bg-red-400"
+                        {
+                            span ."mdi" ."mdi-delete" ."text-xl" {}
+                        }
+                    }
+                }
+                td ."border" ."p-2" { (expense.date) }
+                td ."border" ."p-2" {
+                    @match expense.description {
+                        Some(ref description) => (description),
+                        None => span ."text-gray-400" { "[None]" },
+                    }
+                }
+                td ."border" ."p-2" {
+                    div ."flex" ."flex-row" ."items-center" ."gap-1" {
+                        span ."mdi" .(expense.category.icon()) ."text-xl" {}
+                        span { (expense.category) }
+                    }
+                }
+                td ."border" ."p-2" { (expense.payer) }
+                td ."border" ."p-2" ."text-right" { (amount(expense.amount())) }
+            }
+        )
+    }
+}
+
+pub struct NewExpense;
+
+impl NewExpense {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip, participants: &[String]) -> Markup {
+        html!(
+            tr ."h-10" {
+                td ."border" ."p-0" {
+                    form
+                        name="new-expense"
+                        id="new-expense"
+                        action={ "/trips/" (trip.id) "/expenses/new" }
+                        target="_self"
+                        method="post"
+                        hx-post={ "/trips/" (trip.id) "/expenses/new" }
+                        hx-target="#expenselist"
+                        hx-swap="outerHTML"
+                    {}
+                    button
+                        type="submit"
+                        form="new-expense"
+                        ."w-full"
+                        ."h-full"
+                        ."p-2"
+                        ."bg-green-200"
+                        ."hover:bg-green-300"
+                    {
+                        span ."mdi" ."mdi-plus" ."text-xl" {}
+                    }
+                }
+                td ."border" ."p-1" {
+                    input
+                        type="date"
+                        form="new-expense"
+                        name="new-expense-date"
+                        value=(trip.date.start)
+                        required
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                    {}
+                }
+                td ."border" ."p-1" {
+                    input
+                        type="text"
+                        form="new-expense"
+                        name="new-expense-description"
+                        ."w-full"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                    {}
+                }
+                td ."border" ."p-1" {
+                    select
+                        form="new-expense"
+                        name="new-expense-category"
+                        ."w-full"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                    {
+                        @for category in Category::ALL {
+                            option value=(category.id()) { (category) }
+                        }
+                    }
+                }
+                td ."border" ."p-1" {
+                    select
+                        form="new-expense"
+                        name="new-expense-payer"
+                        ."w-full"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                    {
+                        @for participant in participants {
+                            option value=(participant) { (participant) }
+                        }
+                    }
+                }
+                td ."border" ."p-1" {
+                    div ."flex" ."flex-row" ."gap-1" {
+                        input
+                            type="text"
+                            inputmode="decimal"
+                            form="new-expense"
+                            name="new-expense-amount"
+                            placeholder="0.00"
+                            required
+                            ."w-2/3"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                        {}
+                        select
+                            form="new-expense"
+                            name="new-expense-currency"
+                            ."w-1/3"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                        {
+                            @for currency in CurrencyCode::ALL {
+                                option value=(currency.id()) { (currency) }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct Totals;
+
+impl Totals {
+    #[tracing::instrument]
+    pub fn build(expenses: &Expenses) -> Markup {
+        html!(
+            div ."flex" ."flex-col" ."gap-2" {
+                h2 ."text-lg" { "Totals" }
+                table ."table" ."table-auto" ."border-collapse" ."border" {
+                    tbody {
+                        @for (category, totals) in expenses.totals() {
+                            tr ."h-10" {
+                                td ."border" ."p-2" {
+                                    div ."flex" ."flex-row" ."items-center" ."gap-1" {
+                                        span ."mdi" .(category.icon()) ."text-xl" {}
+                                        span { (category) }
+                                    }
+                                }
+                                td ."border" ."p-2" ."text-right" {
+                                    @for (currency, cents) in totals {
+                                        div { (amount(currency.amount(cents))) }
+                                    }
+                                }
+                            }
+                        }
+                        tr ."h-10" ."bg-gray-200" ."font-bold" {
+                            td ."border" ."p-2" { "Total" }
+                            td ."border" ."p-2" ."text-right" {
+                                @for (currency, cents) in expenses.total() {
+                                    div { (amount(currency.amount(cents))) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct Settlement;
+
+impl Settlement {
+    #[tracing::instrument]
+    pub fn build(expenses: &Expenses) -> Markup {
+        let transfers = expenses.settlement();
+        html!(
+            div ."flex" ."flex-col" ."gap-2" {
+                h2 ."text-lg" { "Settlement" }
+                @if transfers.is_empty() {
+                    p ."text-gray-400" { "[Nothing to settle]" }
+                } @else {
+                    ul ."flex" ."flex-col" ."gap-1" {
+                        @for transfer in &transfers {
+                            li ."flex" ."flex-row" ."items-center" ."gap-2" {
+                                span ."font-bold" { (transfer.from) }
+                                span { "owes" }
+                                span ."font-bold" { (transfer.to) }
+                                span { (amount(transfer.amount())) }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
@@ -0,0 +1,1037 @@
+pub mod list;
+pub use list::List;
+
+use std::{collections::BTreeMap, fmt};
+
+use axum::{
+    Extension,
+    body::Body,
+    extract::{Form, Path, State as StateExtractor},
+    http::HeaderMap,
+    response::{IntoResponse, Redirect, Response},
+    routing::post,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState, Context, RequestError,
+    domains::{
+        crud::{self, Read as _},
+        route,
+        view::View as _,
+    },
+    error::RunError,
+    htmx,
+    models::User,
+};
+
+use async_trait::async_trait;
+
+use super::model::Trip;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "expense_category")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Category {
+    Transport,
+    Food,
+    Lodging,
+}
+
+impl Category {
+    pub const ALL: [Self; 3] = [Self::Transport, Self::Food, Self::Lodging];
+
+    #[must_use]
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Transport => "mdi-train-car",
+            Self::Food => "mdi-food-fork-drink",
+            Self::Lodging => "mdi-bed",
+        }
+    }
+
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Transport => "transport",
+            Self::Food => "food",
+            Self::Lodging => "lodging",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Transport => "Transport",
+                Self::Food => "Food",
+                Self::Lodging => "Lodging",
+            }
+        )
+    }
+}
+
+impl std::str::FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.id() == s)
+            .ok_or_else(|| format!("unknown category \"{s}\""))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "currency_code")]
+#[sqlx(rename_all = "lowercase")]
+pub enum CurrencyCode {
+    Eur,
+    Usd,
+    Chf,
+    Gbp,
+}
+
+impl CurrencyCode {
+    pub const ALL: [Self; 4] = [Self::Eur, Self::Usd, Self::Chf, Self::Gbp];
+
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Eur => "eur",
+            Self::Usd => "usd",
+            Self::Chf => "chf",
+            Self::Gbp => "gbp",
+        }
+    }
+
+    /// Amounts are stored in cents
+    #[must_use]
+    pub fn amount(self, cents: i64) -> framework::models::Currency {
+        let amount = Decimal::new(cents, 2);
+        match self {
+            Self::Eur => framework::models::Currency::Eur(amount),
+            Self::Usd => framework::models::Currency::Usd(amount),
+            Self::Chf => framework::models::Currency::Chf(amount),
+            Self::Gbp => framework::models::Currency::Gbp(amount),
+        }
+    }
+}
+
+impl fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id().to_uppercase())
+    }
+}
+
+impl std::str::FromStr for CurrencyCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|currency| currency.id() == s)
+            .ok_or_else(|| format!("unknown currency \"{s}\""))
+    }
+}
+
+#[derive(Debug)]
+pub struct Expense {
+    pub id: Id,
+    pub description: Option<String>,
+    /// In cents of the currency
+    pub amount: i64,
+    pub currency: CurrencyCode,
+    pub payer: String,
+    pub category: Category,
+    pub date: time::Date,
+}
+
+impl Expense {
+    #[must_use]
+    pub fn amount(&self) -> framework::models::Currency {
+        self.currency.amount(self.amount)
+    }
+}
+
+struct ExpenseRow {
+    id: Uuid,
+    description: Option<String>,
+    amount: database::types::Money,
+    currency: CurrencyCode,
+    payer: String,
+    category: Category,
+    date: time::Date,
+}
+
+impl TryFrom<ExpenseRow> for Expense {
+    type Error = RunError;
+
+    fn try_from(row: ExpenseRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Id::new(row.id),
+            description: row.description,
+            amount: row.amount.0,
+            currency: row.currency,
+            payer: row.payer,
+            category: row.category,
+            date: row.date,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Container {
+    pub trip_id: Uuid,
+}
+
+impl crud::Container for Container {
+    type Id = Id;
+    type Reference = Reference;
+
+    fn with_id(&self, id: Self::Id) -> Self::Reference {
+        Reference {
+            id,
+            container: *self,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Reference {
+    pub id: Id,
+    pub container: Container,
+}
+
+impl From<(Uuid, Uuid)> for Reference {
+    fn from((trip_id, expense_id): (Uuid, Uuid)) -> Self {
+        Self {
+            id: Id::new(expense_id),
+            container: Container { trip_id },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Id(Uuid);
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Id {
+    #[must_use]
+    pub fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+#[async_trait]
+impl crud::Read for Expense {
+    type Reference = Reference;
+    type Container = Container;
+
+    #[tracing::instrument]
+    async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Container,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ExpenseRow,
+            Expense,
+            RunError,
+            r#"
+                SELECT
+                    expense.id AS id,
+                    expense.description AS description,
+                    expense.amount AS amount,
+                    expense.currency AS "currency: _",
+                    expense.payer AS payer,
+                    expense.category AS "category: _",
+                    expense.date AS date
+                FROM trip_expenses AS expense
+                INNER JOIN trips
+                    ON trips.id = expense.trip_id
+                WHERE
+                    trips.id = $1
+                    AND trips.user_id = $2
+                ORDER BY expense.date, expense.payer
+            "#,
+            container.trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    async fn find(
+        ctx: &Context,
+        pool: &database::Pool,
+        reference: Reference,
+    ) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ExpenseRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    expense.id AS id,
+                    expense.description AS description,
+                    expense.amount AS amount,
+                    expense.currency AS "currency: _",
+                    expense.payer AS payer,
+                    expense.category AS "category: _",
+                    expense.date AS date
+                FROM trip_expenses AS expense
+                INNER JOIN trips
+                    ON trips.id = expense.trip_id
+                WHERE
+                    trips.id = $1
+                    AND expense.id = $2
+                    AND trips.user_id = $3
+            "#,
+            reference.container.trip_id,
+            reference.id.0,
+            ctx.user.id,
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct ExpenseNew {
+    pub description: Option<String>,
+    /// In cents of the currency
+    pub amount: i64,
+    pub currency: CurrencyCode,
+    pub payer: String,
+    pub category: Category,
+    pub date: time::Date,
+}
+
+#[async_trait]
+impl crud::Create for Expense {
+    type Id = Id;
+    type Container = Container;
+    type Info = ExpenseNew;
+
+    fn new_id() -> Self::Id {
+        Id::new(Uuid::new_v4())
+    }
+
+    #[tracing::instrument]
+    async fn create(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Self::Container,
+        info: Self::Info,
+    ) -> Result<Self::Id, RunError> {
+        let id = Self::new_id();
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            r"
+                INSERT INTO trip_expenses
+                    (id, trip_id, description, amount, currency, payer, category, date)
+                SELECT $1, trips.id, $3, $4, $5, $6, $7, $8
+                FROM trips
+                WHERE trips.id = $2 AND trips.user_id = $9
+            ",
+            id.0,
+            container.trip_id,
+            info.description,
+            sqlx::postgres::types::PgMoney(info.amount),
+            info.currency as _,
+            info.payer,
+            info.category as _,
+            info.date,
+            ctx.user.id,
+        )
+        .await?;
+
+        Ok(id)
+    }
+}
+
+#[async_trait]
+impl crud::Delete for Expense {
+    type Id = Id;
+    type Container = Container;
+    type Reference = Reference;
+
+    #[tracing::instrument]
+    async fn delete<'c, T>(ctx: &Context, db: T, reference: &Reference) -> Result<bool, RunError>
+    where
+        T: sqlx::Acquire<'c, Database = sqlx::Postgres> + Send + std::fmt::Debug,
+    {
+        let mut conn = db.acquire().await?;
+
+        let results = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *conn,
+            RunError,
+            r"
+                DELETE FROM trip_expenses
+                WHERE
+                    id = $1
+                    AND trip_id = $2
+                    AND EXISTS (SELECT 1 FROM trips WHERE id = $2 AND user_id = $3)
+            ",
+            reference.id.0,
+            reference.container.trip_id,
+            ctx.user.id,
+        )
+        .await?;
+
+        Ok(results.rows_affected() != 0)
+    }
+}
+
+/// The people that share the costs of a trip. Every expense is split evenly
+/// among all of them.
+#[derive(Debug)]
+pub struct Participant {
+    pub name: String,
+}
+
+impl TryFrom<Participant> for String {
+    type Error = RunError;
+
+    fn try_from(participant: Participant) -> Result<Self, Self::Error> {
+        Ok(participant.name)
+    }
+}
+
+impl Participant {
+    #[tracing::instrument]
+    pub async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Container,
+    ) -> Result<Vec<String>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            Participant,
+            String,
+            RunError,
+            r"
+                SELECT participant.name AS name
+                FROM trip_participants AS participant
+                INNER JOIN trips
+                    ON trips.id = participant.trip_id
+                WHERE
+                    trips.id = $1
+                    AND trips.user_id = $2
+                ORDER BY participant.name
+            ",
+            container.trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    pub async fn add(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Container,
+        name: &str,
+    ) -> Result<(), RunError> {
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            r"
+                INSERT INTO trip_participants
+                    (trip_id, name)
+                SELECT trips.id, $2
+                FROM trips
+                WHERE trips.id = $1 AND trips.user_id = $3
+                ON CONFLICT DO NOTHING
+            ",
+            container.trip_id,
+            name,
+            ctx.user.id,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Participants that paid for something cannot be removed, as the expenses
+    /// would be lost
+    #[tracing::instrument]
+    pub async fn remove(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Container,
+        name: &str,
+    ) -> Result<bool, RunError> {
+        let paid: bool = database::query_exists!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            r"
+                SELECT expense.id
+                FROM trip_expenses AS expense
+                INNER JOIN trips
+                    ON trips.id = expense.trip_id
+                WHERE
+                    expense.trip_id = $1
+                    AND expense.payer = $2
+                    AND trips.user_id = $3
+            ",
+            container.trip_id,
+            name,
+            ctx.user.id
+        )
+        .await
+        .map_err(|e: sqlx::Error| -> RunError { e.into() })?;
+
+        if paid {
+            return Err(RunError::Request(RequestError::InvalidState {
+                message: format!("{name} still has expenses"),
+            }));
+        }
+
+        let results = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            r"
+                DELETE FROM trip_participants
+                WHERE
+                    trip_id = $1
+                    AND name = $2
+                    AND EXISTS (SELECT 1 FROM trips WHERE id = $1 AND user_id = $3)
+            ",
+            container.trip_id,
+            name,
+            ctx.user.id,
+        )
+        .await?;
+
+        Ok(results.rows_affected() != 0)
+    }
+}
+
+/// A payment from one participant to another that settles the expenses
+#[derive(Debug, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: String,
+    pub to: String,
+    /// In cents of the currency
+    pub amount: i64,
+    pub currency: CurrencyCode,
+}
+
+impl Transfer {
+    #[must_use]
+    pub fn amount(&self) -> framework::models::Currency {
+        self.currency.amount(self.amount)
+    }
+}
+
+#[derive(Debug)]
+pub struct Expenses {
+    pub participants: Vec<String>,
+    pub expenses: Vec<Expense>,
+}
+
+impl Expenses {
+    #[tracing::instrument]
+    pub async fn find(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Container,
+    ) -> Result<Self, RunError> {
+        Ok(Self {
+            participants: Participant::findall(ctx, pool, container).await?,
+            expenses: Expense::findall(ctx, pool, container).await?,
+        })
+    }
+
+    /// Sum of the expenses for each category, separately for each currency
+    #[must_use]
+    pub fn totals(&self) -> BTreeMap<Category, BTreeMap<CurrencyCode, i64>> {
+        let mut totals: BTreeMap<Category, BTreeMap<CurrencyCode, i64>> = BTreeMap::new();
+        for expense in &self.expenses {
+            *totals
+                .entry(expense.category)
+                .or_default()
+                .entry(expense.currency)
+                .or_default() += expense.amount;
+        }
+        totals
+    }
+
+    /// Sum of all expenses, separately for each currency
+    #[must_use]
+    pub fn total(&self) -> BTreeMap<CurrencyCode, i64> {
+        let mut total: BTreeMap<CurrencyCode, i64> = BTreeMap::new();
+        for expense in &self.expenses {
+            *total.entry(expense.currency).or_default() += expense.amount;
+        }
+        total
+    }
+
+    /// Computes who owes whom how much, so that everyone paid the same share.
+    ///
+    /// Currencies are settled separately, as there is no exchange rate. Cents
+    /// that cannot be split evenly are assigned to the first participants.
+    #[must_use]
+    pub fn settlement(&self) -> Vec<Transfer> {
+        let count = i64::try_from(self.participants.len()).unwrap_or(i64::MAX);
+        if count == 0 {
+            return vec![];
+        }
+
+        let mut balances: BTreeMap<CurrencyCode, BTreeMap<&str, i64>> = BTreeMap::new();
+        for expense in &self.expenses {
+            let balance = balances.entry(expense.currency).or_default();
+            *balance.entry(expense.payer.as_str()).or_default() += expense.amount;
+
+            let share = expense.amount / count;
+            let remainder = expense.amount % count;
+            for (index, participant) in (0..).zip(&self.participants) {
+                *balance.entry(participant.as_str()).or_default() -=
+                    share + i64::from(index < remainder);
+            }
+        }
+
+        let mut transfers = vec![];
+        for (currency, balance) in balances {
+            let mut creditors = balance
+                .iter()
+                .filter(|(_, amount)| **amount > 0)
+                .map(|(name, amount)| (*name, *amount))
+                .collect::<Vec<_>>();
+            let mut debtors = balance
+                .iter()
+                .filter(|(_, amount)| **amount < 0)
+                .map(|(name, amount)| (*name, -amount))
+                .collect::<Vec<_>>();
+
+            // settle the biggest amounts first to keep the number of transfers low
+            creditors.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+            debtors.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+
+            let mut creditors = creditors.into_iter().peekable();
+            let mut debtors = debtors.into_iter().peekable();
+            while let (Some((creditor, credit)), Some((debtor, debt))) =
+                (creditors.peek_mut(), debtors.peek_mut())
+            {
+                let amount = (*credit).min(*debt);
+                transfers.push(Transfer {
+                    from: (*debtor).to_owned(),
+                    to: (*creditor).to_owned(),
+                    amount,
+                    currency,
+                });
+                *credit -= amount;
+                *debt -= amount;
+                if *credit == 0 {
+                    creditors.next();
+                }
+                if *debt == 0 {
+                    debtors.next();
+                }
+            }
+        }
+
+        transfers
+    }
+}
+
+/// Converts an amount like "12.5" into cents
+fn parse_amount(input: &str) -> Result<i64, RunError> {
+    let invalid = |message: &str| {
+        RunError::Request(RequestError::InvalidFormElement {
+            name: "amount".to_string(),
+            message: message.to_string(),
+        })
+    };
+
+    let mut amount: Decimal = input
+        .trim()
+        .replace(',', ".")
+        .parse()
+        .map_err(|_| invalid("not a number"))?;
+
+    if amount.scale() > 2 {
+        return Err(invalid("amount cannot have more than two decimal places"));
+    }
+    if amount <= Decimal::ZERO {
+        return Err(invalid("amount has to be positive"));
+    }
+
+    amount.rescale(2);
+    i64::try_from(amount.mantissa()).map_err(|_| invalid("amount is too big"))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TripExpenseNew {
+    #[serde(rename = "new-expense-date")]
+    date: time::Date,
+    #[serde(rename = "new-expense-description")]
+    description: String,
+    #[serde(rename = "new-expense-category")]
+    category: String,
+    #[serde(rename = "new-expense-payer")]
+    payer: String,
+    #[serde(rename = "new-expense-amount")]
+    amount: String,
+    #[serde(rename = "new-expense-currency")]
+    currency: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TripParticipant {
+    #[serde(rename = "participant-name")]
+    name: String,
+}
+
+#[tracing::instrument]
+async fn expense_list(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+) -> Result<Response<Body>, crate::RunError> {
+    let trip = Trip::find(ctx, &state.database_pool, trip_id).await?;
+    match trip {
+        None => Err(crate::RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        })),
+        Some(mut trip) => {
+            trip.load_expenses(ctx, &state.database_pool).await?;
+            Ok(list::List {
+                trip: &trip,
+                expenses: trip.expenses(),
+            }
+            .build(list::BuildInput {})
+            .into_response())
+        }
+    }
+}
+
+#[tracing::instrument]
+async fn respond(
+    ctx: &Context,
+    state: &AppState,
+    headers: &HeaderMap,
+    trip_id: Uuid,
+) -> Result<Response<Body>, crate::RunError> {
+    if htmx::is_htmx(headers) {
+        expense_list(ctx, state, trip_id).await
+    } else {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+    }
+}
+
+#[async_trait]
+impl route::Create for Expense {
+    type Form = TripExpenseNew;
+    type UrlParams = (Uuid,);
+
+    #[tracing::instrument]
+    async fn create(
+        Extension(current_user): Extension<User>,
+        StateExtractor(state): StateExtractor<AppState>,
+        headers: HeaderMap,
+        Path((trip_id,)): Path<Self::UrlParams>,
+        Form(form): Form<Self::Form>,
+    ) -> Result<Response<Body>, crate::RunError> {
+        let ctx = Context::build(current_user);
+
+        let invalid = |name: &str, message: String| {
+            crate::RunError::Request(RequestError::InvalidFormElement {
+                name: name.to_string(),
+                message,
+            })
+        };
+
+        let category = form
+            .category
+            .parse::<Category>()
+            .map_err(|e| invalid("category", e))?;
+        let currency = form
+            .currency
+            .parse::<CurrencyCode>()
+            .map_err(|e| invalid("currency", e))?;
+        let amount = parse_amount(&form.amount)?;
+
+        let participants =
+            Participant::findall(&ctx, &state.database_pool, Container { trip_id }).await?;
+        if !participants.contains(&form.payer) {
+            return Err(invalid(
+                "payer",
+                format!("{} is not a participant of the trip", form.payer),
+            ));
+        }
+
+        let _expense_id = <Self as crud::Create>::create(
+            &ctx,
+            &state.database_pool,
+            Container { trip_id },
+            ExpenseNew {
+                description: Some(form.description).filter(|description| !description.is_empty()),
+                amount,
+                currency,
+                payer: form.payer,
+                category,
+                date: form.date,
+            },
+        )
+        .await?;
+
+        respond(&ctx, &state, &headers, trip_id).await
+    }
+}
+
+#[async_trait]
+impl route::Delete for Expense {
+    type UrlParams = (Uuid, Uuid);
+
+    #[tracing::instrument]
+    async fn delete(
+        Extension(current_user): Extension<User>,
+        StateExtractor(state): StateExtractor<AppState>,
+        headers: HeaderMap,
+        Path((trip_id, expense_id)): Path<Self::UrlParams>,
+    ) -> Result<Response<Body>, crate::RunError> {
+        let ctx = Context::build(current_user);
+        let deleted = <Self as crud::Delete>::delete(
+            &ctx,
+            &state.database_pool,
+            &(trip_id, expense_id).into(),
+        )
+        .await?;
+
+        if !deleted {
+            return Err(crate::RunError::Request(RequestError::NotFound {
+                message: format!("expense with id {expense_id} not found"),
+            }));
+        }
+
+        respond(&ctx, &state, &headers, trip_id).await
+    }
+}
+
+#[tracing::instrument]
+async fn add_participant(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path((trip_id,)): Path<(Uuid,)>,
+    Form(form): Form<TripParticipant>,
+) -> Result<Response<Body>, crate::RunError> {
+    let ctx = Context::build(current_user);
+
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Err(crate::RunError::Request(RequestError::EmptyFormElement {
+            name: "name".to_string(),
+        }));
+    }
+
+    Participant::add(&ctx, &state.database_pool, Container { trip_id }, name).await?;
+
+    respond(&ctx, &state, &headers, trip_id).await
+}
+
+#[tracing::instrument]
+async fn remove_participant(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path((trip_id,)): Path<(Uuid,)>,
+    Form(form): Form<TripParticipant>,
+) -> Result<Response<Body>, crate::RunError> {
+    let ctx = Context::build(current_user);
+
+    if !Participant::remove(
+        &ctx,
+        &state.database_pool,
+        Container { trip_id },
+        &form.name,
+    )
+    .await?
+    {
+        return Err(crate::RunError::Request(RequestError::NotFound {
+            message: format!("participant {} not found", form.name),
+        }));
+    }
+
+    respond(&ctx, &state, &headers, trip_id).await
+}
+
+impl route::Router for Expense {
+    fn router() -> axum::Router<AppState> {
+        axum::Router::new()
+            .route("/new", post(<Self as route::Create>::create))
+            .route("/{id}/delete", post(<Self as route::Delete>::delete))
+            .route("/participants/new", post(add_participant))
+            .route("/participants/delete", post(remove_participant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(payer: &str, amount: i64, currency: CurrencyCode) -> Expense {
+        Expense {
+            id: Id(Uuid::new_v4()),
+            description: None,
+            amount,
+            currency,
+            payer: payer.to_owned(),
+            category: Category::Food,
+            date: time::macros::date!(2026 - 01 - 01),
+        }
+    }
+
+    fn expenses(participants: &[&str], expenses: Vec<Expense>) -> Expenses {
+        Expenses {
+            participants: participants.iter().map(|&name| name.to_owned()).collect(),
+            expenses,
+        }
+    }
+
+    fn transfer(from: &str, to: &str, amount: i64, currency: CurrencyCode) -> Transfer {
+        Transfer {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            amount,
+            currency,
+        }
+    }
+
+    #[test]
+    fn settlement_without_participants() {
+        let expenses = expenses(&[], vec![expense("anna", 1000, CurrencyCode::Eur)]);
+        assert_eq!(expenses.settlement(), vec![]);
+    }
+
+    #[test]
+    fn settlement_single_payer() {
+        let expenses = expenses(
+            &["anna", "ben", "carl"],
+            vec![expense("anna", 3000, CurrencyCode::Eur)],
+        );
+        assert_eq!(
+            expenses.settlement(),
+            vec![
+                transfer("ben", "anna", 1000, CurrencyCode::Eur),
+                transfer("carl", "anna", 1000, CurrencyCode::Eur),
+            ]
+        );
+    }
+
+    #[test]
+    fn settlement_remainder_goes_to_first_participants() {
+        // 10.00 split three ways: anna and ben carry one cent more than carl
+        let expenses = expenses(
+            &["anna", "ben", "carl"],
+            vec![expense("carl", 1000, CurrencyCode::Eur)],
+        );
+        assert_eq!(
+            expenses.settlement(),
+            vec![
+                transfer("anna", "carl", 334, CurrencyCode::Eur),
+                transfer("ben", "carl", 333, CurrencyCode::Eur),
+            ]
+        );
+    }
+
+    #[test]
+    fn settlement_currencies_are_separate() {
+        let expenses = expenses(
+            &["anna", "ben"],
+            vec![
+                expense("anna", 2000, CurrencyCode::Eur),
+                expense("ben", 500, CurrencyCode::Usd),
+                expense("ben", 1000, CurrencyCode::Eur),
+            ],
+        );
+        assert_eq!(
+            expenses.settlement(),
+            vec![
+                transfer("ben", "anna", 500, CurrencyCode::Eur),
+                transfer("anna", "ben", 250, CurrencyCode::Usd),
+            ]
+        );
+    }
+
+    #[test]
+    fn settlement_balanced() {
+        let expenses = expenses(
+            &["anna", "ben"],
+            vec![
+                expense("anna", 1500, CurrencyCode::Chf),
+                expense("ben", 1500, CurrencyCode::Chf),
+            ],
+        );
+        assert_eq!(expenses.settlement(), vec![]);
+    }
+
+    #[test]
+    fn parse_amount_valid() {
+        assert_eq!(parse_amount("12").unwrap(), 1200);
+        assert_eq!(parse_amount("12.5").unwrap(), 1250);
+        assert_eq!(parse_amount(" 12,05 ").unwrap(), 1205);
+        assert_eq!(parse_amount("0.01").unwrap(), 1);
+    }
+
+    #[test]
+    fn parse_amount_invalid() {
+        for input in ["12.500", "0.001", "0", "0.00", "-5", "-0.01", "abc", ""] {
+            assert!(
+                matches!(
+                    parse_amount(input),
+                    Err(RunError::Request(RequestError::InvalidFormElement { .. }))
+                ),
+                "{input:?} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,551 @@
+//! Plain text exports of a trip, meant to be pasted into chats and notes.
+
+use std::{borrow::Cow, fmt::Write as _};
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::header,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{AppState, Context, RequestError, RunError, models::User};
+
+use super::{
+    model::{Trip, TripParts},
+    todos,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+}
+
+impl ExportFormat {
+    #[must_use]
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Text => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// Escapes user provided text, so names like `*nix tools` or `[spare]` are not
+/// rendered as Markdown
+fn inline(format: ExportFormat, text: &str) -> Cow<'_, str> {
+    const SPECIAL: [char; 11] = ['\\', '`', '*', '_', '[', ']', '<', '>', '#', '|', '~'];
+
+    match format {
+        ExportFormat::Markdown if text.contains(SPECIAL) => {
+            let mut escaped = String::with_capacity(text.len() + 8);
+            for c in text.chars() {
+                if SPECIAL.contains(&c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            Cow::Owned(escaped)
+        }
+        ExportFormat::Markdown | ExportFormat::Text => Cow::Borrowed(text),
+    }
+}
+
+/// Small helper to emit the structural elements in the respective format
+struct Writer {
+    format: ExportFormat,
+    out: String,
+}
+
+impl Writer {
+    fn heading(&mut self, level: usize, text: &str) {
+        match self.format {
+            ExportFormat::Markdown => {
+                let _ = writeln!(self.out, "{} {text}\n", "#".repeat(level));
+            }
+            ExportFormat::Text => {
+                let underline = if level == 1 { "=" } else { "-" };
+                let _ = writeln!(
+                    self.out,
+                    "{text}\n{}\n",
+                    underline.repeat(text.chars().count())
+                );
+            }
+        }
+    }
+
+    fn field(&mut self, label: &str, value: &str) {
+        match self.format {
+            ExportFormat::Markdown => {
+                let _ = writeln!(self.out, "- **{label}:** {value}");
+            }
+            ExportFormat::Text => {
+                let _ = writeln!(self.out, "{label}: {value}");
+            }
+        }
+    }
+
+    fn checkbox(&mut self, indent: bool, checked: bool, text: &str) {
+        let mark = if checked { "x" } else { " " };
+        match self.format {
+            ExportFormat::Markdown => {
+                let _ = writeln!(self.out, "- [{mark}] {text}");
+            }
+            ExportFormat::Text => {
+                let indent = if indent { "  " } else { "" };
+                let _ = writeln!(self.out, "{indent}[{mark}] {text}");
+            }
+        }
+    }
+
+    fn list_item(&mut self, text: &str) {
+        let _ = writeln!(self.out, "- {text}");
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        let _ = writeln!(self.out, "{text}\n");
+    }
+
+    fn blank(&mut self) {
+        self.out.push('\n');
+    }
+}
+
+/// Renders the trip. Requires categories, todos, types and stages to be loaded.
+#[must_use]
+pub fn render(trip: &Trip, format: ExportFormat) -> String {
+    let mut writer = Writer {
+        format,
+        out: String::new(),
+    };
+
+    writer.heading(1, &inline(format, &trip.name));
+
+    writer.field("Date", &trip.date.to_string());
+    writer.field("State", &trip.state.to_string());
+    if let Some(ref location) = trip.location {
+        writer.field("Location", &inline(format, location));
+    }
+    match (trip.temp_min, trip.temp_max) {
+        (Some(min), Some(max)) => writer.field("Temperature", &format!("{min} – {max} °C")),
+        (Some(min), None) => writer.field("Temperature", &format!("≥ {min} °C")),
+        (None, Some(max)) => writer.field("Temperature", &format!("≤ {max} °C")),
+        (None, None) => {}
+    }
+    let types = trip
+        .types()
+        .iter()
+        .filter(|trip_type| trip_type.active)
+        .map(|trip_type| inline(format, &trip_type.name))
+        .collect::<Vec<_>>();
+    if !types.is_empty() {
+        writer.field("Types", &types.join(", "));
+    }
+    writer.field("Total weight", &format!("{} g", trip.total_picked_weight()));
+    writer.blank();
+
+    if let Some(comment) = trip.comment.as_ref().filter(|comment| !comment.is_empty()) {
+        writer.heading(2, "Comment");
+        // free text, any Markdown in there is meant to be rendered
+        writer.paragraph(comment);
+    }
+
+    if !trip.stages().is_empty() {
+        writer.heading(2, "Stages");
+        for stage in trip.stages() {
+            let mut line = stage.date.to_string();
+            if let Some(ref location) = stage.location {
+                let _ = write!(line, ": {}", inline(format, location));
+            }
+            if let Some(accommodation) = stage.accommodation {
+                let _ = write!(line, " ({accommodation})");
+            }
+            writer.list_item(&line);
+        }
+        writer.blank();
+    }
+
+    if !trip.todos().is_empty() {
+        writer.heading(2, "Todos");
+        for todo in trip.todos() {
+            writer.checkbox(
+                false,
+                todo.state == todos::State::Done,
+                &inline(format, &todo.description),
+            );
+        }
+        writer.blank();
+    }
+
+    writer.heading(2, "Packing list");
+    for category in trip.categories() {
+        let items = category
+            .items
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter(|item| item.picked)
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            continue;
+        }
+
+        match format {
+            ExportFormat::Markdown => writer.heading(
+                3,
+                &format!(
+                    "{} ({} g)",
+                    inline(format, &category.category.name),
+                    category.total_picked_weight()
+                ),
+            ),
+            ExportFormat::Text => {
+                let _ = writeln!(
+                    writer.out,
+                    "{} ({} g)",
+                    category.category.name,
+                    category.total_picked_weight()
+                );
+            }
+        }
+
+        for item in items {
+            let mut line = if item.quantity > 1 {
+                format!(
+                    "{} × {} ({} g)",
+                    item.quantity,
+                    inline(format, &item.item.name),
+                    item.weight()
+                )
+            } else {
+                format!("{} ({} g)", inline(format, &item.item.name), item.weight())
+            };
+            if !item.packed {
+                line.push_str(if item.ready { ", ready" } else { ", not ready" });
+            }
+            if let Some(ref note) = item.note {
+                let _ = write!(line, " – {}", inline(format, note));
+            }
+            writer.checkbox(true, item.packed, &line);
+        }
+        writer.blank();
+    }
+
+    writer.out
+}
+
+/// Loads everything that is required for [`render()`]
+#[tracing::instrument]
+pub async fn export(
+    pool: &database::Pool,
+    user: User,
+    trip_id: Uuid,
+    format: ExportFormat,
+) -> Result<Option<String>, RunError> {
+    let ctx = Context::build(user);
+
+    let parts = TripParts {
+        types: true,
+        todos: true,
+        stages: true,
+        categories: true,
+        ..TripParts::NONE
+    };
+
+    let Some(trip) = Trip::load(&ctx, pool, trip_id, parts).await? else {
+        return Ok(None);
+    };
+
+    Ok(Some(render(&trip, format)))
+}
+
+#[tracing::instrument]
+async fn respond(
+    current_user: User,
+    state: &AppState,
+    trip_id: Uuid,
+    format: ExportFormat,
+) -> Result<impl IntoResponse + use<>, RunError> {
+    let output = export(&state.database_pool, current_user, trip_id, format)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    Ok(([(header::CONTENT_TYPE, format.content_type())], output))
+}
+
+#[tracing::instrument]
+pub async fn markdown(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    respond(current_user, &state, trip_id, ExportFormat::Markdown).await
+}
+
+#[tracing::instrument]
+pub async fn text(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    respond(current_user, &state, trip_id, ExportFormat::Text).await
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+    use crate::domains::{
+        inventory,
+        trips::model::{TripCategory, TripDate, TripItem, TripState, TripType},
+    };
+
+    fn item(name: &str, weight: i32) -> TripItem {
+        TripItem {
+            item: inventory::Item {
+                id: Uuid::new_v4(),
+                name: name.to_owned(),
+                description: None,
+                weight,
+                category_id: Uuid::nil(),
+            },
+            picked: true,
+            packed: false,
+            ready: false,
+            worn: false,
+            new: false,
+            note: None,
+            quantity: 1,
+            container_id: None,
+        }
+    }
+
+    fn category(name: &str, items: Vec<TripItem>) -> TripCategory {
+        TripCategory {
+            category: inventory::Category {
+                id: Uuid::new_v4(),
+                name: name.to_owned(),
+                items: None,
+            },
+            items: Some(items),
+        }
+    }
+
+    fn trip(categories: Vec<TripCategory>) -> Trip {
+        Trip {
+            id: Uuid::new_v4(),
+            name: "Alps".to_owned(),
+            date: TripDate {
+                start: date!(2026 - 07 - 01),
+                end: date!(2026 - 07 - 05),
+            },
+            state: TripState::Planned,
+            location: Some("Zermatt".to_owned()),
+            temp_min: Some(-5),
+            temp_max: Some(15),
+            comment: None,
+            todo_order: todos::Order::Manual,
+            archived: false,
+            todos: Some(vec![]),
+            types: Some(vec![
+                TripType {
+                    id: Uuid::new_v4(),
+                    name: "Climbing".to_owned(),
+                    active: true,
+                },
+                TripType {
+                    id: Uuid::new_v4(),
+                    name: "Beach".to_owned(),
+                    active: false,
+                },
+            ]),
+            categories: Some(categories),
+            state_history: None,
+            stages: Some(vec![]),
+            review_hints: None,
+            expenses: None,
+            containers: None,
+            tracks: None,
+        }
+    }
+
+    fn todo(description: &str, state: todos::State) -> todos::Todo {
+        todos::Todo {
+            id: todos::Id::new(Uuid::new_v4()),
+            description: description.to_owned(),
+            state,
+            due: None,
+            due_at: None,
+            overdue: false,
+            assignee: None,
+        }
+    }
+
+    fn packing_trip() -> Trip {
+        let mut trip = trip(vec![
+            category(
+                "Climbing",
+                vec![
+                    TripItem {
+                        packed: true,
+                        ..item("Helmet", 350)
+                    },
+                    TripItem {
+                        ready: true,
+                        note: Some("borrow the larger one".to_owned()),
+                        ..item("Harness", 400)
+                    },
+                    TripItem {
+                        picked: false,
+                        ..item("Rope", 3000)
+                    },
+                ],
+            ),
+            category(
+                "Clothing",
+                vec![TripItem {
+                    quantity: 3,
+                    ..item("Socks", 50)
+                }],
+            ),
+            category(
+                "Cooking",
+                vec![TripItem {
+                    picked: false,
+                    ..item("Stove", 300)
+                }],
+            ),
+        ]);
+        trip.todos = Some(vec![
+            todo("Book the hut", todos::State::Done),
+            todo("Check the weather", todos::State::Todo),
+        ]);
+        trip
+    }
+
+    #[test]
+    fn render_markdown() {
+        assert_eq!(
+            render(&packing_trip(), ExportFormat::Markdown),
+            "\
+# Alps
+
+- **Date:** 2026-07-01 - 2026-07-05
+- **State:** Planned
+- **Location:** Zermatt
+- **Temperature:** -5 – 15 °C
+- **Types:** Climbing
+- **Total weight:** 900 g
+
+## Todos
+
+- [x] Book the hut
+- [ ] Check the weather
+
+## Packing list
+
+### Climbing (750 g)
+
+- [x] Helmet (350 g)
+- [ ] Harness (400 g), ready – borrow the larger one
+
+### Clothing (150 g)
+
+- [ ] 3 × Socks (150 g), not ready
+
+"
+        );
+    }
+
+    #[test]
+    fn render_text() {
+        assert_eq!(
+            render(&packing_trip(), ExportFormat::Text),
+            "\
+Alps
+====
+
+Date: 2026-07-01 - 2026-07-05
+State: Planned
+Location: Zermatt
+Temperature: -5 – 15 °C
+Types: Climbing
+Total weight: 900 g
+
+Todos
+-----
+
+[x] Book the hut
+[ ] Check the weather
+
+Packing list
+------------
+
+Climbing (750 g)
+  [x] Helmet (350 g)
+  [ ] Harness (400 g), ready – borrow the larger one
+
+Clothing (150 g)
+  [ ] 3 × Socks (150 g), not ready
+
+"
+        );
+    }
+
+    #[test]
+    fn render_without_picked_items() {
+        let output = render(
+            &trip(vec![category("Climbing", vec![])]),
+            ExportFormat::Markdown,
+        );
+        assert!(output.ends_with("## Packing list\n\n"), "{output}");
+        assert!(!output.contains("## Todos"), "{output}");
+    }
+
+    #[test]
+    fn markdown_escapes_user_text() {
+        let mut trip = trip(vec![category(
+            "Tools_and_stuff",
+            vec![TripItem {
+                note: Some("the <b>big</b> one".to_owned()),
+                ..item("*nix [spare] knife #2", 80)
+            }],
+        )]);
+        trip.name = "Alps *2026*".to_owned();
+
+        let output = render(&trip, ExportFormat::Markdown);
+        assert!(output.starts_with("# Alps \\*2026\\*\n"), "{output}");
+        assert!(
+            output.contains("### Tools\\_and\\_stuff (80 g)"),
+            "{output}"
+        );
+        assert!(
+            output.contains(
+                "- [ ] \\*nix \\[spare\\] knife \\#2 (80 g), not ready – the \\<b\\>big\\</b\\> one"
+            ),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn text_does_not_escape() {
+        let trip = trip(vec![category(
+            "Tools",
+            vec![TripItem {
+                note: Some("the <b>big</b> one".to_owned()),
+                ..item("*nix [spare] knife", 80)
+            }],
+        )]);
+
+        let output = render(&trip, ExportFormat::Text);
+        assert!(
+            output.contains("  [ ] *nix [spare] knife (80 g), not ready – the <b>big</b> one"),
+            "{output}"
+        );
+    }
+}
@@ -1,20 +1,21 @@
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Form, Path, RawForm, State},
     http::header::{HeaderMap, HeaderName},
-    response::{IntoResponse, Redirect},
-    routing::get,
+    response::{IntoResponse, Redirect, Response},
+    routing::post,
     Router,
 };
 
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    htmx,
+    auth, htmx,
     routing::{get_referer, html},
     AppState, Context, RunError, RequestError,
 };
 
-use super::{model, view};
+use super::{containers, model, review, view};
 use crate::models::User;
 
 #[tracing::instrument]
@@ -32,6 +33,11 @@ async fn trip_row(
             })
         })?;
 
+    let review_hints =
+        review::ReviewHint::findall(ctx, &state.database_pool, trip_id, Some(item_id)).await?;
+
+    let containers = containers::Container::findall(ctx, &state.database_pool, trip_id).await?;
+
     let item_row = view::TripItemListRow::build(
         trip_id,
         &item,
@@ -41,6 +47,8 @@ async fn trip_row(
             item.item.category_id,
         )
         .await?,
+        review_hints.first(),
+        &containers,
     );
 
     let category =
@@ -334,30 +342,535 @@ async fn set_item_unready_htmx(
     Ok((headers, trip_row(&ctx, &state, trip_id, item_id).await?))
 }
 
+#[tracing::instrument]
+async fn set_state_and_redirect(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+    item_id: Uuid,
+    key: model::TripItemStateKey,
+    value: bool,
+    headers: &HeaderMap,
+) -> Result<Redirect, RunError> {
+    model::trip_item_set_state(ctx, &state.database_pool, trip_id, item_id, key, value).await?;
+    Ok(Redirect::to(get_referer(headers)?))
+}
+
+#[tracing::instrument]
+async fn set_state_and_render_row(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+    item_id: Uuid,
+    key: model::TripItemStateKey,
+    value: bool,
+) -> Result<impl IntoResponse + use<>, RunError> {
+    model::trip_item_set_state(ctx, &state.database_pool, trip_id, item_id, key, value).await?;
+    let mut headers = HeaderMap::new();
+    headers.insert::<HeaderName>(
+        htmx::ResponseHeaders::Trigger.into(),
+        htmx::Event::TripItemEdited.into(),
+    );
+    Ok((headers, trip_row(ctx, state, trip_id, item_id).await?))
+}
+
+#[tracing::instrument]
+async fn set_item_wear(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    set_state_and_redirect(
+        &ctx,
+        &state,
+        trip_id,
+        item_id,
+        model::TripItemStateKey::Wear,
+        true,
+        &headers,
+    )
+    .await
+}
+
+#[tracing::instrument]
+async fn set_item_wear_htmx(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    set_state_and_render_row(
+        &ctx,
+        &state,
+        trip_id,
+        item_id,
+        model::TripItemStateKey::Wear,
+        true,
+    )
+    .await
+}
+
+#[tracing::instrument]
+async fn set_item_unwear(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    set_state_and_redirect(
+        &ctx,
+        &state,
+        trip_id,
+        item_id,
+        model::TripItemStateKey::Wear,
+        false,
+        &headers,
+    )
+    .await
+}
+
+#[tracing::instrument]
+async fn set_item_unwear_htmx(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    set_state_and_render_row(
+        &ctx,
+        &state,
+        trip_id,
+        item_id,
+        model::TripItemStateKey::Wear,
+        false,
+    )
+    .await
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct NoteUpdate {
+    #[serde(rename = "item-note")]
+    note: String,
+}
+
+#[tracing::instrument]
+async fn set_item_note(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Form(note_update): Form<NoteUpdate>,
+) -> Result<Response, RunError> {
+    let ctx = Context::build(current_user);
+    let note = Some(note_update.note.trim()).filter(|note| !note.is_empty());
+
+    let found =
+        model::TripItem::set_note(&ctx, &state.database_pool, trip_id, item_id, note).await?;
+    if !found {
+        return Err(RunError::Request(RequestError::NotFound {
+            message: format!("item with id {item_id} not found for trip {trip_id}"),
+        }));
+    }
+
+    if htmx::is_htmx(&headers) {
+        Ok(trip_row(&ctx, &state, trip_id, item_id)
+            .await?
+            .into_response())
+    } else {
+        Ok(Redirect::to(get_referer(&headers)?).into_response())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct QuantityUpdate {
+    #[serde(rename = "item-quantity")]
+    quantity: i32,
+}
+
+#[tracing::instrument]
+async fn set_item_quantity(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Form(quantity_update): Form<QuantityUpdate>,
+) -> Result<Response, RunError> {
+    let ctx = Context::build(current_user);
+
+    if quantity_update.quantity < 1 {
+        return Err(RunError::Request(RequestError::InvalidFormElement {
+            name: "item-quantity".to_owned(),
+            message: "quantity has to be at least 1".to_owned(),
+        }));
+    }
+
+    let found = model::TripItem::set_quantity(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        item_id,
+        quantity_update.quantity,
+    )
+    .await?;
+    if !found {
+        return Err(RunError::Request(RequestError::NotFound {
+            message: format!("item with id {item_id} not found for trip {trip_id}"),
+        }));
+    }
+
+    if htmx::is_htmx(&headers) {
+        Ok(trip_row(&ctx, &state, trip_id, item_id)
+            .await?
+            .into_response())
+    } else {
+        Ok(Redirect::to(get_referer(&headers)?).into_response())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ContainerUpdate {
+    #[serde(
+        rename = "container-id",
+        default,
+        deserialize_with = "crate::routing::uuid_or_empty"
+    )]
+    container_id: Option<Uuid>,
+}
+
+#[tracing::instrument]
+async fn set_item_container(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Form(container_update): Form<ContainerUpdate>,
+) -> Result<Response, RunError> {
+    let ctx = Context::build(current_user);
+
+    let found = containers::Container::assign(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        item_id,
+        container_update.container_id,
+    )
+    .await?;
+    if !found {
+        return Err(RunError::Request(RequestError::NotFound {
+            message: format!("item with id {item_id} not found for trip {trip_id}"),
+        }));
+    }
+
+    if htmx::is_htmx(&headers) {
+        // the weights of the containers change
+        let mut response_headers = HeaderMap::new();
+        response_headers.insert::<HeaderName>(
+            htmx::ResponseHeaders::Trigger.into(),
+            htmx::Event::TripItemEdited.into(),
+        );
+        Ok((
+            response_headers,
+            trip_row(&ctx, &state, trip_id, item_id).await?,
+        )
+            .into_response())
+    } else {
+        Ok(Redirect::to(get_referer(&headers)?).into_response())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NewItemAction {
+    Pick,
+    Dismiss,
+}
+
+#[tracing::instrument]
+async fn review_new_items(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+    item_id: Option<Uuid>,
+    action: NewItemAction,
+    headers: &HeaderMap,
+) -> Result<Redirect, RunError> {
+    let changed = model::TripItem::clear_new(
+        ctx,
+        &state.database_pool,
+        trip_id,
+        item_id,
+        matches!(action, NewItemAction::Pick),
+    )
+    .await?;
+
+    if let Some(item_id) = item_id
+        && changed == 0
+    {
+        return Err(RunError::Request(RequestError::NotFound {
+            message: format!("new item with id {item_id} not found for trip {trip_id}"),
+        }));
+    }
+
+    Ok(Redirect::to(get_referer(headers)?))
+}
+
+#[tracing::instrument]
+async fn pick_new_item(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    review_new_items(
+        &ctx,
+        &state,
+        trip_id,
+        Some(item_id),
+        NewItemAction::Pick,
+        &headers,
+    )
+    .await
+}
+
+#[tracing::instrument]
+async fn dismiss_new_item(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    review_new_items(
+        &ctx,
+        &state,
+        trip_id,
+        Some(item_id),
+        NewItemAction::Dismiss,
+        &headers,
+    )
+    .await
+}
+
+#[tracing::instrument]
+async fn pick_all_new_items(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    review_new_items(&ctx, &state, trip_id, None, NewItemAction::Pick, &headers).await
+}
+
+#[tracing::instrument]
+async fn dismiss_all_new_items(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    review_new_items(
+        &ctx,
+        &state,
+        trip_id,
+        None,
+        NewItemAction::Dismiss,
+        &headers,
+    )
+    .await
+}
+
+#[derive(Debug)]
+struct BulkUpdate {
+    key: model::TripItemStateKey,
+    value: bool,
+    category_id: Uuid,
+    /// Only these items of the category, if given
+    item_ids: Option<Vec<Uuid>>,
+}
+
+impl BulkUpdate {
+    /// This cannot use `Form`, as the selected items are sent as repeated
+    /// `item-id` fields
+    fn parse(body: &[u8]) -> Result<Self, RunError> {
+        let invalid = |name: &str, message: String| {
+            RunError::Request(RequestError::InvalidFormElement {
+                name: name.to_owned(),
+                message,
+            })
+        };
+
+        let mut action = None;
+        let mut category_id = None;
+        let mut item_ids: Option<Vec<Uuid>> = None;
+
+        for (name, value) in url::form_urlencoded::parse(body) {
+            match name.as_ref() {
+                "bulk-action" => {
+                    action = Some(match value.as_ref() {
+                        "pick" => (model::TripItemStateKey::Pick, true),
+                        "unpick" => (model::TripItemStateKey::Pick, false),
+                        "pack" => (model::TripItemStateKey::Pack, true),
+                        "unpack" => (model::TripItemStateKey::Pack, false),
+                        "ready" => (model::TripItemStateKey::Ready, true),
+                        "unready" => (model::TripItemStateKey::Ready, false),
+                        other => {
+                            return Err(invalid("bulk-action", format!("unknown action {other}")));
+                        }
+                    });
+                }
+                "category-id" => {
+                    category_id = Some(
+                        Uuid::parse_str(&value)
+                            .map_err(|error| invalid("category-id", error.to_string()))?,
+                    );
+                }
+                "item-id" => {
+                    let item_id = Uuid::parse_str(&value)
+                        .map_err(|error| invalid("item-id", error.to_string()))?;
+                    let item_ids = item_ids.get_or_insert_with(Vec::new);
+                    if !item_ids.contains(&item_id) {
+                        item_ids.push(item_id);
+                    }
+                }
+                other => return Err(invalid(other, "unknown form element".to_owned())),
+            }
+        }
+
+        let Some((key, value)) = action else {
+            return Err(RunError::Request(RequestError::EmptyFormElement {
+                name: "bulk-action".to_owned(),
+            }));
+        };
+        let Some(category_id) = category_id else {
+            return Err(RunError::Request(RequestError::EmptyFormElement {
+                name: "category-id".to_owned(),
+            }));
+        };
+
+        Ok(Self {
+            key,
+            value,
+            category_id,
+            item_ids,
+        })
+    }
+}
+
+/// Changes the state of a whole category, or of the selected items of it, at
+/// once
+#[tracing::instrument]
+async fn set_items_bulk(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    headers: HeaderMap,
+    RawForm(body): RawForm,
+) -> Result<Response, RunError> {
+    let ctx = Context::build(current_user);
+    let BulkUpdate {
+        key,
+        value,
+        category_id,
+        item_ids,
+    } = BulkUpdate::parse(&body)?;
+
+    let mut trip = model::Trip::find(&ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or_else(|| {
+            RunError::Request(RequestError::NotFound {
+                message: format!("trip with id {trip_id} not found"),
+            })
+        })?;
+
+    model::TripItem::set_state_bulk(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        category_id,
+        item_ids.as_deref(),
+        key,
+        value,
+    )
+    .await?;
+
+    if !htmx::is_htmx(&headers) {
+        return Ok(Redirect::to(get_referer(&headers)?).into_response());
+    }
+
+    trip.load_categories(&ctx, &state.database_pool).await?;
+    trip.load_review_hints(&ctx, &state.database_pool).await?;
+    trip.load_containers(&ctx, &state.database_pool).await?;
+
+    let active_category = trip
+        .categories()
+        .iter()
+        .find(|category| category.category.id == category_id)
+        .ok_or_else(|| {
+            RunError::Request(RequestError::NotFound {
+                message: format!("category with id {category_id} not found"),
+            })
+        })?;
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert::<HeaderName>(
+        htmx::ResponseHeaders::Trigger.into(),
+        htmx::Event::TripItemEdited.into(),
+    );
+    Ok((
+        response_headers,
+        view::TripItems::build(Some(active_category), &trip),
+    )
+        .into_response())
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route(
-            "/{id}/items/{id}/pick",
-            get(set_item_pick).post(set_item_pick_htmx),
+            "/{id}/pick",
+            auth::get_mutating(set_item_pick).post(set_item_pick_htmx),
+        )
+        .route(
+            "/{id}/unpick",
+            auth::get_mutating(set_item_unpick).post(set_item_unpick_htmx),
+        )
+        .route(
+            "/{id}/pack",
+            auth::get_mutating(set_item_pack).post(set_item_pack_htmx),
         )
         .route(
-            "/{id}/items/{id}/unpick",
-            get(set_item_unpick).post(set_item_unpick_htmx),
+            "/{id}/unpack",
+            auth::get_mutating(set_item_unpack).post(set_item_unpack_htmx),
         )
         .route(
-            "/{id}/items/{id}/pack",
-            get(set_item_pack).post(set_item_pack_htmx),
+            "/{id}/ready",
+            auth::get_mutating(set_item_ready).post(set_item_ready_htmx),
         )
         .route(
-            "/{id}/items/{id}/unpack",
-            get(set_item_unpack).post(set_item_unpack_htmx),
+            "/{id}/unready",
+            auth::get_mutating(set_item_unready).post(set_item_unready_htmx),
         )
         .route(
-            "/{id}/items/{id}/ready",
-            get(set_item_ready).post(set_item_ready_htmx),
+            "/{id}/wear",
+            auth::get_mutating(set_item_wear).post(set_item_wear_htmx),
         )
         .route(
-            "/{id}/items/{id}/unready",
-            get(set_item_unready).post(set_item_unready_htmx),
+            "/{id}/unwear",
+            auth::get_mutating(set_item_unwear).post(set_item_unwear_htmx),
         )
+        .route("/{id}/note", post(set_item_note))
+        .route("/{id}/quantity", post(set_item_quantity))
+        .route("/{id}/container", post(set_item_container))
+        .route("/bulk", post(set_items_bulk))
+        .route("/new/pick", post(pick_all_new_items))
+        .route("/new/dismiss", post(dismiss_all_new_items))
+        .route("/{id}/new/pick", post(pick_new_item))
+        .route("/{id}/new/dismiss", post(dismiss_new_item))
 }
@@ -1,9 +1,22 @@
+mod compare;
+mod containers;
+mod expenses;
+mod export;
 mod items;
 mod model;
 mod packagelist;
+mod progression;
+mod review;
 mod routes;
+mod stages;
 mod todos;
+mod tracks;
 mod view;
 
-pub use model::{TripAttribute, TripState};
+pub use export::{ExportFormat, export};
+pub use model::{
+    GuardPolicy, TransitionRules, TripAttribute, TripState, add_inventory_item_to_trips,
+    lock_trip_items,
+};
+pub use progression::progress_trip_states_periodically;
 pub use routes::router;
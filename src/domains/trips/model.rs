@@ -3,7 +3,7 @@ use std::fmt;
 use crate::{
     Context,
     domains::{crud::Read, inventory},
-    error::{DataError, RunError},
+    error::{DataError, RequestError, RunError},
 };
 
 use serde::{Deserialize, Serialize};
@@ -62,7 +62,7 @@ impl TryFrom<TripDate> for sqlx::postgres::types::PgRange<time::Date> {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Deserialize, Debug, sqlx::Type)]
+#[derive(PartialEq, Eq, PartialOrd, Clone, Deserialize, Debug, sqlx::Type)]
 #[sqlx(type_name = "trip_state")]
 #[sqlx(rename_all = "lowercase")]
 pub enum TripState {
@@ -86,6 +86,67 @@ pub async fn trip_item_set_state(
     TripItem::set_state(ctx, pool, trip_id, item_id, key, value).await
 }
 
+/// Serializes the creation of trips and inventory items of the user.
+///
+/// Both add trip items for every combination of trip and inventory item. If a
+/// trip and an item were created at the same time, neither transaction would
+/// see the other, and the item would be missing from the trip for good. Call
+/// this first in the transaction, so the later inserts see whatever the other
+/// transaction committed in the meantime.
+#[tracing::instrument(skip(transaction))]
+pub async fn lock_trip_items(
+    ctx: &Context,
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+) -> Result<(), RunError> {
+    // NO KEY UPDATE, so inserts referencing the user elsewhere do not block
+    database::query_exists!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Select,
+            component: crate::Component::Trips,
+        },
+        &mut **transaction,
+        "SELECT id
+        FROM users
+        WHERE id = $1
+        FOR NO KEY UPDATE",
+        ctx.user.id
+    )
+    .await
+    .map_err(|e: sqlx::Error| -> RunError { e.into() })?;
+
+    Ok(())
+}
+
+/// Adds a newly created inventory item to all trips of the user. Trips that
+/// are not yet underway mark it as new, so the user notices it and can decide
+/// whether to take it along.
+#[tracing::instrument(skip(transaction))]
+pub async fn add_inventory_item_to_trips(
+    ctx: &Context,
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    item_id: Uuid,
+) -> Result<(), RunError> {
+    database::execute!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Insert,
+            component: crate::Component::Trips,
+        },
+        &mut **transaction,
+        RunError,
+        "INSERT INTO trip_items
+            (item_id, trip_id, pick, pack, ready, new, user_id)
+        SELECT $1, trip.id, false, false, false, trip.state < 'active', trip.user_id
+        FROM trips AS trip
+        WHERE trip.user_id = $2
+        ON CONFLICT (item_id, trip_id) DO NOTHING",
+        item_id,
+        ctx.user.id
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[allow(clippy::new_without_default)]
 impl TripState {
     #[must_use]
@@ -135,6 +196,14 @@ impl fmt::Display for TripState {
     }
 }
 
+impl std::str::FromStr for TripState {
+    type Err = RunError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
 impl std::convert::TryFrom<&str> for TripState {
     type Error = RunError;
 
@@ -154,11 +223,202 @@ impl std::convert::TryFrom<&str> for TripState {
     }
 }
 
+/// What to do when a guard condition of a state transition is not met
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuardPolicy {
+    Allow,
+    Confirm,
+    Refuse,
+}
+
+/// Guard conditions that are checked before a trip is moved to `Active`
+#[derive(Clone, Debug)]
+pub struct TransitionRules {
+    /// Items that are picked but not yet packed
+    pub unpacked_items: GuardPolicy,
+    /// Todos that are not done yet
+    pub open_todos: GuardPolicy,
+}
+
+impl Default for TransitionRules {
+    fn default() -> Self {
+        Self {
+            unpacked_items: GuardPolicy::Confirm,
+            open_todos: GuardPolicy::Confirm,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TransitionGuard {
+    UnpackedItems { count: i64 },
+    OpenTodos { count: i64 },
+}
+
+impl fmt::Display for TransitionGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnpackedItems { count } => {
+                write!(f, "{count} picked item(s) not packed yet")
+            }
+            Self::OpenTodos { count } => write!(f, "{count} todo(s) still open"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GuardViolation {
+    pub guard: TransitionGuard,
+    pub policy: GuardPolicy,
+}
+
+#[derive(Debug)]
+pub enum StateTransition {
+    Applied,
+    Blocked {
+        from: TripState,
+        violations: Vec<GuardViolation>,
+    },
+}
+
+impl StateTransition {
+    /// A blocked transition can be forced by the user if none of the violated
+    /// guards refuses it outright
+    #[must_use]
+    pub fn confirmable(violations: &[GuardViolation]) -> bool {
+        violations
+            .iter()
+            .all(|violation| violation.policy == GuardPolicy::Confirm)
+    }
+}
+
+#[derive(Debug)]
+pub struct TripStateChange {
+    pub from: Option<TripState>,
+    pub to: TripState,
+    pub changed_at: time::OffsetDateTime,
+    pub username: String,
+    pub automated: bool,
+}
+
+pub struct DbTripStateChangeRow {
+    pub from_state: Option<TripState>,
+    pub to_state: TripState,
+    pub changed_at: time::OffsetDateTime,
+    pub username: String,
+    pub automated: bool,
+}
+
+impl TryFrom<DbTripStateChangeRow> for TripStateChange {
+    type Error = RunError;
+
+    fn try_from(row: DbTripStateChangeRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            from: row.from_state,
+            to: row.to_state,
+            changed_at: row.changed_at,
+            username: row.username,
+            automated: row.automated,
+        })
+    }
+}
+
+/// Trips moved by [`progress_trip_states`]
+#[derive(Debug, Default)]
+pub struct StateProgression {
+    pub activated: Vec<Uuid>,
+    pub reviewed: Vec<Uuid>,
+}
+
+/// Moves trips along based on their date: `Planned` trips become `Active` on
+/// their start date, `Active` trips go to `Review` the day after their end
+/// date. Transition guards do not apply here, the trip happens anyway.
+///
+/// Each step is a single statement that only touches trips that are still in
+/// the source state, so it is fine to run this concurrently from several
+/// instances: every transition is only done and recorded once.
+#[tracing::instrument]
+pub async fn progress_trip_states(pool: &database::Pool) -> Result<StateProgression, RunError> {
+    struct Row {
+        trip_id: Uuid,
+    }
+
+    impl TryFrom<Row> for Uuid {
+        type Error = RunError;
+
+        fn try_from(value: Row) -> Result<Self, Self::Error> {
+            Ok(value.trip_id)
+        }
+    }
+
+    let activated: Vec<Uuid> = database::query_all!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Update,
+            component: crate::Component::Trips,
+        },
+        pool,
+        Row,
+        Uuid,
+        RunError,
+        r#"
+        WITH moved AS (
+            UPDATE trips
+            SET state = 'active'
+            WHERE state = 'planned'
+                AND lower(date) <= CURRENT_DATE
+            RETURNING id, user_id
+        )
+        INSERT INTO trip_state_history
+            (id, trip_id, from_state, to_state, user_id, automated)
+        SELECT
+            gen_random_uuid(), id, 'planned', 'active', user_id, true
+        FROM moved
+        RETURNING trip_id AS "trip_id!"
+        "#,
+    )
+    .await?;
+
+    // the upper bound of the range is exclusive, so it already is the day
+    // after the end date
+    let reviewed: Vec<Uuid> = database::query_all!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Update,
+            component: crate::Component::Trips,
+        },
+        pool,
+        Row,
+        Uuid,
+        RunError,
+        r#"
+        WITH moved AS (
+            UPDATE trips
+            SET state = 'review'
+            WHERE state = 'active'
+                AND upper(date) <= CURRENT_DATE
+            RETURNING id, user_id
+        )
+        INSERT INTO trip_state_history
+            (id, trip_id, from_state, to_state, user_id, automated)
+        SELECT
+            gen_random_uuid(), id, 'active', 'review', user_id, true
+        FROM moved
+        RETURNING trip_id AS "trip_id!"
+        "#,
+    )
+    .await?;
+
+    Ok(StateProgression {
+        activated,
+        reviewed,
+    })
+}
+
 #[derive(Serialize, Debug)]
 pub enum TripItemStateKey {
     Pick,
     Pack,
     Ready,
+    Wear,
 }
 
 impl fmt::Display for TripItemStateKey {
@@ -170,6 +430,7 @@ impl fmt::Display for TripItemStateKey {
                 Self::Pick => "pick",
                 Self::Pack => "pack",
                 Self::Ready => "ready",
+                Self::Wear => "wear",
             },
         )
     }
@@ -189,7 +450,18 @@ impl TripCategory {
             .unwrap()
             .iter()
             .filter(|item| item.picked)
-            .map(|item| item.item.weight)
+            .map(TripItem::weight)
+            .sum()
+    }
+
+    #[tracing::instrument]
+    pub fn total_worn_weight(&self) -> i32 {
+        self.items
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter(|item| item.picked && item.worn)
+            .map(TripItem::weight)
             .sum()
     }
 
@@ -212,7 +484,11 @@ impl TripCategory {
             item_is_picked: Option<bool>,
             item_is_packed: Option<bool>,
             item_is_ready: Option<bool>,
+            item_is_worn: Option<bool>,
             item_is_new: Option<bool>,
+            item_note: Option<String>,
+            item_quantity: Option<i32>,
+            item_container_id: Option<Uuid>,
         }
 
         struct RowParsed {
@@ -247,7 +523,11 @@ impl TripCategory {
                             picked: row.item_is_picked.unwrap(),
                             packed: row.item_is_packed.unwrap(),
                             ready: row.item_is_ready.unwrap(),
+                            worn: row.item_is_worn.unwrap(),
                             new: row.item_is_new.unwrap(),
+                            note: row.item_note,
+                            quantity: row.item_quantity.unwrap(),
+                            container_id: row.item_container_id,
                         }),
                         None => None,
                     },
@@ -277,7 +557,11 @@ impl TripCategory {
                         trip.pick AS item_is_picked,
                         trip.pack AS item_is_packed,
                         trip.ready AS item_is_ready,
-                        trip.new AS item_is_new
+                        trip.worn AS item_is_worn,
+                        trip.new AS item_is_new,
+                        trip.note AS item_note,
+                        trip.quantity AS item_quantity,
+                        trip.container_id AS item_container_id
                     FROM trip_items AS trip
                     INNER JOIN inventory_items AS item
                         ON item.id = trip.item_id
@@ -298,7 +582,11 @@ impl TripCategory {
                     items.item_is_picked AS item_is_picked,
                     items.item_is_packed AS item_is_packed,
                     items.item_is_ready AS item_is_ready,
-                    items.item_is_new AS item_is_new
+                    items.item_is_worn AS item_is_worn,
+                    items.item_is_new AS item_is_new,
+                    items.item_note AS item_note,
+                    items.item_quantity AS item_quantity,
+                    items.item_container_id AS item_container_id
                 FROM inventory_items_categories AS category
                     LEFT JOIN category_items AS items
                     ON items.category_id = category.id
@@ -341,14 +629,26 @@ pub struct TripItem {
     pub picked: bool,
     pub packed: bool,
     pub ready: bool,
+    /// Worn on the body instead of being carried
+    pub worn: bool,
     pub new: bool,
+    /// Trip specific remark, e.g. "charge before"
+    pub note: Option<String>,
+    /// How many of the item are taken along
+    pub quantity: i32,
+    /// The bag the item is packed into
+    pub container_id: Option<Uuid>,
 }
 
 pub struct DbTripsItemsRow {
     pub picked: bool,
     pub packed: bool,
     pub ready: bool,
+    pub worn: bool,
     pub new: bool,
+    pub note: Option<String>,
+    pub quantity: i32,
+    pub container_id: Option<Uuid>,
     pub id: Uuid,
     pub name: String,
     pub weight: i32,
@@ -364,7 +664,11 @@ impl TryFrom<DbTripsItemsRow> for TripItem {
             picked: row.picked,
             packed: row.packed,
             ready: row.ready,
+            worn: row.worn,
             new: row.new,
+            note: row.note,
+            quantity: row.quantity,
+            container_id: row.container_id,
             item: inventory::Item {
                 id: row.id,
                 name: row.name,
@@ -377,6 +681,12 @@ impl TryFrom<DbTripsItemsRow> for TripItem {
 }
 
 impl TripItem {
+    /// Weight of all pieces of the item taken along
+    #[must_use]
+    pub fn weight(&self) -> i32 {
+        self.item.weight.saturating_mul(self.quantity)
+    }
+
     #[tracing::instrument]
     pub async fn find(
         ctx: &Context,
@@ -399,7 +709,11 @@ impl TripItem {
                     t_item.pick AS picked,
                     t_item.pack AS packed,
                     t_item.ready AS ready,
+                    t_item.worn AS worn,
                     t_item.new AS new,
+                    t_item.note AS note,
+                    t_item.quantity AS quantity,
+                    t_item.container_id AS container_id,
                     i_item.name AS name,
                     i_item.description AS description,
                     i_item.weight AS weight,
@@ -418,6 +732,102 @@ impl TripItem {
         .await
     }
 
+    #[tracing::instrument]
+    pub async fn set_note(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Uuid,
+        note: Option<&str>,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trip_items
+                SET note = $1
+                WHERE trip_id = $2
+                AND item_id = $3
+                AND user_id = $4",
+            note,
+            trip_id,
+            item_id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    #[tracing::instrument]
+    pub async fn set_quantity(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Uuid,
+        quantity: i32,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trip_items
+                SET quantity = $1
+                WHERE trip_id = $2
+                AND item_id = $3
+                AND user_id = $4",
+            quantity,
+            trip_id,
+            item_id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Clears the `new` flag of items that were added to the inventory after
+    /// the trip was created, optionally picking them at the same time. Without
+    /// an `item_id`, this applies to all new items of the trip.
+    ///
+    /// Returns the number of items that were changed.
+    #[tracing::instrument]
+    pub async fn clear_new(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Option<Uuid>,
+        pick: bool,
+    ) -> Result<u64, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trip_items
+                SET new = false, pick = pick OR $1
+                WHERE trip_id = $2
+                AND user_id = $3
+                AND new = true
+                AND ($4::uuid IS NULL OR item_id = $4)",
+            pick,
+            trip_id,
+            ctx.user.id,
+            item_id
+        )
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     #[tracing::instrument]
     pub async fn set_state(
         ctx: &Context,
@@ -488,6 +898,26 @@ impl TripItem {
                 )
                 .await
             }
+            TripItemStateKey::Wear => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    pool,
+                    RunError,
+                    "UPDATE trip_items
+                        SET worn = $1
+                        WHERE trip_id = $2
+                        AND item_id = $3
+                        AND user_id = $4",
+                    value,
+                    trip_id,
+                    item_id,
+                    ctx.user.id
+                )
+                .await
+            }
         }?;
 
         (result.rows_affected() != 0).then_some(()).ok_or_else(|| {
@@ -496,85 +926,418 @@ impl TripItem {
             })
         })
     }
-}
-
-pub struct DbTripRow {
-    pub id: Uuid,
-    pub name: String,
-    pub date: TripDate,
-    pub state: TripState,
-    pub location: Option<String>,
-    pub temp_min: Option<i32>,
-    pub temp_max: Option<i32>,
-    pub comment: Option<String>,
-}
-
-impl TryFrom<DbTripRow> for Trip {
-    type Error = RunError;
 
-    fn try_from(row: DbTripRow) -> Result<Self, Self::Error> {
-        Ok(Self {
-            id: row.id,
-            name: row.name,
-            date: row.date,
-            state: row.state,
-            location: row.location,
-            temp_min: row.temp_min,
-            temp_max: row.temp_max,
-            comment: row.comment,
-            todos: None,
-            types: None,
-            categories: None,
-        })
-    }
-}
+    /// Sets the state of all items of a category at once, or only of the
+    /// given items of it. Everything but picking only applies to picked items,
+    /// as the others are not taken along.
+    ///
+    /// Returns the number of changed items.
+    #[tracing::instrument]
+    pub async fn set_state_bulk(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        category_id: Uuid,
+        item_ids: Option<&[Uuid]>,
+        key: TripItemStateKey,
+        value: bool,
+    ) -> Result<u64, RunError> {
+        struct Row {
+            item_id: Uuid,
+        }
 
-#[derive(Debug)]
-pub struct Trip {
-    pub id: Uuid,
-    pub name: String,
-    pub date: TripDate,
-    pub state: TripState,
-    pub location: Option<String>,
-    pub temp_min: Option<i32>,
-    pub temp_max: Option<i32>,
-    pub comment: Option<String>,
-    pub todos: Option<Vec<crate::domains::trips::todos::Todo>>,
-    pub types: Option<Vec<TripType>>,
-    pub categories: Option<Vec<TripCategory>>,
-}
+        impl TryFrom<Row> for Uuid {
+            type Error = RunError;
 
-macro_rules! build_trip_edit {
-    ( $( ($name:ident, $( $id:ident ).* , $human:expr, $wire:expr, $type:path, $param_type:path, $query:expr) ),* $(,)? ) => {
-        #[derive(Clone, Debug, Serialize, Deserialize)]
-        #[allow(dead_code)]
-        pub enum TripAttributeUpdate {
-            $(
-                #[serde(rename = $wire)]
-                $name($type),
-            )*
+            fn try_from(value: Row) -> Result<Self, Self::Error> {
+                Ok(value.item_id)
+            }
         }
 
-        #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-        pub enum TripAttribute {
-            $(
-                #[serde(rename = $wire)]
-                $name,
-            )*
-        }
+        let mut transaction = pool.begin().await?;
 
-        impl TripAttribute {
-            pub fn id(&self) -> &'static str {
-                match self {
-                    $(
-                        Self::$name => $wire,
-                    )*
+        if let Some(item_ids) = item_ids {
+            let found: Vec<Uuid> = database::query_all!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Select,
+                    component: crate::Component::Trips,
+                },
+                &mut *transaction,
+                Row,
+                Uuid,
+                RunError,
+                "SELECT t_item.item_id AS item_id
+                FROM trip_items AS t_item
+                INNER JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                WHERE t_item.trip_id = $1
+                AND t_item.user_id = $2
+                AND i_item.category_id = $3
+                AND t_item.item_id = ANY($4)
+                FOR UPDATE OF t_item",
+                trip_id,
+                ctx.user.id,
+                category_id,
+                item_ids
+            )
+            .await?;
 
-                }
+            if let Some(missing) = item_ids.iter().find(|id| !found.contains(id)) {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: "item-id".to_owned(),
+                    message: format!("item {missing} not found in category {category_id}"),
+                }));
             }
         }
 
-        $(
+        let result = match key {
+            TripItemStateKey::Pick => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    &mut *transaction,
+                    RunError,
+                    "UPDATE trip_items AS t_item
+                        SET pick = $1
+                        FROM inventory_items AS i_item
+                        WHERE i_item.id = t_item.item_id
+                        AND t_item.trip_id = $2
+                        AND t_item.user_id = $3
+                        AND i_item.category_id = $4
+                        AND ($5::uuid[] IS NULL OR t_item.item_id = ANY($5))",
+                    value,
+                    trip_id,
+                    ctx.user.id,
+                    category_id,
+                    item_ids
+                )
+                .await
+            }
+            TripItemStateKey::Pack => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    &mut *transaction,
+                    RunError,
+                    "UPDATE trip_items AS t_item
+                        SET pack = $1
+                        FROM inventory_items AS i_item
+                        WHERE i_item.id = t_item.item_id
+                        AND t_item.trip_id = $2
+                        AND t_item.user_id = $3
+                        AND i_item.category_id = $4
+                        AND ($5::uuid[] IS NULL OR t_item.item_id = ANY($5))
+                        AND t_item.pick = true",
+                    value,
+                    trip_id,
+                    ctx.user.id,
+                    category_id,
+                    item_ids
+                )
+                .await
+            }
+            TripItemStateKey::Ready => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    &mut *transaction,
+                    RunError,
+                    "UPDATE trip_items AS t_item
+                        SET ready = $1
+                        FROM inventory_items AS i_item
+                        WHERE i_item.id = t_item.item_id
+                        AND t_item.trip_id = $2
+                        AND t_item.user_id = $3
+                        AND i_item.category_id = $4
+                        AND ($5::uuid[] IS NULL OR t_item.item_id = ANY($5))
+                        AND t_item.pick = true",
+                    value,
+                    trip_id,
+                    ctx.user.id,
+                    category_id,
+                    item_ids
+                )
+                .await
+            }
+            TripItemStateKey::Wear => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    &mut *transaction,
+                    RunError,
+                    "UPDATE trip_items AS t_item
+                        SET worn = $1
+                        FROM inventory_items AS i_item
+                        WHERE i_item.id = t_item.item_id
+                        AND t_item.trip_id = $2
+                        AND t_item.user_id = $3
+                        AND i_item.category_id = $4
+                        AND ($5::uuid[] IS NULL OR t_item.item_id = ANY($5))
+                        AND t_item.pick = true",
+                    value,
+                    trip_id,
+                    ctx.user.id,
+                    category_id,
+                    item_ids
+                )
+                .await
+            }
+        }?;
+
+        transaction.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Picked weight of a trip, split into worn and carried items
+#[derive(Debug)]
+pub struct TripWeight {
+    pub id: Uuid,
+    pub name: String,
+    pub worn: i32,
+    pub carried: i32,
+}
+
+pub struct DbTripWeightRow {
+    pub id: Uuid,
+    pub name: String,
+    pub worn: Option<i32>,
+    pub carried: Option<i32>,
+}
+
+impl TryFrom<DbTripWeightRow> for TripWeight {
+    type Error = RunError;
+
+    fn try_from(row: DbTripWeightRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            worn: row.worn.unwrap_or(0),
+            carried: row.carried.unwrap_or(0),
+        })
+    }
+}
+
+impl TripWeight {
+    /// The weights of the given trip and the trips that started before it, oldest first
+    #[tracing::instrument]
+    pub async fn find_history(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, RunError> {
+        let mut weights = database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            DbTripWeightRow,
+            Self,
+            RunError,
+            "
+                SELECT
+                    trip.id AS id,
+                    trip.name AS name,
+                    CAST(SUM(i_item.weight * t_item.quantity) FILTER (WHERE t_item.worn) AS INTEGER) AS worn,
+                    CAST(SUM(i_item.weight * t_item.quantity) FILTER (WHERE NOT t_item.worn) AS INTEGER) AS carried
+                FROM trips AS trip
+                INNER JOIN trips AS current
+                    ON current.id = $1 AND current.user_id = $2
+                LEFT JOIN trip_items AS t_item
+                    ON t_item.trip_id = trip.id AND t_item.pick = true
+                LEFT JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                WHERE
+                    trip.user_id = $2
+                    AND (trip.id = current.id OR lower(trip.date) < lower(current.date))
+                GROUP BY trip.id
+                ORDER BY lower(trip.date) DESC
+                LIMIT $3
+            ",
+            trip_id,
+            ctx.user.id,
+            limit
+        )
+        .await?;
+
+        weights.reverse();
+        Ok(weights)
+    }
+}
+
+pub struct DbTripRow {
+    pub id: Uuid,
+    pub name: String,
+    pub date: TripDate,
+    pub state: TripState,
+    pub location: Option<String>,
+    pub temp_min: Option<i32>,
+    pub temp_max: Option<i32>,
+    pub comment: Option<String>,
+    pub todo_order: crate::domains::trips::todos::Order,
+    pub archived: bool,
+}
+
+impl TryFrom<DbTripRow> for Trip {
+    type Error = RunError;
+
+    fn try_from(row: DbTripRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            date: row.date,
+            state: row.state,
+            location: row.location,
+            temp_min: row.temp_min,
+            temp_max: row.temp_max,
+            comment: row.comment,
+            todo_order: row.todo_order,
+            archived: row.archived,
+            todos: None,
+            types: None,
+            categories: None,
+            state_history: None,
+            stages: None,
+            review_hints: None,
+            expenses: None,
+            containers: None,
+            tracks: None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Trip {
+    pub id: Uuid,
+    pub name: String,
+    pub date: TripDate,
+    pub state: TripState,
+    pub location: Option<String>,
+    pub temp_min: Option<i32>,
+    pub temp_max: Option<i32>,
+    pub comment: Option<String>,
+    pub todo_order: crate::domains::trips::todos::Order,
+    /// Archived trips are hidden from the trip list by default. Trips are
+    /// archived when they are done, and can be unarchived by hand.
+    pub archived: bool,
+    pub todos: Option<Vec<crate::domains::trips::todos::Todo>>,
+    pub types: Option<Vec<TripType>>,
+    pub categories: Option<Vec<TripCategory>>,
+    pub state_history: Option<Vec<TripStateChange>>,
+    pub stages: Option<Vec<crate::domains::trips::stages::Stage>>,
+    pub review_hints: Option<Vec<crate::domains::trips::review::ReviewHint>>,
+    pub expenses: Option<crate::domains::trips::expenses::Expenses>,
+    pub containers: Option<Vec<crate::domains::trips::containers::Container>>,
+    pub tracks: Option<Vec<crate::domains::trips::tracks::Track>>,
+}
+
+/// Selects the lazily loaded parts of a [`Trip`] that [`Trip::load`] fetches
+/// together with the trip itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TripParts {
+    pub types: bool,
+    pub todos: bool,
+    pub stages: bool,
+    pub expenses: bool,
+    pub containers: bool,
+    pub tracks: bool,
+    pub state_history: bool,
+    pub categories: bool,
+    pub review_hints: bool,
+}
+
+impl TripParts {
+    pub const NONE: Self = Self {
+        types: false,
+        todos: false,
+        stages: false,
+        expenses: false,
+        containers: false,
+        tracks: false,
+        state_history: false,
+        categories: false,
+        review_hints: false,
+    };
+
+    pub const ALL: Self = Self {
+        types: true,
+        todos: true,
+        stages: true,
+        expenses: true,
+        containers: true,
+        tracks: true,
+        state_history: true,
+        categories: true,
+        review_hints: true,
+    };
+
+    /// Everything required to render the item list of a trip
+    pub const ITEMS: Self = Self {
+        categories: true,
+        containers: true,
+        review_hints: true,
+        ..Self::NONE
+    };
+}
+
+/// Runs `query` only if the part is requested, so unrequested parts do not
+/// cost a round trip.
+async fn load_part<T, E>(
+    requested: bool,
+    query: impl Future<Output = Result<T, E>>,
+) -> Result<Option<T>, RunError>
+where
+    E: Into<RunError>,
+{
+    if requested {
+        Ok(Some(query.await.map_err(Into::into)?))
+    } else {
+        Ok(None)
+    }
+}
+
+macro_rules! build_trip_edit {
+    ( $( ($name:ident, $( $id:ident ).* , $human:expr, $wire:expr, $type:path, $param_type:path, $query:expr) ),* $(,)? ) => {
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        #[allow(dead_code)]
+        pub enum TripAttributeUpdate {
+            $(
+                #[serde(rename = $wire)]
+                $name($type),
+            )*
+        }
+
+        #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+        pub enum TripAttribute {
+            $(
+                #[serde(rename = $wire)]
+                $name,
+            )*
+        }
+
+        impl TripAttribute {
+            pub fn id(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$name => $wire,
+                    )*
+
+                }
+            }
+        }
+
+        $(
             paste::paste! {
                 #[tracing::instrument]
                 pub async fn [<set_attribute_ $name:lower >] (
@@ -628,6 +1391,8 @@ macro_rules! build_trip_edit {
                                 (&trip.$( $id ).*).into(),
                                 super::TripAttribute::$name,
                                 trip_edit_attribute,
+                                !(super::TripAttribute::$name.derived_from_stages()
+                                    && !trip.stages().is_empty()),
                             )
                         },
                     )*
@@ -683,7 +1448,11 @@ macro_rules! build_trip_edit {
                         Form(trip_update): Form<[< TripEditUpdate $name >]>,
                     ) -> Result<Redirect, RunError> {
                         let ctx = Context::build(current_user);
-                        super::[<set_attribute_ $name:lower >](&ctx, &state.database_pool, trip_id, trip_update.into())
+                        let value: super::[< Type$name >] = trip_update.into();
+                        super::TripAttributeUpdate::$name(value.clone())
+                            .validate(&ctx, &state.database_pool, trip_id)
+                            .await?;
+                        super::[<set_attribute_ $name:lower >](&ctx, &state.database_pool, trip_id, value)
                             .await?;
 
                         Ok(Redirect::to(&format!("/trips/{trip_id}/")))
@@ -714,85 +1483,607 @@ build_trip_edit! {
     (TempMax, temp_max, "Temp (max)", "temp_max", i32, i32, "UPDATE trips SET temp_max = $1 WHERE id = $2 AND user_id = $3"),
 }
 
-impl Trip {
-    #[tracing::instrument]
-    pub async fn all(ctx: &Context, pool: &database::Pool) -> Result<Vec<Self>, RunError> {
-        let mut trips = database::query_all!(
-            &database::QueryClassification {
-                query_type: database::QueryType::Select,
-                component: crate::Component::Trips,
-            },
-            pool,
-            DbTripRow,
-            Self,
-            RunError,
-            r#"SELECT
-                id,
-                name,
-                date,
-                state as "state: _",
-                location,
-                temp_min,
-                temp_max,
-                comment
-            FROM trips
-            WHERE user_id = $1"#,
-            ctx.user.id
-        )
-        .await?;
-
-        trips.sort_by_key(|trip| trip.date.start);
-        Ok(trips)
+impl TripAttribute {
+    /// Attributes that are derived from the stages as soon as the trip has
+    /// any. The value of the trip itself is only used for trips without stages.
+    #[must_use]
+    pub const fn derived_from_stages(&self) -> bool {
+        matches!(self, Self::Location | Self::TempMin | Self::TempMax)
     }
+}
 
+impl TripAttributeUpdate {
+    /// Checks the new value against the rest of the trip: the stages of a
+    /// trip have to stay within its date.
     #[tracing::instrument]
-    pub async fn find(
+    pub async fn validate(
+        &self,
         ctx: &Context,
         pool: &database::Pool,
         trip_id: Uuid,
-    ) -> Result<Option<Self>, RunError> {
-        database::query_one!(
-            &database::QueryClassification {
-                query_type: database::QueryType::Select,
-                component: crate::Component::Trips,
-            },
-            pool,
-            DbTripRow,
-            Self,
-            RunError,
-            r#"SELECT
-                id,
-                name,
-                date,
-                state as "state: _",
-                location,
-                temp_min,
-                temp_max,
-                comment
-            FROM trips
-            WHERE id = $1 and user_id = $2"#,
-            trip_id,
-            ctx.user.id
-        )
-        .await
+    ) -> Result<(), RunError> {
+        if let Self::TripDate(date) = self {
+            let stages = crate::domains::trips::stages::Stage::findall(
+                ctx,
+                pool,
+                crate::domains::trips::stages::Container { trip_id },
+            )
+            .await?;
+
+            if let Some(stage) = stages
+                .iter()
+                .find(|stage| stage.date.start < date.start || stage.date.end > date.end)
+            {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: "date".to_string(),
+                    message: format!(
+                        "the stage on {} has to be within the trip date, move or delete it first",
+                        stage.date
+                    ),
+                }));
+            }
+        }
+
+        Ok(())
     }
+}
 
-    #[tracing::instrument]
-    pub async fn trip_type_remove(
-        ctx: &Context,
-        pool: &database::Pool,
-        id: Uuid,
-        type_id: Uuid,
-    ) -> Result<bool, RunError> {
-        let results = database::execute!(
-            &database::QueryClassification {
-                query_type: database::QueryType::Delete,
-                component: crate::Component::Trips,
-            },
-            pool,
-            RunError,
-            "DELETE FROM trip_to_trip_types AS ttt
-            WHERE ttt.trip_id = $1
+/// Number of trips shown on one page of the trip list
+pub const TRIPS_PER_PAGE: i64 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TripSortColumn {
+    Name,
+    #[default]
+    Start,
+    End,
+    Nights,
+    Location,
+    State,
+}
+
+impl TripSortColumn {
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Start => "start",
+            Self::End => "end",
+            Self::Nights => "nights",
+            Self::Location => "location",
+            Self::State => "state",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+
+    #[must_use]
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::Asc => Self::Desc,
+            Self::Desc => Self::Asc,
+        }
+    }
+}
+
+const fn first_page() -> i64 {
+    1
+}
+
+/// Filter, sort order and page of the trip list, taken from the query string
+/// so that every view of the list has its own URL
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TripFilter {
+    #[serde(default, deserialize_with = "crate::routing::parse_or_empty")]
+    pub state: Option<TripState>,
+    #[serde(
+        default,
+        rename = "type",
+        deserialize_with = "crate::routing::uuid_or_empty"
+    )]
+    pub trip_type: Option<Uuid>,
+    /// Trips that end on or after this date
+    #[serde(default, deserialize_with = "crate::routing::date_or_empty")]
+    pub from: Option<time::Date>,
+    /// Trips that start on or before this date
+    #[serde(default, deserialize_with = "crate::routing::date_or_empty")]
+    pub to: Option<time::Date>,
+    #[serde(default)]
+    pub location: String,
+    /// Archived trips are only shown on request
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub sort: TripSortColumn,
+    #[serde(default)]
+    pub direction: SortDirection,
+    #[serde(default = "first_page")]
+    pub page: i64,
+}
+
+impl Default for TripFilter {
+    fn default() -> Self {
+        Self {
+            state: None,
+            trip_type: None,
+            from: None,
+            to: None,
+            location: String::new(),
+            archived: false,
+            sort: TripSortColumn::default(),
+            direction: SortDirection::default(),
+            page: first_page(),
+        }
+    }
+}
+
+impl TripFilter {
+    /// The query string that leads to this view of the list, starting with `?`
+    #[must_use]
+    pub fn url(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::for_suffix(String::from("?"), 1);
+        if let Some(ref state) = self.state {
+            query.append_pair("state", &state.to_string());
+        }
+        if let Some(trip_type) = self.trip_type {
+            query.append_pair("type", &trip_type.to_string());
+        }
+        if let Some(from) = self.from {
+            query.append_pair("from", &from.to_string());
+        }
+        if let Some(to) = self.to {
+            query.append_pair("to", &to.to_string());
+        }
+        if !self.location.is_empty() {
+            query.append_pair("location", &self.location);
+        }
+        if self.archived {
+            query.append_pair("archived", "true");
+        }
+        query.append_pair("sort", self.sort.id());
+        query.append_pair("direction", self.direction.id());
+        query.append_pair("page", &self.page.to_string());
+        query.finish()
+    }
+
+    #[must_use]
+    pub fn with_page(&self, page: i64) -> Self {
+        Self {
+            page,
+            ..self.clone()
+        }
+    }
+
+    /// Sorts by the column, or reverses the direction if the list is already
+    /// sorted by it. Starts again on the first page.
+    #[must_use]
+    pub fn with_sort(&self, sort: TripSortColumn) -> Self {
+        Self {
+            sort,
+            direction: if self.sort == sort {
+                self.direction.reverse()
+            } else {
+                SortDirection::Asc
+            },
+            page: first_page(),
+            ..self.clone()
+        }
+    }
+
+    #[must_use]
+    pub fn with_archived(&self, archived: bool) -> Self {
+        Self {
+            archived,
+            page: first_page(),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TripPage {
+    pub trips: Vec<Trip>,
+    /// Number of trips matching the filter, on all pages. Zero if the page is
+    /// past the last one, as there is no trip to count it from
+    pub total: i64,
+    /// Number of trips that are hidden because they are archived
+    pub archived: i64,
+}
+
+impl TripPage {
+    #[must_use]
+    pub fn pages(&self) -> i64 {
+        ((self.total + TRIPS_PER_PAGE - 1) / TRIPS_PER_PAGE).max(1)
+    }
+}
+
+pub struct DbTripPageRow {
+    pub id: Uuid,
+    pub name: String,
+    pub date: TripDate,
+    pub state: TripState,
+    pub location: Option<String>,
+    pub temp_min: Option<i32>,
+    pub temp_max: Option<i32>,
+    pub comment: Option<String>,
+    pub todo_order: crate::domains::trips::todos::Order,
+    pub archived: bool,
+    pub total: i64,
+}
+
+pub struct TripPageEntry {
+    trip: Trip,
+    total: i64,
+}
+
+impl TryFrom<DbTripPageRow> for TripPageEntry {
+    type Error = RunError;
+
+    fn try_from(row: DbTripPageRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            trip: Trip::try_from(DbTripRow {
+                id: row.id,
+                name: row.name,
+                date: row.date,
+                state: row.state,
+                location: row.location,
+                temp_min: row.temp_min,
+                temp_max: row.temp_max,
+                comment: row.comment,
+                todo_order: row.todo_order,
+                archived: row.archived,
+            })?,
+            total: row.total,
+        })
+    }
+}
+
+impl Trip {
+    /// One page of the trip list
+    #[tracing::instrument]
+    pub async fn find_page(
+        ctx: &Context,
+        pool: &database::Pool,
+        filter: &TripFilter,
+    ) -> Result<TripPage, RunError> {
+        let location = Some(filter.location.trim()).filter(|location| !location.is_empty());
+        let offset = (filter.page.max(1) - 1)
+            .checked_mul(TRIPS_PER_PAGE)
+            .ok_or_else(|| {
+                RunError::Request(RequestError::InvalidQueryParameter {
+                    name: "page".to_string(),
+                    message: "page number is too large".to_string(),
+                })
+            })?;
+
+        let entries = database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            DbTripPageRow,
+            TripPageEntry,
+            RunError,
+            r#"SELECT
+                trip.id AS id,
+                trip.name AS name,
+                trip.date AS date,
+                trip.state as "state: _",
+                stages.location AS location,
+                stages.temp_min AS temp_min,
+                stages.temp_max AS temp_max,
+                trip.comment AS comment,
+                trip.todo_order AS "todo_order: _",
+                trip.archived AS archived,
+                COUNT(*) OVER () AS "total!"
+            FROM trips AS trip
+            LEFT JOIN LATERAL (
+                -- if the trip has stages, location and temperature are derived from them
+                SELECT
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN string_agg(stage.location, ' → ' ORDER BY stage.position)
+                        ELSE trip.location
+                    END AS location,
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN MIN(stage.temp_min)
+                        ELSE trip.temp_min
+                    END AS temp_min,
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN MAX(stage.temp_max)
+                        ELSE trip.temp_max
+                    END AS temp_max
+                FROM trip_stages AS stage
+                WHERE stage.trip_id = trip.id
+            ) AS stages ON true
+            WHERE
+                trip.user_id = $1
+                AND ($2::trip_state IS NULL OR trip.state = $2)
+                AND ($3 OR NOT trip.archived)
+                AND ($4::uuid IS NULL OR EXISTS (
+                    SELECT 1
+                    FROM trip_to_trip_types AS ttt
+                    WHERE ttt.trip_id = trip.id AND ttt.trip_type_id = $4
+                ))
+                AND ($5::date IS NULL OR upper(trip.date) > $5)
+                AND ($6::date IS NULL OR lower(trip.date) <= $6)
+                AND ($7::text IS NULL OR strpos(
+                    lower(stages.location),
+                    lower($7)
+                ) > 0)
+            ORDER BY
+                CASE WHEN $8 = 'name' AND NOT $9 THEN trip.name END ASC,
+                CASE WHEN $8 = 'name' AND $9 THEN trip.name END DESC,
+                CASE WHEN $8 = 'start' AND NOT $9 THEN lower(trip.date) END ASC,
+                CASE WHEN $8 = 'start' AND $9 THEN lower(trip.date) END DESC,
+                CASE WHEN $8 = 'end' AND NOT $9 THEN upper(trip.date) END ASC,
+                CASE WHEN $8 = 'end' AND $9 THEN upper(trip.date) END DESC,
+                CASE WHEN $8 = 'nights' AND NOT $9 THEN upper(trip.date) - lower(trip.date) END ASC,
+                CASE WHEN $8 = 'nights' AND $9 THEN upper(trip.date) - lower(trip.date) END DESC,
+                CASE WHEN $8 = 'location' AND NOT $9
+                    THEN stages.location
+                END ASC NULLS LAST,
+                CASE WHEN $8 = 'location' AND $9
+                    THEN stages.location
+                END DESC NULLS LAST,
+                CASE WHEN $8 = 'state' AND NOT $9 THEN trip.state END ASC,
+                CASE WHEN $8 = 'state' AND $9 THEN trip.state END DESC,
+                lower(trip.date) DESC,
+                trip.id
+            LIMIT $10
+            OFFSET $11"#,
+            ctx.user.id,
+            filter.state.clone() as _,
+            filter.archived,
+            filter.trip_type,
+            filter.from,
+            filter.to,
+            location,
+            filter.sort.id(),
+            filter.direction == SortDirection::Desc,
+            TRIPS_PER_PAGE,
+            offset,
+        )
+        .await?;
+
+        let archived = if filter.archived {
+            0
+        } else {
+            database::execute_returning!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Select,
+                    component: crate::Component::Trips,
+                },
+                pool,
+                RunError,
+                r#"SELECT COUNT(*) AS "count!"
+                FROM trips
+                WHERE user_id = $1 AND archived"#,
+                i64,
+                |row| row.count,
+                ctx.user.id
+            )
+            .await?
+        };
+
+        // past the last page, there is no row that carries the total. the
+        // page then looks empty and the route sends the user back to the start
+        let total = entries.first().map_or(0, |entry| entry.total);
+        Ok(TripPage {
+            trips: entries.into_iter().map(|entry| entry.trip).collect(),
+            total,
+            archived,
+        })
+    }
+}
+
+impl Trip {
+    #[tracing::instrument]
+    pub async fn all(ctx: &Context, pool: &database::Pool) -> Result<Vec<Self>, RunError> {
+        let mut trips = database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            DbTripRow,
+            Self,
+            RunError,
+            r#"SELECT
+                trip.id AS id,
+                trip.name AS name,
+                trip.date AS date,
+                trip.state as "state: _",
+                stages.location AS location,
+                stages.temp_min AS temp_min,
+                stages.temp_max AS temp_max,
+                trip.comment AS comment,
+                trip.todo_order AS "todo_order: _",
+                trip.archived AS archived
+            FROM trips AS trip
+            LEFT JOIN LATERAL (
+                -- if the trip has stages, location and temperature are derived from them
+                SELECT
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN string_agg(stage.location, ' → ' ORDER BY stage.position)
+                        ELSE trip.location
+                    END AS location,
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN MIN(stage.temp_min)
+                        ELSE trip.temp_min
+                    END AS temp_min,
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN MAX(stage.temp_max)
+                        ELSE trip.temp_max
+                    END AS temp_max
+                FROM trip_stages AS stage
+                WHERE stage.trip_id = trip.id
+            ) AS stages ON true
+            WHERE trip.user_id = $1"#,
+            ctx.user.id
+        )
+        .await?;
+
+        trips.sort_by_key(|trip| trip.date.start);
+        Ok(trips)
+    }
+
+    #[tracing::instrument]
+    pub async fn find(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            DbTripRow,
+            Self,
+            RunError,
+            r#"SELECT
+                trip.id AS id,
+                trip.name AS name,
+                trip.date AS date,
+                trip.state as "state: _",
+                stages.location AS location,
+                stages.temp_min AS temp_min,
+                stages.temp_max AS temp_max,
+                trip.comment AS comment,
+                trip.todo_order AS "todo_order: _",
+                trip.archived AS archived
+            FROM trips AS trip
+            LEFT JOIN LATERAL (
+                -- if the trip has stages, location and temperature are derived from them
+                SELECT
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN string_agg(stage.location, ' → ' ORDER BY stage.position)
+                        ELSE trip.location
+                    END AS location,
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN MIN(stage.temp_min)
+                        ELSE trip.temp_min
+                    END AS temp_min,
+                    CASE WHEN COUNT(stage.id) > 0
+                        THEN MAX(stage.temp_max)
+                        ELSE trip.temp_max
+                    END AS temp_max
+                FROM trip_stages AS stage
+                WHERE stage.trip_id = trip.id
+            ) AS stages ON true
+            WHERE trip.id = $1 AND trip.user_id = $2"#,
+            trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    /// Fetches the trip together with all requested parts.
+    ///
+    /// None of the queries depends on another, so they run concurrently. The
+    /// number of queries only depends on `parts`, never on the number of
+    /// categories, items or todos of the trip.
+    #[tracing::instrument]
+    pub async fn load(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        parts: TripParts,
+    ) -> Result<Option<Self>, RunError> {
+        use crate::domains::trips::{containers, expenses, review, stages, todos, tracks};
+
+        let (
+            trip,
+            types,
+            todos,
+            stages,
+            expenses,
+            containers,
+            tracks,
+            state_history,
+            categories,
+            review_hints,
+        ) = tokio::try_join!(
+            Self::find(ctx, pool, trip_id),
+            load_part(parts.types, Self::find_trip_types(ctx, pool, trip_id)),
+            load_part(
+                parts.todos,
+                todos::Todo::findall(ctx, pool, todos::Container { trip_id })
+            ),
+            load_part(
+                parts.stages,
+                stages::Stage::findall(ctx, pool, stages::Container { trip_id })
+            ),
+            load_part(
+                parts.expenses,
+                expenses::Expenses::find(ctx, pool, expenses::Container { trip_id })
+            ),
+            load_part(
+                parts.containers,
+                containers::Container::findall(ctx, pool, trip_id)
+            ),
+            load_part(parts.tracks, tracks::Track::findall(ctx, pool, trip_id)),
+            load_part(
+                parts.state_history,
+                Self::find_state_history(ctx, pool, trip_id)
+            ),
+            load_part(parts.categories, Self::find_categories(ctx, pool, trip_id)),
+            load_part(
+                parts.review_hints,
+                review::ReviewHint::findall(ctx, pool, trip_id, None)
+            ),
+        )?;
+
+        Ok(trip.map(|trip| Self {
+            todos,
+            types,
+            categories,
+            state_history,
+            stages,
+            review_hints,
+            expenses,
+            containers,
+            tracks,
+            ..trip
+        }))
+    }
+
+    #[tracing::instrument]
+    pub async fn trip_type_remove(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        type_id: Uuid,
+    ) -> Result<bool, RunError> {
+        let results = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "DELETE FROM trip_to_trip_types AS ttt
+            WHERE ttt.trip_id = $1
                 AND ttt.trip_type_id = $2
             AND EXISTS(SELECT * FROM trips WHERE id = $1 AND user_id = $3)
             AND EXISTS(SELECT * FROM trip_types WHERE id = $2 AND user_id = $3)
@@ -806,6 +2097,10 @@ impl Trip {
         Ok(results.rows_affected() != 0)
     }
 
+    /// Adds the type to the trip, together with the default todos of the type.
+    ///
+    /// Todos that were already added by an earlier assignment of the type are
+    /// not added again.
     #[tracing::instrument]
     pub async fn trip_type_add(
         ctx: &Context,
@@ -815,185 +2110,664 @@ impl Trip {
     ) -> Result<(), RunError> {
         // TODO user handling?
 
+        let mut transaction = pool.begin().await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO
+                trip_to_trip_types (trip_id, trip_type_id)
+            (SELECT trips.id as trip_id, trip_types.id as trip_type_id
+                FROM trips
+                INNER JOIN trip_types ON true
+                WHERE
+                    trips.id = $1
+                    AND trips.user_id = $3
+                    AND trip_types.id = $2
+                    AND trip_types.user_id = $3)",
+            id,
+            type_id,
+            ctx.user.id
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Todo,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO trip_todos
+                (id, trip_id, description, done, position, due_days_before, trip_type_todo_id)
+            SELECT
+                gen_random_uuid(),
+                trips.id,
+                type_todo.description,
+                false,
+                COALESCE((SELECT MAX(position) FROM trip_todos WHERE trip_id = trips.id), -1)
+                    + row_number() OVER (ORDER BY type_todo.position),
+                type_todo.due_days_before,
+                type_todo.id
+            FROM trip_type_todos AS type_todo
+            INNER JOIN trip_types
+                ON trip_types.id = type_todo.trip_type_id
+            INNER JOIN trips
+                ON trips.id = $1
+            WHERE
+                trip_types.id = $2
+                AND trip_types.user_id = $3
+                AND trips.user_id = $3
+            ON CONFLICT (trip_id, trip_type_todo_id) DO NOTHING",
+            id,
+            type_id,
+            ctx.user.id
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Moves the trip to `new_state` and records the transition in the state
+    /// history. Trips that are done are archived.
+    ///
+    /// Returns `None` if the trip does not exist. If any guard of `rules` is
+    /// violated, the state is left untouched and the violations are returned,
+    /// unless all of them only require confirmation and `confirmed` is set.
+    #[tracing::instrument]
+    pub async fn set_state(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        new_state: &TripState,
+        rules: &TransitionRules,
+        confirmed: bool,
+    ) -> Result<Option<StateTransition>, RunError> {
+        struct Row {
+            state: TripState,
+        }
+
+        impl TryFrom<Row> for TripState {
+            type Error = RunError;
+
+            fn try_from(value: Row) -> Result<Self, Self::Error> {
+                Ok(value.state)
+            }
+        }
+
+        let mut transaction = pool.begin().await?;
+
+        // lock the trip row so concurrent transitions are serialized
+        let current_state: Option<TripState> = database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            Row,
+            TripState,
+            RunError,
+            r#"SELECT state AS "state: _"
+            FROM trips
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE"#,
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        let Some(current_state) = current_state else {
+            return Ok(None);
+        };
+
+        if current_state == *new_state {
+            return Ok(Some(StateTransition::Applied));
+        }
+
+        let violations =
+            Self::check_transition_guards(ctx, &mut transaction, id, &current_state, new_state, rules)
+                .await?;
+
+        let forced = confirmed && StateTransition::confirmable(&violations);
+
+        if !(violations.is_empty() || forced) {
+            return Ok(Some(StateTransition::Blocked {
+                from: current_state,
+                violations,
+            }));
+        }
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "UPDATE trips
+            SET state = $1, archived = archived OR $4
+            WHERE id = $2 and user_id = $3",
+            new_state as _,
+            id,
+            ctx.user.id,
+            *new_state == TripState::Done
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO trip_state_history
+                (id, trip_id, from_state, to_state, user_id)
+            VALUES
+                ($1, $2, $3, $4, $5)",
+            Uuid::new_v4(),
+            id,
+            current_state as _,
+            new_state as _,
+            ctx.user.id
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Some(StateTransition::Applied))
+    }
+
+    #[tracing::instrument(skip(transaction))]
+    async fn check_transition_guards(
+        ctx: &Context,
+        transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: Uuid,
+        from: &TripState,
+        to: &TripState,
+        rules: &TransitionRules,
+    ) -> Result<Vec<GuardViolation>, RunError> {
+        let mut violations = vec![];
+
+        // guards only apply when starting the trip, going back to an earlier
+        // state is always fine
+        if !(*to == TripState::Active && *from < TripState::Active) {
+            return Ok(violations);
+        }
+
+        if rules.unpacked_items != GuardPolicy::Allow {
+            let count = database::execute_returning!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Select,
+                    component: crate::Component::Trips,
+                },
+                &mut **transaction,
+                RunError,
+                r#"SELECT COUNT(*) AS "count!"
+                FROM trip_items
+                WHERE trip_id = $1 AND user_id = $2
+                AND pick = true AND pack = false"#,
+                i64,
+                |row| row.count,
+                id,
+                ctx.user.id
+            )
+            .await?;
+
+            if count > 0 {
+                violations.push(GuardViolation {
+                    guard: TransitionGuard::UnpackedItems { count },
+                    policy: rules.unpacked_items,
+                });
+            }
+        }
+
+        if rules.open_todos != GuardPolicy::Allow {
+            let count = database::execute_returning!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Select,
+                    component: crate::Component::Todo,
+                },
+                &mut **transaction,
+                RunError,
+                r#"SELECT COUNT(*) AS "count!"
+                FROM trip_todos AS todo
+                INNER JOIN trips AS trip
+                    ON trip.id = todo.trip_id
+                WHERE todo.trip_id = $1 AND trip.user_id = $2
+                AND todo.done = false"#,
+                i64,
+                |row| row.count,
+                id,
+                ctx.user.id
+            )
+            .await?;
+
+            if count > 0 {
+                violations.push(GuardViolation {
+                    guard: TransitionGuard::OpenTodos { count },
+                    policy: rules.open_todos,
+                });
+            }
+        }
+
+        Ok(violations)
+    }
+
+    #[tracing::instrument]
+    pub async fn find_state_history(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+    ) -> Result<Vec<TripStateChange>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            DbTripStateChangeRow,
+            TripStateChange,
+            RunError,
+            r#"SELECT
+                history.from_state AS "from_state: _",
+                history.to_state AS "to_state: _",
+                history.changed_at,
+                users.username,
+                history.automated
+            FROM trip_state_history AS history
+            INNER JOIN trips AS trip
+                ON trip.id = history.trip_id
+            INNER JOIN users
+                ON users.id = history.user_id
+            WHERE history.trip_id = $1 AND trip.user_id = $2
+            ORDER BY history.changed_at ASC"#,
+            id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    pub async fn set_comment(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        new_comment: &str,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trips
+            SET comment = $1
+            WHERE id = $2 AND user_id = $3",
+            new_comment,
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    #[tracing::instrument]
+    pub async fn set_archived(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trips
+            SET archived = $1
+            WHERE id = $2 AND user_id = $3",
+            archived,
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    #[tracing::instrument]
+    pub async fn save(
+        ctx: &Context,
+        pool: &database::Pool,
+        name: &str,
+        date: TripDate,
+        copy_from: Option<Uuid>,
+    ) -> Result<Uuid, RunError> {
+        let id = Uuid::new_v4();
+
+        let trip_state = TripState::new();
+
+        let mut transaction = pool.begin().await?;
+
+        lock_trip_items(ctx, &mut transaction).await?;
+
+        println!("date: {date:?}");
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO trips
+                (id, name, date, state, user_id)
+            VALUES
+                ($1, $2, $3, $4, $5)",
+            id,
+            name,
+            <TripDate as TryInto<sqlx::postgres::types::PgRange<time::Date>>>::try_into(date)?,
+            trip_state as _,
+            ctx.user.id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO trip_state_history
+                (id, trip_id, from_state, to_state, user_id)
+            VALUES
+                ($1, $2, NULL, $3, $4)",
+            Uuid::new_v4(),
+            id,
+            TripState::new() as _,
+            ctx.user.id,
+        )
+        .await?;
+
+        if let Some(copy_from_trip_id) = copy_from {
+            database::execute!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Insert,
+                    component: crate::Component::Trips,
+                },
+                &mut *transaction,
+                RunError,
+                r"INSERT INTO trip_items (
+                    item_id,
+                    trip_id,
+                    pick,
+                    pack,
+                    ready,
+                    new,
+                    user_id
+                ) SELECT
+                    item_id,
+                    $1 as trip_id,
+                    pick,
+                    false as pack,
+                    false as ready,
+                    false as new,
+                    user_id
+                FROM trip_items
+                WHERE trip_id = $2 AND user_id = $3",
+                id,
+                copy_from_trip_id,
+                ctx.user.id
+            )
+            .await?;
+        }
+
+        // also covers the items that the copied trip does not have
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            r"INSERT INTO trip_items (
+                item_id,
+                trip_id,
+                pick,
+                pack,
+                ready,
+                new,
+                user_id
+            ) SELECT
+                id as item_id,
+                $1 as trip_id,
+                false as pick,
+                false as pack,
+                false as ready,
+                false as new,
+                user_id
+            FROM inventory_items
+            WHERE user_id = $2
+            ON CONFLICT (item_id, trip_id) DO NOTHING",
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    /// Deletes the trip together with everything that references it
+    #[tracing::instrument]
+    pub async fn delete(ctx: &Context, pool: &database::Pool, id: Uuid) -> Result<bool, RunError> {
+        let mut transaction = pool.begin().await?;
+
+        // lock the trip, so nothing can be added to it while we delete
+        let exists: bool = database::query_exists!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            "SELECT id
+            FROM trips
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE",
+            id,
+            ctx.user.id
+        )
+        .await
+        .map_err(|e: sqlx::Error| -> RunError { e.into() })?;
+
+        if !exists {
+            return Ok(false);
+        }
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_items_stages
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_tracks
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_stages
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_items
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_containers
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Todo,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_todos
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_expenses
+            WHERE trip_id = $1",
+            id,
+        )
+        .await?;
+
         database::execute!(
             &database::QueryClassification {
-                query_type: database::QueryType::Insert,
+                query_type: database::QueryType::Delete,
                 component: crate::Component::Trips,
             },
-            pool,
+            &mut *transaction,
             RunError,
-            "INSERT INTO
-                trip_to_trip_types (trip_id, trip_type_id)
-            (SELECT trips.id as trip_id, trip_types.id as trip_type_id
-                FROM trips
-                INNER JOIN trip_types ON true
-                WHERE
-                    trips.id = $1
-                    AND trips.user_id = $3
-                    AND trip_types.id = $2
-                    AND trip_types.user_id = $3)",
+            "DELETE FROM trip_participants
+            WHERE trip_id = $1",
             id,
-            type_id,
-            ctx.user.id
         )
         .await?;
 
-        Ok(())
-    }
-
-    #[tracing::instrument]
-    pub async fn set_state(
-        ctx: &Context,
-        pool: &database::Pool,
-        id: Uuid,
-        new_state: &TripState,
-    ) -> Result<bool, RunError> {
-        let result = database::execute!(
+        database::execute!(
             &database::QueryClassification {
-                query_type: database::QueryType::Update,
+                query_type: database::QueryType::Delete,
                 component: crate::Component::Trips,
             },
-            pool,
+            &mut *transaction,
             RunError,
-            "UPDATE trips
-            SET state = $1
-            WHERE id = $2 and user_id = $3",
-            new_state as _,
+            "DELETE FROM trip_to_trip_types
+            WHERE trip_id = $1",
             id,
-            ctx.user.id
         )
         .await?;
 
-        Ok(result.rows_affected() != 0)
-    }
-
-    #[tracing::instrument]
-    pub async fn set_comment(
-        ctx: &Context,
-        pool: &database::Pool,
-        id: Uuid,
-        new_comment: &str,
-    ) -> Result<bool, RunError> {
         let result = database::execute!(
             &database::QueryClassification {
-                query_type: database::QueryType::Update,
+                query_type: database::QueryType::Delete,
                 component: crate::Component::Trips,
             },
-            pool,
+            &mut *transaction,
             RunError,
-            "UPDATE trips
-            SET comment = $1
-            WHERE id = $2 AND user_id = $3",
-            new_comment,
+            "DELETE FROM trips
+            WHERE id = $1 AND user_id = $2",
             id,
             ctx.user.id
         )
         .await?;
 
+        transaction.commit().await?;
+
         Ok(result.rows_affected() != 0)
     }
 
+    /// Sets the picked state of the given items in the target trip to the one
+    /// they have in the source trip
     #[tracing::instrument]
-    pub async fn save(
+    pub async fn copy_picks(
         ctx: &Context,
         pool: &database::Pool,
-        name: &str,
-        date: TripDate,
-        copy_from: Option<Uuid>,
-    ) -> Result<Uuid, RunError> {
-        let id = Uuid::new_v4();
-
-        let trip_state = TripState::new();
-
-        let mut transaction = pool.begin().await?;
-
-        println!("date: {date:?}");
-
-        database::execute!(
+        target_trip_id: Uuid,
+        source_trip_id: Uuid,
+        item_ids: &[Uuid],
+    ) -> Result<u64, RunError> {
+        let result = database::execute!(
             &database::QueryClassification {
-                query_type: database::QueryType::Insert,
+                query_type: database::QueryType::Update,
                 component: crate::Component::Trips,
             },
-            &mut *transaction,
+            pool,
             RunError,
-            "INSERT INTO trips
-                (id, name, date, state, user_id)
-            VALUES
-                ($1, $2, $3, $4, $5)",
-            id,
-            name,
-            <TripDate as TryInto<sqlx::postgres::types::PgRange<time::Date>>>::try_into(date)?,
-            trip_state as _,
-            ctx.user.id,
+            "UPDATE trip_items AS target
+                SET pick = source.pick
+            FROM trip_items AS source
+            WHERE
+                source.trip_id = $2
+                AND source.item_id = target.item_id
+                AND source.user_id = $4
+                AND target.trip_id = $1
+                AND target.item_id = ANY($3)
+                AND target.user_id = $4",
+            target_trip_id,
+            source_trip_id,
+            item_ids,
+            ctx.user.id
         )
         .await?;
 
-        if let Some(copy_from_trip_id) = copy_from {
-            database::execute!(
-                &database::QueryClassification {
-                    query_type: database::QueryType::Insert,
-                    component: crate::Component::Trips,
-                },
-                &mut *transaction,
-                RunError,
-                r"INSERT INTO trip_items (
-                    item_id,
-                    trip_id,
-                    pick,
-                    pack,
-                    ready,
-                    new,
-                    user_id
-                ) SELECT
-                    item_id,
-                    $1 as trip_id,
-                    pick,
-                    false as pack,
-                    false as ready,
-                    false as new,
-                    user_id
-                FROM trip_items
-                WHERE trip_id = $2 AND user_id = $3",
-                id,
-                copy_from_trip_id,
-                ctx.user.id
-            )
-            .await?;
-        } else {
-            database::execute!(
-                &database::QueryClassification {
-                    query_type: database::QueryType::Insert,
-                    component: crate::Component::Trips,
-                },
-                &mut *transaction,
-                RunError,
-                r"INSERT INTO trip_items (
-                    item_id,
-                    trip_id,
-                    pick,
-                    pack,
-                    ready,
-                    new,
-                    user_id
-                ) SELECT
-                    id as item_id,
-                    $1 as trip_id,
-                    false as pick,
-                    false as pack,
-                    false as ready,
-                    false as new,
-                    user_id
-                FROM inventory_items
-                WHERE user_id = $2",
-                id,
-                ctx.user.id
-            )
-            .await?;
-        }
-
-        transaction.commit().await?;
-
-        Ok(id)
+        Ok(result.rows_affected())
     }
 
     #[tracing::instrument]
@@ -1011,7 +2785,7 @@ impl Trip {
             RunError,
             "
                 SELECT
-                    CAST(COALESCE(SUM(i_item.weight), 0) AS INTEGER) AS total_weight
+                    CAST(COALESCE(SUM(i_item.weight * t_item.quantity), 0) AS INTEGER) AS total_weight
                 FROM trips AS trip
                 INNER JOIN trip_items AS t_item
                     ON t_item.trip_id = trip.id
@@ -1035,7 +2809,7 @@ impl Trip {
     pub fn types(&self) -> &Vec<TripType> {
         self.types
             .as_ref()
-            .expect("you need to call load_trip_types()")
+            .expect("you need to load the trip with TripParts::types")
     }
 
     #[tracing::instrument]
@@ -1050,6 +2824,46 @@ impl Trip {
         self.todos.as_ref().expect("you need to call load_todos()")
     }
 
+    #[tracing::instrument]
+    pub fn stages(&self) -> &Vec<crate::domains::trips::stages::Stage> {
+        self.stages.as_ref().expect("you need to call load_stages()")
+    }
+
+    #[tracing::instrument]
+    pub fn review_hints(&self) -> &Vec<crate::domains::trips::review::ReviewHint> {
+        self.review_hints
+            .as_ref()
+            .expect("you need to call load_review_hints()")
+    }
+
+    #[tracing::instrument]
+    pub fn expenses(&self) -> &crate::domains::trips::expenses::Expenses {
+        self.expenses
+            .as_ref()
+            .expect("you need to call load_expenses()")
+    }
+
+    #[tracing::instrument]
+    pub fn containers(&self) -> &Vec<crate::domains::trips::containers::Container> {
+        self.containers
+            .as_ref()
+            .expect("you need to call load_containers()")
+    }
+
+    #[tracing::instrument]
+    pub fn tracks(&self) -> &Vec<crate::domains::trips::tracks::Track> {
+        self.tracks
+            .as_ref()
+            .expect("you need to load the trip with TripParts::tracks")
+    }
+
+    #[tracing::instrument]
+    pub fn state_history(&self) -> &Vec<TripStateChange> {
+        self.state_history
+            .as_ref()
+            .expect("you need to load the trip with TripParts::state_history")
+    }
+
     #[tracing::instrument]
     pub fn total_picked_weight(&self) -> i32 {
         self.categories()
@@ -1060,12 +2874,24 @@ impl Trip {
                     .as_ref()
                     .unwrap()
                     .iter()
-                    .filter_map(|item| Some(item.item.weight).filter(|_| item.picked))
+                    .filter_map(|item| Some(item.weight()).filter(|_| item.picked))
                     .sum::<i32>()
             })
             .sum::<i32>()
     }
 
+    #[tracing::instrument]
+    pub async fn load_review_hints(
+        &mut self,
+        ctx: &Context,
+        pool: &database::Pool,
+    ) -> Result<(), RunError> {
+        self.review_hints = Some(
+            crate::domains::trips::review::ReviewHint::findall(ctx, pool, self.id, None).await?,
+        );
+        Ok(())
+    }
+
     #[tracing::instrument]
     pub async fn load_todos(
         &mut self,
@@ -1084,12 +2910,57 @@ impl Trip {
     }
 
     #[tracing::instrument]
-    pub async fn load_trip_types(
+    pub async fn load_stages(
+        &mut self,
+        ctx: &Context,
+        pool: &database::Pool,
+    ) -> Result<(), RunError> {
+        self.stages = Some(
+            crate::domains::trips::stages::Stage::findall(
+                ctx,
+                pool,
+                crate::domains::trips::stages::Container { trip_id: self.id },
+            )
+            .await?,
+        );
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub async fn load_expenses(
+        &mut self,
+        ctx: &Context,
+        pool: &database::Pool,
+    ) -> Result<(), RunError> {
+        self.expenses = Some(
+            crate::domains::trips::expenses::Expenses::find(
+                ctx,
+                pool,
+                crate::domains::trips::expenses::Container { trip_id: self.id },
+            )
+            .await?,
+        );
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub async fn load_containers(
         &mut self,
         ctx: &Context,
         pool: &database::Pool,
     ) -> Result<(), RunError> {
-        let types = database::query_all!(
+        self.containers =
+            Some(crate::domains::trips::containers::Container::findall(ctx, pool, self.id).await?);
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn find_trip_types(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Vec<TripType>, RunError> {
+        database::query_all!(
             &database::QueryClassification {
                 query_type: database::QueryType::Select,
                 component: crate::Component::Trips,
@@ -1114,110 +2985,13 @@ impl Trip {
                 trips.id IS NOT NULL AS "active!"
             FROM trip_types AS type
                 LEFT JOIN trips
-                ON trips.id = type.id
-            WHERE type.user_id = $2
-            "#,
-            self.id,
-            ctx.user.id
-        )
-        .await?;
-
-        self.types = Some(types);
-        Ok(())
-    }
-
-    #[tracing::instrument]
-    pub async fn sync_trip_items_with_inventory(
-        &self,
-        ctx: &Context,
-        pool: &database::Pool,
-    ) -> Result<(), RunError> {
-        // we need to get all items that are part of the inventory but not
-        // part of the trip items
-        //
-        // then, we know which items we need to sync. there are different
-        // states for them:
-        //
-        // * if the trip is new (it's state is INITIAL), we can just forward
-        //   as-is
-        // * if the trip is not new, we have to make these new items prominently
-        //   visible so the user knows that there might be new items to
-        //   consider
-        struct Row {
-            item_id: Uuid,
-        }
-
-        impl TryFrom<Row> for Uuid {
-            type Error = RunError;
-
-            fn try_from(value: Row) -> Result<Self, Self::Error> {
-                Ok(value.item_id)
-            }
-        }
-
-        let unsynced_items: Vec<Uuid> = database::query_all!(
-            &database::QueryClassification {
-                query_type: database::QueryType::Select,
-                component: crate::Component::Trips,
-            },
-            pool,
-            Row,
-            Uuid,
-            RunError,
-            "
-            SELECT
-                i_item.id AS item_id
-            FROM inventory_items AS i_item
-                LEFT JOIN (
-                    SELECT t_item.item_id AS item_id, t_item.user_id AS user_id
-                    FROM trip_items AS t_item
-                    WHERE t_item.trip_id = $1 AND t_item.user_id = $2
-                ) AS t_item
-                ON t_item.item_id = i_item.id
-            WHERE t_item.item_id IS NULL AND i_item.user_id = $2",
-            self.id,
+                ON trips.id = type.id
+            WHERE type.user_id = $2
+            "#,
+            trip_id,
             ctx.user.id
         )
-        .await?;
-
-        // only mark as new when the trip not already underway
-        let mark_as_new = self.state < TripState::Active;
-
-        for unsynced_item in &unsynced_items {
-            database::execute!(
-                &database::QueryClassification {
-                    query_type: database::QueryType::Insert,
-                    component: crate::Component::Trips,
-                },
-                pool,
-                RunError,
-                "
-                INSERT INTO trip_items
-                    (
-                        item_id,
-                        trip_id,
-                        pick,
-                        pack,
-                        ready,
-                        new,
-                        user_id
-                    )
-                    VALUES ($1, $2, $3, $4, $5, $6, $7)
-                ",
-                unsynced_item,
-                self.id,
-                false,
-                false,
-                false,
-                mark_as_new,
-                ctx.user.id
-            )
-            .await?;
-        }
-
-        tracing::info!("unsynced items: {:?}", &unsynced_items);
-
-        Ok(())
+        .await
     }
 
     #[tracing::instrument]
@@ -1226,6 +3000,16 @@ impl Trip {
         ctx: &Context,
         pool: &database::Pool,
     ) -> Result<(), RunError> {
+        self.categories = Some(Self::find_categories(ctx, pool, self.id).await?);
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    async fn find_categories(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Vec<TripCategory>, RunError> {
         let mut categories: Vec<TripCategory> = vec![];
         // we can ignore the return type as we collect into `categories`
         // in the `map_ok()` closure
@@ -1241,7 +3025,11 @@ impl Trip {
             item_is_picked: Option<bool>,
             item_is_packed: Option<bool>,
             item_is_ready: Option<bool>,
+            item_is_worn: Option<bool>,
             item_is_new: Option<bool>,
+            item_note: Option<String>,
+            item_quantity: Option<i32>,
+            item_container_id: Option<Uuid>,
         }
 
         struct RowParsed {
@@ -1276,7 +3064,11 @@ impl Trip {
                             picked: row.item_is_picked.unwrap(),
                             packed: row.item_is_packed.unwrap(),
                             ready: row.item_is_ready.unwrap(),
+                            worn: row.item_is_worn.unwrap(),
                             new: row.item_is_new.unwrap(),
+                            note: row.item_note,
+                            quantity: row.item_quantity.unwrap(),
+                            container_id: row.item_container_id,
                         }),
                         None => None,
                     },
@@ -1293,7 +3085,7 @@ impl Trip {
             Row,
             RowParsed,
             RunError,
-            r"
+            r#"
                 WITH trip_items AS (
                     SELECT
                         trip.trip_id AS trip_id,
@@ -1306,7 +3098,11 @@ impl Trip {
                         trip.pick AS item_is_picked,
                         trip.pack AS item_is_packed,
                         trip.ready AS item_is_ready,
+                        trip.worn AS item_is_worn,
                         trip.new AS item_is_new,
+                        trip.note AS item_note,
+                        trip.quantity AS item_quantity,
+                        trip.container_id AS item_container_id,
                         trip.user_id AS user_id
                     FROM trip_items AS trip
                     INNER JOIN inventory_items AS item
@@ -1318,21 +3114,26 @@ impl Trip {
                 SELECT
                     category.id AS category_id,
                     category.name AS category_name,
-                    trip_items.trip_id AS trip_id,
-                    trip_items.item_id AS item_id,
-                    trip_items.item_name AS item_name,
+                    -- all item columns are NULL for categories without items in the trip
+                    trip_items.trip_id AS "trip_id?",
+                    trip_items.item_id AS "item_id?",
+                    trip_items.item_name AS "item_name?",
                     trip_items.item_description AS item_description,
-                    trip_items.item_weight AS item_weight,
-                    trip_items.item_is_picked AS item_is_picked,
-                    trip_items.item_is_packed AS item_is_packed,
-                    trip_items.item_is_ready AS item_is_ready,
-                    trip_items.item_is_new AS item_is_new
+                    trip_items.item_weight AS "item_weight?",
+                    trip_items.item_is_picked AS "item_is_picked?",
+                    trip_items.item_is_packed AS "item_is_packed?",
+                    trip_items.item_is_ready AS "item_is_ready?",
+                    trip_items.item_is_worn AS "item_is_worn?",
+                    trip_items.item_is_new AS "item_is_new?",
+                    trip_items.item_note AS item_note,
+                    trip_items.item_quantity AS "item_quantity?",
+                    trip_items.item_container_id AS item_container_id
                 FROM inventory_items_categories AS category
                     LEFT JOIN trip_items
                     ON trip_items.category_id = category.id
                 WHERE category.user_id = $2
-            ",
-            self.id,
+            "#,
+            trip_id,
             ctx.user.id
         )
         .await?;
@@ -1359,9 +3160,7 @@ impl Trip {
             }
         }
 
-        self.categories = Some(categories);
-
-        Ok(())
+        Ok(categories)
         // .fetch(pool)
         // .map_ok(|row| -> Result<(), Error> {
         //     let mut category = TripCategory {
@@ -1515,6 +3314,350 @@ impl TripsType {
 
         Ok(result.rows_affected() != 0)
     }
+
+    /// Removes the type from all trips. Todos that were already added to trips
+    /// stay there.
+    #[tracing::instrument]
+    pub async fn delete(ctx: &Context, pool: &database::Pool, id: Uuid) -> Result<bool, RunError> {
+        let mut transaction = pool.begin().await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_to_trip_types
+            WHERE
+                trip_type_id = $1
+                AND EXISTS(SELECT 1 FROM trip_types WHERE id = $1 AND user_id = $2)",
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Todo,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_type_todos
+            WHERE
+                trip_type_id = $1
+                AND EXISTS(SELECT 1 FROM trip_types WHERE id = $1 AND user_id = $2)",
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_types
+            WHERE id = $1 AND user_id = $2",
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Merges the type `id` into `into_id`: All trips of `id` get `into_id`
+    /// instead, together with its default todos like when the type is added
+    /// to a trip, unless the trip already started. Default todos are moved
+    /// over unless `into_id` already has one with the same description, and
+    /// `id` is deleted.
+    #[tracing::instrument]
+    pub async fn merge(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        into_id: Uuid,
+    ) -> Result<bool, RunError> {
+        if id == into_id {
+            return Err(RunError::Request(RequestError::InvalidFormElement {
+                name: "merge-into-id".to_string(),
+                message: "a trip type cannot be merged into itself".to_string(),
+            }));
+        }
+
+        let mut transaction = pool.begin().await?;
+
+        let found = database::execute_returning!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            r#"SELECT COUNT(*) AS "count!"
+            FROM trip_types
+            WHERE (id = $1 OR id = $2) AND user_id = $3"#,
+            i64,
+            |row| row.count,
+            id,
+            into_id,
+            ctx.user.id
+        )
+        .await?;
+
+        if found != 2 {
+            return Ok(false);
+        }
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO trip_to_trip_types
+                (trip_id, trip_type_id)
+            SELECT trip_id, $2
+            FROM trip_to_trip_types
+            WHERE trip_type_id = $1
+            ON CONFLICT DO NOTHING",
+            id,
+            into_id
+        )
+        .await?;
+
+        // todos the trip already got from `id` under the same description are
+        // not added a second time. trips that already started do not get any,
+        // their due dates would be in the past
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Todo,
+            },
+            &mut *transaction,
+            RunError,
+            "INSERT INTO trip_todos
+                (id, trip_id, description, done, position, due_days_before, trip_type_todo_id)
+            SELECT
+                gen_random_uuid(),
+                ttt.trip_id,
+                type_todo.description,
+                false,
+                COALESCE((SELECT MAX(position) FROM trip_todos WHERE trip_id = ttt.trip_id), -1)
+                    + row_number() OVER (PARTITION BY ttt.trip_id ORDER BY type_todo.position),
+                type_todo.due_days_before,
+                type_todo.id
+            FROM trip_to_trip_types AS ttt
+            INNER JOIN trips AS trip
+                ON trip.id = ttt.trip_id
+            INNER JOIN trip_type_todos AS type_todo
+                ON type_todo.trip_type_id = $2
+            WHERE
+                ttt.trip_type_id = $1
+                AND trip.state < 'active'
+                AND NOT EXISTS(
+                    SELECT 1
+                    FROM trip_todos AS existing
+                    WHERE existing.trip_id = ttt.trip_id
+                        AND existing.description = type_todo.description
+                )
+            ON CONFLICT (trip_id, trip_type_todo_id) DO NOTHING",
+            id,
+            into_id
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_to_trip_types
+            WHERE trip_type_id = $1",
+            id
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Todo,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_type_todos AS todo
+            WHERE
+                todo.trip_type_id = $1
+                AND EXISTS(
+                    SELECT 1
+                    FROM trip_type_todos AS existing
+                    WHERE existing.trip_type_id = $2 AND existing.description = todo.description
+                )",
+            id,
+            into_id
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Todo,
+            },
+            &mut *transaction,
+            RunError,
+            "UPDATE trip_type_todos
+            SET
+                trip_type_id = $2,
+                position = position + COALESCE(
+                    (SELECT MAX(position) + 1 FROM trip_type_todos WHERE trip_type_id = $2),
+                    0
+                )
+            WHERE trip_type_id = $1",
+            id,
+            into_id
+        )
+        .await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "DELETE FROM trip_types
+            WHERE id = $1",
+            id
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    #[tracing::instrument]
+    pub fn todos(&self) -> &Vec<TripTypeTodo> {
+        self.todos.as_ref().expect("you need to call load_todos()")
+    }
+
+    /// Loads the default todos of all given types at once
+    #[tracing::instrument]
+    pub async fn load_todos(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_types: &mut [Self],
+    ) -> Result<(), RunError> {
+        let mut todos: Vec<TripTypeTodo> = database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Todo,
+            },
+            pool,
+            DbTripTypeTodoRow,
+            TripTypeTodo,
+            RunError,
+            "SELECT
+                type_todo.id AS id,
+                type_todo.trip_type_id AS trip_type_id,
+                type_todo.description AS description,
+                type_todo.due_days_before AS due_days_before
+            FROM trip_type_todos AS type_todo
+            INNER JOIN trip_types
+                ON trip_types.id = type_todo.trip_type_id
+            WHERE trip_types.user_id = $1
+            ORDER BY type_todo.position",
+            ctx.user.id
+        )
+        .await?;
+
+        for trip_type in trip_types {
+            let (own, rest) = todos
+                .into_iter()
+                .partition(|todo| todo.trip_type_id == trip_type.id);
+            trip_type.todos = Some(own);
+            todos = rest;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub async fn add_todo(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        description: &str,
+        due_days_before: Option<i32>,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Todo,
+            },
+            pool,
+            RunError,
+            "INSERT INTO trip_type_todos
+                (id, trip_type_id, position, description, due_days_before)
+            SELECT
+                $1,
+                trip_types.id,
+                COALESCE(
+                    (SELECT MAX(position) + 1 FROM trip_type_todos WHERE trip_type_id = trip_types.id),
+                    0
+                ),
+                $3,
+                $4
+            FROM trip_types
+            WHERE trip_types.id = $2 AND trip_types.user_id = $5",
+            Uuid::new_v4(),
+            id,
+            description,
+            due_days_before,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Todos that were already added to trips stay there
+    #[tracing::instrument]
+    pub async fn remove_todo(
+        ctx: &Context,
+        pool: &database::Pool,
+        id: Uuid,
+        todo_id: Uuid,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Todo,
+            },
+            pool,
+            RunError,
+            "DELETE FROM trip_type_todos
+            WHERE
+                id = $1
+                AND trip_type_id = $2
+                AND EXISTS(SELECT 1 FROM trip_types WHERE id = $2 AND user_id = $3)",
+            todo_id,
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
 }
 
 pub struct DbTripsTypesRow {
@@ -1533,6 +3676,7 @@ pub enum TripTypeAttribute {
 pub struct TripsType {
     pub id: Uuid,
     pub name: String,
+    pub todos: Option<Vec<TripTypeTodo>>,
 }
 
 impl TryFrom<DbTripsTypesRow> for TripsType {
@@ -1542,6 +3686,38 @@ impl TryFrom<DbTripsTypesRow> for TripsType {
         Ok(Self {
             id: row.id,
             name: row.name,
+            todos: None,
+        })
+    }
+}
+
+/// A todo that is added to every trip of the type
+#[derive(Debug)]
+pub struct TripTypeTodo {
+    pub id: Uuid,
+    pub trip_type_id: Uuid,
+    pub description: String,
+    pub due: Option<crate::domains::trips::todos::Due>,
+}
+
+pub struct DbTripTypeTodoRow {
+    pub id: Uuid,
+    pub trip_type_id: Uuid,
+    pub description: String,
+    pub due_days_before: Option<i32>,
+}
+
+impl TryFrom<DbTripTypeTodoRow> for TripTypeTodo {
+    type Error = RunError;
+
+    fn try_from(row: DbTripTypeTodoRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            trip_type_id: row.trip_type_id,
+            description: row.description,
+            due: row
+                .due_days_before
+                .map(crate::domains::trips::todos::Due::BeforeStart),
         })
     }
 }
@@ -1,22 +1,41 @@
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
+    http::header::{self, HeaderMap, HeaderName},
     response::IntoResponse,
     routing::{get, post},
     Router,
 };
 
+use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{AppState, Context, RunError, RequestError, TopLevelPage};
+use crate::{htmx, AppState, Context, RunError, RequestError, TopLevelPage};
 
 use super::{model, view};
 use crate::models::User;
 
+/// How the items to pack are grouped in the package list
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Grouping {
+    #[default]
+    Category,
+    Container,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackageListQuery {
+    #[serde(default)]
+    group: Grouping,
+}
+
 #[tracing::instrument]
 pub async fn base(
     Extension(current_user): Extension<User>,
     State(state): State<AppState>,
     Path(trip_id): Path<Uuid>,
+    Query(query): Query<PackageListQuery>,
 ) -> Result<impl IntoResponse, RunError> {
     let ctx = Context::build(current_user);
     let mut trip = model::Trip::find(&ctx, &state.database_pool, trip_id)
@@ -27,13 +46,60 @@ pub async fn base(
 
     trip.load_categories(&ctx, &state.database_pool).await?;
 
+    trip.load_containers(&ctx, &state.database_pool).await?;
+
     Ok(crate::view::Root::build(
         &ctx,
-        &view::packagelist::TripPackageList::build(&trip),
+        &view::packagelist::TripPackageList::build(&trip, query.group),
         Some(&TopLevelPage::Trips),
     ))
 }
 
+#[tracing::instrument]
+pub async fn print(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let mut trip = model::Trip::find(&ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    trip.load_categories(&ctx, &state.database_pool).await?;
+
+    Ok(view::packagelist::TripPackageListPrint::build(&trip))
+}
+
+#[tracing::instrument]
+pub async fn pdf(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let mut trip = model::Trip::find(&ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    trip.load_categories(&ctx, &state.database_pool).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"packagelist-{trip_id}.pdf\""),
+            ),
+        ],
+        view::pdf::TripPackageListPdf::build(&trip),
+    ))
+}
+
 #[tracing::instrument]
 pub async fn set_item_pack_html(
     Extension(current_user): Extension<User>,
@@ -150,11 +216,112 @@ pub async fn set_item_unready_html(
     ))
 }
 
+/// Sets the state of all picked items of a category and returns the updated
+/// category block
+#[tracing::instrument]
+async fn set_category_state(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+    category_id: Uuid,
+    key: model::TripItemStateKey,
+) -> Result<(model::Trip, HeaderMap), RunError> {
+    let mut trip = model::Trip::find(ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    model::TripItem::set_state_bulk(
+        ctx,
+        &state.database_pool,
+        trip_id,
+        category_id,
+        None,
+        key,
+        true,
+    )
+    .await?;
+
+    trip.load_categories(ctx, &state.database_pool).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert::<HeaderName>(
+        htmx::ResponseHeaders::Trigger.into(),
+        htmx::Event::TripItemEdited.into(),
+    );
+
+    Ok((trip, headers))
+}
+
+fn find_category(trip: &model::Trip, category_id: Uuid) -> Result<&model::TripCategory, RunError> {
+    trip.categories()
+        .iter()
+        .find(|category| category.category.id == category_id)
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("category with id {category_id} not found"),
+        }))
+}
+
+#[tracing::instrument]
+pub async fn pack_category_htmx(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, category_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let (trip, headers) = set_category_state(
+        &ctx,
+        &state,
+        trip_id,
+        category_id,
+        model::TripItemStateKey::Pack,
+    )
+    .await?;
+
+    Ok((
+        headers,
+        view::packagelist::TripPackageListCategoryBlockReady::build(
+            &trip,
+            find_category(&trip, category_id)?,
+        ),
+    ))
+}
+
+#[tracing::instrument]
+pub async fn ready_category_htmx(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, category_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let (trip, headers) = set_category_state(
+        &ctx,
+        &state,
+        trip_id,
+        category_id,
+        model::TripItemStateKey::Ready,
+    )
+    .await?;
+
+    Ok((
+        headers,
+        view::packagelist::TripPackageListCategoryBlockUnready::build(
+            &trip,
+            find_category(&trip, category_id)?,
+        ),
+    ))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(base))
+        .route("/print", get(print))
+        .route("/pdf", get(pdf))
         .route("/item/{id}/pack", post(set_item_pack_html))
         .route("/item/{id}/unpack", post(set_item_unpack_htmx))
         .route("/item/{id}/ready", post(set_item_ready_htmx))
         .route("/item/{id}/unready", post(set_item_unready_html))
+        .route("/category/{id}/pack", post(pack_category_htmx))
+        .route("/category/{id}/ready", post(ready_category_htmx))
 }
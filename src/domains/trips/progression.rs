@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use crate::StartError;
+
+use super::model;
+
+/// Periodically moves trips to the next state based on their date, see
+/// [`model::progress_trip_states`].
+///
+/// This never returns. Failed runs are logged and retried on the next tick.
+pub async fn progress_trip_states_periodically(
+    pool: database::Pool,
+    interval: Duration,
+) -> Result<(), StartError> {
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        match model::progress_trip_states(&pool).await {
+            Ok(progression) => {
+                for (to_state, trips) in [
+                    (model::TripState::Active, &progression.activated),
+                    (model::TripState::Review, &progression.reviewed),
+                ] {
+                    for trip_id in trips {
+                        tracing::info!(%trip_id, %to_state, "trip state progressed automatically");
+                    }
+
+                    metrics::counter!(
+                        "packager_trips_state_progression_total",
+                        &[("to_state", to_state.to_string())]
+                    )
+                    .increment(trips.len() as u64);
+                }
+
+                metrics::counter!("packager_trips_state_progression_runs_total").increment(1);
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "automatic trip state progression failed");
+                metrics::counter!("packager_trips_state_progression_failures_total").increment(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,635 @@
+//! Review of a trip after it is over: what of the gear was actually used, what
+//! was missing, and what broke. The review is shown as hints the next time an
+//! item is picked for a trip of the same type.
+
+pub mod view;
+
+use std::fmt;
+
+use axum::{
+    Extension, Router,
+    extract::{Form, Path, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    response::{IntoResponse, Redirect},
+    routing::post,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState, Context, RequestError, RunError, htmx,
+    models::User,
+    routing::{get_referer, parse_or_empty},
+};
+
+use super::model::{Trip, TripState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "trip_item_review")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Verdict {
+    Used,
+    Unused,
+    /// Was not taken, but would have been needed
+    Missing,
+    Broken,
+}
+
+impl Verdict {
+    pub const ALL: [Self; 4] = [Self::Used, Self::Unused, Self::Missing, Self::Broken];
+
+    #[must_use]
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Used => "mdi-check-circle-outline",
+            Self::Unused => "mdi-sleep",
+            Self::Missing => "mdi-help-circle-outline",
+            Self::Broken => "mdi-hammer-wrench",
+        }
+    }
+
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Used => "used",
+            Self::Unused => "unused",
+            Self::Missing => "missing",
+            Self::Broken => "broken",
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Used => "Used",
+                Self::Unused => "Unused",
+                Self::Missing => "Missing",
+                Self::Broken => "Broken",
+            }
+        )
+    }
+}
+
+impl std::str::FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|verdict| verdict.id() == s)
+            .ok_or_else(|| format!("unknown verdict \"{s}\""))
+    }
+}
+
+#[derive(Debug)]
+pub struct ReviewItem {
+    pub id: Uuid,
+    pub name: String,
+    pub weight: i32,
+    pub category_name: String,
+    pub packed: bool,
+    pub verdict: Option<Verdict>,
+}
+
+struct ReviewItemRow {
+    id: Uuid,
+    name: String,
+    weight: i32,
+    category_name: String,
+    packed: bool,
+    verdict: Option<Verdict>,
+}
+
+impl TryFrom<ReviewItemRow> for ReviewItem {
+    type Error = RunError;
+
+    fn try_from(row: ReviewItemRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            weight: row.weight,
+            category_name: row.category_name,
+            packed: row.packed,
+            verdict: row.verdict,
+        })
+    }
+}
+
+impl ReviewItem {
+    /// Only items that were packed or already reviewed are part of the review
+    #[must_use]
+    pub fn is_reviewable(&self) -> bool {
+        self.packed || self.verdict.is_some()
+    }
+
+    #[tracing::instrument]
+    pub async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ReviewItemRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    i_item.id AS id,
+                    i_item.name AS name,
+                    i_item.weight AS weight,
+                    category.name AS category_name,
+                    t_item.pack AS packed,
+                    t_item.review AS "verdict: _"
+                FROM trip_items AS t_item
+                INNER JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                INNER JOIN inventory_items_categories AS category
+                    ON category.id = i_item.category_id
+                WHERE t_item.trip_id = $1 AND t_item.user_id = $2
+                ORDER BY category.name, i_item.name
+            "#,
+            trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    pub async fn find(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ReviewItemRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    i_item.id AS id,
+                    i_item.name AS name,
+                    i_item.weight AS weight,
+                    category.name AS category_name,
+                    t_item.pack AS packed,
+                    t_item.review AS "verdict: _"
+                FROM trip_items AS t_item
+                INNER JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                INNER JOIN inventory_items_categories AS category
+                    ON category.id = i_item.category_id
+                WHERE t_item.trip_id = $1 AND t_item.item_id = $2 AND t_item.user_id = $3
+            "#,
+            trip_id,
+            item_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    /// Sets the verdict of an item. Marking an item as broken adds a todo to
+    /// the trip to take care of it, which is removed again if the verdict
+    /// changes before the todo is done.
+    ///
+    /// Returns whether the todos of the trip changed, or `None` if the item
+    /// does not exist.
+    #[tracing::instrument]
+    pub async fn set_verdict(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Uuid,
+        verdict: Option<Verdict>,
+    ) -> Result<Option<bool>, RunError> {
+        let mut transaction = pool.begin().await?;
+
+        let Some(item) = database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            ReviewItemRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    i_item.id AS id,
+                    i_item.name AS name,
+                    i_item.weight AS weight,
+                    category.name AS category_name,
+                    t_item.pack AS packed,
+                    t_item.review AS "verdict: _"
+                FROM trip_items AS t_item
+                INNER JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                INNER JOIN inventory_items_categories AS category
+                    ON category.id = i_item.category_id
+                WHERE t_item.trip_id = $1 AND t_item.item_id = $2 AND t_item.user_id = $3
+                FOR UPDATE OF t_item
+            "#,
+            trip_id,
+            item_id,
+            ctx.user.id
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            "UPDATE trip_items
+                SET review = $1
+                WHERE trip_id = $2
+                AND item_id = $3
+                AND user_id = $4",
+            verdict as _,
+            trip_id,
+            item_id,
+            ctx.user.id
+        )
+        .await?;
+
+        let todos_changed = if verdict == Some(Verdict::Broken) {
+            database::execute!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Insert,
+                    component: crate::Component::Todo,
+                },
+                &mut *transaction,
+                RunError,
+                "INSERT INTO trip_todos
+                    (id, description, done, trip_id, position, item_id)
+                VALUES (
+                    $1,
+                    $2,
+                    false,
+                    $3,
+                    COALESCE((SELECT MAX(position) + 1 FROM trip_todos WHERE trip_id = $3), 0),
+                    $4
+                )
+                ON CONFLICT (trip_id, item_id) DO NOTHING",
+                Uuid::new_v4(),
+                format!("Repair or replace {}", item.name),
+                trip_id,
+                item_id
+            )
+            .await?
+            .rows_affected()
+                != 0
+        } else if item.verdict == Some(Verdict::Broken) {
+            database::execute!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Delete,
+                    component: crate::Component::Todo,
+                },
+                &mut *transaction,
+                RunError,
+                "DELETE FROM trip_todos
+                WHERE trip_id = $1 AND item_id = $2 AND NOT done",
+                trip_id,
+                item_id
+            )
+            .await?
+            .rows_affected()
+                != 0
+        } else {
+            false
+        };
+
+        transaction.commit().await?;
+
+        Ok(Some(todos_changed))
+    }
+}
+
+#[derive(Debug)]
+pub struct Review {
+    pub lesson: Option<String>,
+    pub items: Vec<ReviewItem>,
+}
+
+struct LessonRow {
+    lesson: Option<String>,
+}
+
+impl TryFrom<LessonRow> for Option<String> {
+    type Error = RunError;
+
+    fn try_from(row: LessonRow) -> Result<Self, Self::Error> {
+        Ok(row.lesson)
+    }
+}
+
+impl Review {
+    #[tracing::instrument]
+    pub async fn find(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Option<Self>, RunError> {
+        let Some(lesson) = database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            LessonRow,
+            Option<String>,
+            RunError,
+            "SELECT lesson FROM trips WHERE id = $1 AND user_id = $2",
+            trip_id,
+            ctx.user.id
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            lesson,
+            items: ReviewItem::findall(ctx, pool, trip_id).await?,
+        }))
+    }
+
+    #[tracing::instrument]
+    pub async fn set_lesson(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        lesson: &str,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "UPDATE trips
+            SET lesson = $1
+            WHERE id = $2 AND user_id = $3",
+            Some(lesson).filter(|lesson| !lesson.is_empty()),
+            trip_id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+}
+
+/// The most recent review of an item on an earlier trip that shares a type
+/// with the current one
+#[derive(Debug)]
+pub struct ReviewHint {
+    pub item_id: Uuid,
+    pub trip_id: Uuid,
+    pub trip_name: String,
+    pub verdict: Verdict,
+}
+
+struct ReviewHintRow {
+    item_id: Uuid,
+    trip_id: Uuid,
+    trip_name: String,
+    verdict: Verdict,
+}
+
+impl TryFrom<ReviewHintRow> for ReviewHint {
+    type Error = RunError;
+
+    fn try_from(row: ReviewHintRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            item_id: row.item_id,
+            trip_id: row.trip_id,
+            trip_name: row.trip_name,
+            verdict: row.verdict,
+        })
+    }
+}
+
+impl ReviewHint {
+    /// Finds the hints for all items of the trip, or only for the given item.
+    /// Items that were simply used are not worth a hint.
+    #[tracing::instrument]
+    pub async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        item_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            ReviewHintRow,
+            Self,
+            RunError,
+            r#"
+                SELECT DISTINCT ON (t_item.item_id)
+                    t_item.item_id AS item_id,
+                    trip.id AS trip_id,
+                    trip.name AS trip_name,
+                    t_item.review AS "verdict!: _"
+                FROM trip_items AS t_item
+                INNER JOIN trips AS trip
+                    ON trip.id = t_item.trip_id
+                INNER JOIN trips AS current
+                    ON current.id = $1 AND current.user_id = $2
+                WHERE
+                    t_item.user_id = $2
+                    AND t_item.review IS NOT NULL
+                    AND t_item.review != 'used'
+                    AND ($3::uuid IS NULL OR t_item.item_id = $3)
+                    AND lower(trip.date) < lower(current.date)
+                    AND EXISTS (
+                        SELECT 1
+                        FROM trip_to_trip_types AS trip_type
+                        INNER JOIN trip_to_trip_types AS current_type
+                            ON current_type.trip_type_id = trip_type.trip_type_id
+                        WHERE
+                            trip_type.trip_id = trip.id
+                            AND current_type.trip_id = current.id
+                    )
+                ORDER BY t_item.item_id, lower(trip.date) DESC
+            "#,
+            trip_id,
+            ctx.user.id,
+            item_id
+        )
+        .await
+    }
+}
+
+impl fmt::Display for ReviewHint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.verdict {
+            Verdict::Used => write!(f, "Used on {}", self.trip_name),
+            Verdict::Unused => write!(f, "Not used on {}", self.trip_name),
+            Verdict::Missing => write!(f, "Was missing on {}", self.trip_name),
+            Verdict::Broken => write!(f, "Broke on {}", self.trip_name),
+        }
+    }
+}
+
+/// Reviews can only be changed while the trip is in review
+#[tracing::instrument]
+async fn ensure_in_review(ctx: &Context, state: &AppState, trip_id: Uuid) -> Result<(), RunError> {
+    let trip = Trip::find(ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    if trip.state == TripState::Review {
+        Ok(())
+    } else {
+        Err(RunError::Request(RequestError::InvalidState {
+            message: format!("trip is in state {}, not in review", trip.state),
+        }))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VerdictUpdate {
+    #[serde(default, deserialize_with = "parse_or_empty")]
+    verdict: Option<Verdict>,
+}
+
+/// Returns whether the todos of the trip changed
+async fn update_verdict(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+    item_id: Uuid,
+    verdict: Option<Verdict>,
+) -> Result<bool, RunError> {
+    ensure_in_review(ctx, state, trip_id).await?;
+
+    ReviewItem::set_verdict(ctx, &state.database_pool, trip_id, item_id, verdict)
+        .await?
+        .ok_or_else(|| {
+            RunError::Request(RequestError::NotFound {
+                message: format!("item with id {item_id} not found for trip {trip_id}"),
+            })
+        })
+}
+
+#[tracing::instrument]
+pub async fn set_verdict(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Form(form): Form<VerdictUpdate>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    update_verdict(&ctx, &state, trip_id, item_id, form.verdict).await?;
+    Ok(Redirect::to(get_referer(&headers)?))
+}
+
+#[tracing::instrument]
+pub async fn set_verdict_htmx(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, item_id)): Path<(Uuid, Uuid)>,
+    Form(form): Form<VerdictUpdate>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let todos_changed = update_verdict(&ctx, &state, trip_id, item_id, form.verdict).await?;
+
+    let item = ReviewItem::find(&ctx, &state.database_pool, trip_id, item_id)
+        .await?
+        .ok_or_else(|| {
+            RunError::Request(RequestError::NotFound {
+                message: format!("item with id {item_id} not found for trip {trip_id}"),
+            })
+        })?;
+
+    // the todos are shown elsewhere on the page, so it needs to be refreshed
+    let mut headers = HeaderMap::new();
+    if todos_changed {
+        headers.insert::<HeaderName>(
+            htmx::ResponseHeaders::Refresh.into(),
+            HeaderValue::from_static("true"),
+        );
+    }
+    Ok((headers, view::ReviewItemRow::build(trip_id, &item, true)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MissingItem {
+    #[serde(rename = "missing-item-id")]
+    item_id: Uuid,
+}
+
+#[tracing::instrument]
+pub async fn add_missing(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    Form(form): Form<MissingItem>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    update_verdict(&ctx, &state, trip_id, form.item_id, Some(Verdict::Missing)).await?;
+    Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LessonUpdate {
+    #[serde(rename = "new-lesson")]
+    new_lesson: String,
+}
+
+#[tracing::instrument]
+pub async fn set_lesson(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    Form(form): Form<LessonUpdate>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    ensure_in_review(&ctx, &state, trip_id).await?;
+
+    if Review::set_lesson(&ctx, &state.database_pool, trip_id, &form.new_lesson).await? {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/items/{id}/verdict", post(set_verdict))
+        .route("/items/{id}/verdict/htmx", post(set_verdict_htmx))
+        .route("/missing", post(add_missing))
+        .route("/lesson", post(set_lesson))
+}
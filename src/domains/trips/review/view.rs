@@ -0,0 +1,204 @@
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use super::{Review, ReviewHint, ReviewItem, Verdict};
+use crate::domains::trips::model::{Trip, TripState};
+
+pub struct ReviewSection;
+
+impl ReviewSection {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip, review: &Review) -> Markup {
+        let editable = trip.state == TripState::Review;
+        let missing_candidates = review
+            .items
+            .iter()
+            .filter(|item| !item.is_reviewable())
+            .collect::<Vec<_>>();
+
+        html!(
+            div #review ."flex" ."flex-col" ."gap-5" {
+                h1 ."text-xl" { "Review" }
+                @if editable {
+                    p {
+                        "How did the gear work out? Marking an item as broken adds a todo to take care of it. "
+                        "The review is shown the next time the item is picked for a trip of the same type."
+                    }
+                }
+                table
+                    ."table"
+                    ."table-auto"
+                    ."border-collapse"
+                    ."border-spacing-0"
+                    ."border"
+                    ."w-full"
+                {
+                    thead ."bg-gray-200" {
+                        tr ."h-10" {
+                            th ."border" ."p-2" { "Category" }
+                            th ."border" ."p-2" { "Name" }
+                            th ."border" ."p-2" { "Weight" }
+                            th ."border" ."p-2" { "Verdict" }
+                        }
+                    }
+                    tbody {
+                        @for item in review.items.iter().filter(|item| item.is_reviewable()) {
+                            (ReviewItemRow::build(trip.id, item, editable))
+                        }
+                    }
+                }
+                @if editable && !missing_candidates.is_empty() {
+                    form
+                        action={ "/trips/" (trip.id) "/review/missing" }
+                        method="post"
+                        ."flex"
+                        ."flex-row"
+                        ."items-center"
+                        ."gap-x-3"
+                    {
+                        label for="missing-item-id" { "Wished we had:" }
+                        select
+                            id="missing-item-id"
+                            name="missing-item-id"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                            ."p-1"
+                        {
+                            @for item in missing_candidates {
+                                option value=(item.id) { (item.category_name) ": " (item.name) }
+                            }
+                        }
+                        button
+                            type="submit"
+                            ."p-1"
+                            ."px-3"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                        {
+                            "Add as missing"
+                        }
+                    }
+                }
+                div {
+                    h2 ."text-lg" ."mb-2" { "Lessons learned" }
+                    @if editable {
+                        form
+                            action={ "/trips/" (trip.id) "/review/lesson" }
+                            method="post"
+                            ."flex"
+                            ."flex-col"
+                            ."gap-2"
+                        {
+                            textarea
+                                name="new-lesson"
+                                autocomplete="off"
+                                ."border"
+                                ."w-full"
+                                ."h-32"
+                            { (review.lesson.as_deref().unwrap_or_default()) }
+                            button
+                                type="submit"
+                                ."bg-green-200"
+                                ."hover:bg-green-400"
+                                ."border"
+                                ."flex"
+                                ."flex-row"
+                                ."self-start"
+                                ."p-2"
+                                ."gap-2"
+                                ."items-center"
+                            {
+                                span ."mdi" ."mdi-content-save" ."text-xl" {}
+                                span { "Save" }
+                            }
+                        }
+                    } @else {
+                        @match review.lesson {
+                            Some(ref lesson) => p ."whitespace-pre-line" { (lesson) },
+                            None => p ."text-gray-400" { "[None]" },
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct ReviewItemRow;
+
+impl ReviewItemRow {
+    #[tracing::instrument]
+    pub fn build(trip_id: Uuid, item: &ReviewItem, editable: bool) -> Markup {
+        let url = format!("/trips/{trip_id}/review/items/{item_id}/verdict", item_id = item.id);
+        html!(
+            tr ."h-10" {
+                td ."border" ."p-2" { (item.category_name) }
+                td ."border" ."p-2" { (item.name) }
+                td ."border" ."p-2" { (item.weight) }
+                td ."border" ."p-0" {
+                    @if editable {
+                        form
+                            action=(url)
+                            method="post"
+                            hx-post={ (url) "/htmx" }
+                            hx-target="closest tr"
+                            hx-swap="outerHTML"
+                            ."flex"
+                            ."flex-row"
+                            ."h-full"
+                        {
+                            @for verdict in Verdict::ALL {
+                                @let active = item.verdict == Some(verdict);
+                                button
+                                    type="submit"
+                                    name="verdict"
+                                    // clicking the active verdict again clears it
+                                    value=(if active { "" } else { verdict.id() })
+                                    title=(verdict)
+                                    ."grow"
+                                    ."p-2"
+                                    ."flex"
+                                    ."flex-row"
+                                    ."items-center"
+                                    ."justify-center"
+                                    ."gap-1"
+                                    ."bg-green-200"[active]
+                                    ."hover:bg-green-100"[!active]
+                                {
+                                    span ."mdi" .(verdict.icon()) ."text-xl" {}
+                                    span ."text-sm" { (verdict) }
+                                }
+                            }
+                        }
+                    } @else {
+                        @if let Some(verdict) = item.verdict {
+                            div ."flex" ."flex-row" ."items-center" ."gap-1" ."p-2" {
+                                span ."mdi" .(verdict.icon()) ."text-xl" {}
+                                span { (verdict) }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct ReviewHintIcon;
+
+impl ReviewHintIcon {
+    #[tracing::instrument]
+    pub fn build(hint: &ReviewHint) -> Markup {
+        html!(
+            a
+                href={ "/trips/" (hint.trip_id) "/" }
+                title=(hint)
+                ."mr-2"
+                ."text-orange-500"
+            {
+                span ."mdi" .(hint.verdict.icon()) ."text-xl" {}
+            }
+        )
+    }
+}
@@ -11,16 +11,19 @@ use time::Date;
 use uuid::Uuid;
 
 use crate::{
-    AppState, Context, RunError, RequestError, TopLevelPage,
-    domains::{crud::Delete as _, route::Router as _, trips::todos},
+    AppState, Context, RunError, RequestError, TopLevelPage, auth,
+    domains::{crud::Delete as _, route::Router as _, trips::{containers, expenses, review, stages, todos, tracks}},
     htmx,
-    routing::{get_referer, uuid_or_empty},
+    routing::{get_referer, parse_or_empty, uuid_or_empty},
     view::Component,
 };
 
 use super::{model, view};
 use crate::models::User;
 
+/// Number of trips shown in the weight comparison chart
+const WEIGHT_HISTORY_LENGTH: i64 = 8;
+
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct NewTrip {
@@ -50,6 +53,13 @@ pub struct CommentUpdate {
     new_comment: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateQuery {
+    #[serde(default)]
+    confirm: bool,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TripTypeQuery {
@@ -70,6 +80,25 @@ pub struct TripTypeUpdate {
     new_value: String,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TripTypeMerge {
+    #[serde(rename = "merge-into-id")]
+    into_id: Uuid,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct NewTripTypeTodo {
+    #[serde(rename = "new-type-todo-description")]
+    description: String,
+    #[serde(
+        rename = "new-type-todo-days-before",
+        deserialize_with = "parse_or_empty"
+    )]
+    due_days_before: Option<i32>,
+}
+
 #[tracing::instrument]
 pub async fn create(
     Extension(current_user): Extension<User>,
@@ -102,26 +131,42 @@ pub async fn create(
 pub async fn trips(
     Extension(current_user): Extension<User>,
     State(state): State<AppState>,
+    Query(filter): Query<model::TripFilter>,
     headers: HeaderMap,
 ) -> Result<impl IntoResponse, RunError> {
     let ctx = Context::build(current_user);
+    let page = model::Trip::find_page(&ctx, &state.database_pool, &filter).await?;
+
+    // e.g. after the last trip of the last page was deleted. the page does not
+    // know the total then, so this goes back to the first page
+    if filter.page > page.pages() {
+        let first_page = filter.with_page(1);
+        return Ok(Redirect::to(&format!("/trips/{}", first_page.url())).into_response());
+    }
+    let trip_types = model::TripsType::all(&ctx, &state.database_pool).await?;
+
+    // filtering, sorting and paging only replace the list
+    if htmx::target(&headers) == Some("trip-list") {
+        return Ok(view::TripList::build(&page, &filter, &trip_types).into_response());
+    }
+
+    // all trips are offered as a template for new trips
     let trips = model::Trip::all(&ctx, &state.database_pool).await?;
+    let distance_per_year = tracks::Track::distance_per_year(&ctx, &state.database_pool).await?;
+    let manager = view::TripManager::build(&page, &filter, &trips, &trip_types, &distance_per_year);
 
     if htmx::is_htmx(&headers) {
         Ok(crate::view::root::Body::init(
             crate::view::Parent::Root,
             crate::view::root::BodyArgs {
-                body: &view::TripManager::build(&trips),
+                body: &manager,
                 active_page: Some(&TopLevelPage::Trips),
             },
         )
-        .build(&ctx))
+        .build(&ctx)
+        .into_response())
     } else {
-        Ok(crate::view::Root::build(
-            &ctx,
-            &view::TripManager::build(&trips),
-            Some(&TopLevelPage::Trips),
-        ))
+        Ok(crate::view::Root::build(&ctx, &manager, Some(&TopLevelPage::Trips)).into_response())
     }
 }
 
@@ -157,20 +202,21 @@ pub async fn trip(
         };
     }
 
-    let mut trip = model::Trip::find(&ctx, &state.database_pool, id)
+    let trip = model::Trip::load(&ctx, &state.database_pool, id, model::TripParts::ALL)
         .await?
         .ok_or(RunError::Request(RequestError::NotFound {
             message: format!("trip with id {id} not found"),
         }))?;
 
-    trip.load_trip_types(&ctx, &state.database_pool).await?;
-
-    trip.load_todos(&ctx, &state.database_pool).await?;
-
-    trip.sync_trip_items_with_inventory(&ctx, &state.database_pool)
-        .await?;
+    let review = if trip.state >= model::TripState::Review {
+        review::Review::find(&ctx, &state.database_pool, id).await?
+    } else {
+        None
+    };
 
-    trip.load_categories(&ctx, &state.database_pool).await?;
+    let weight_history =
+        model::TripWeight::find_history(&ctx, &state.database_pool, id, WEIGHT_HISTORY_LENGTH)
+            .await?;
 
     let active_category: Option<&model::TripCategory> = state
         .client_state
@@ -192,12 +238,68 @@ pub async fn trip(
             state.client_state.trip_edit_attribute.as_ref(),
             active_category,
             trip_query.edit_todo,
+            &weight_history,
+            review.as_ref(),
         ),
         Some(&TopLevelPage::Trips),
     )
     .into_response())
 }
 
+#[tracing::instrument]
+pub async fn delete(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    let deleted = model::Trip::delete(&ctx, &state.database_pool, trip_id).await?;
+
+    if deleted {
+        Ok(Redirect::to("/trips/"))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))
+    }
+}
+
+#[tracing::instrument]
+pub async fn archive(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<Redirect, RunError> {
+    set_archived(current_user, &state, trip_id, true).await
+}
+
+#[tracing::instrument]
+pub async fn unarchive(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<Redirect, RunError> {
+    set_archived(current_user, &state, trip_id, false).await
+}
+
+async fn set_archived(
+    current_user: User,
+    state: &AppState,
+    trip_id: Uuid,
+    archived: bool,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    let found = model::Trip::set_archived(&ctx, &state.database_pool, trip_id, archived).await?;
+
+    if found {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))
+    }
+}
+
 #[tracing::instrument]
 pub async fn remove_type(
     Extension(current_user): Extension<User>,
@@ -265,27 +367,91 @@ pub async fn total_weight_htmx(
     Ok(view::TripInfoTotalWeightRow::build(trip_id, total_weight))
 }
 
+#[tracing::instrument]
+pub async fn charts_htmx(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let mut trip = model::Trip::find(&ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or(RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }))?;
+
+    trip.load_categories(&ctx, &state.database_pool).await?;
+
+    let weight_history =
+        model::TripWeight::find_history(&ctx, &state.database_pool, trip_id, WEIGHT_HISTORY_LENGTH)
+            .await?;
+
+    Ok(view::TripWeightCharts::build(&trip, &weight_history))
+}
+
 #[tracing::instrument]
 pub async fn set_state(
     Extension(current_user): Extension<User>,
     State(state): State<AppState>,
     headers: HeaderMap,
     Path((trip_id, new_state)): Path<(Uuid, model::TripState)>,
+    Query(state_query): Query<StateQuery>,
 ) -> Result<impl IntoResponse, RunError> {
     let ctx = Context::build(current_user);
-    let exists = model::Trip::set_state(&ctx, &state.database_pool, trip_id, &new_state).await?;
-
-    if !exists {
-        return Err(RunError::Request(RequestError::NotFound {
-            message: format!("trip with id {trip_id} not found"),
-        }));
+    let transition = model::Trip::set_state(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        &new_state,
+        &state.transition_rules,
+        state_query.confirm,
+    )
+    .await?
+    .ok_or(RunError::Request(RequestError::NotFound {
+        message: format!("trip with id {trip_id} not found"),
+    }))?;
+
+    match transition {
+        model::StateTransition::Applied => {
+            if htmx::is_htmx(&headers) {
+                let mut headers = HeaderMap::new();
+                headers.insert::<HeaderName>(
+                    htmx::ResponseHeaders::Trigger.into(),
+                    htmx::Event::TripStateChanged.into(),
+                );
+                Ok((headers, view::TripInfoStateRow::build(&new_state, None)).into_response())
+            } else {
+                Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+            }
+        }
+        model::StateTransition::Blocked { from, violations } => {
+            if htmx::is_htmx(&headers) {
+                Ok(
+                    view::TripInfoStateRow::build(&from, Some((&new_state, &violations)))
+                        .into_response(),
+                )
+            } else {
+                Err(RunError::Request(RequestError::TransitionBlocked {
+                    message: violations
+                        .iter()
+                        .map(|violation| violation.guard.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                }))
+            }
+        }
     }
+}
 
-    if htmx::is_htmx(&headers) {
-        Ok(view::TripInfoStateRow::build(&new_state).into_response())
-    } else {
-        Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
-    }
+#[tracing::instrument]
+pub async fn state_history(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+) -> Result<impl IntoResponse, RunError> {
+    let ctx = Context::build(current_user);
+    let history = model::Trip::find_state_history(&ctx, &state.database_pool, trip_id).await?;
+    Ok(view::TripStateHistory::build(trip_id, &history))
 }
 
 #[tracing::instrument]
@@ -297,12 +463,13 @@ pub async fn trip_types(
     let ctx = Context::build(current_user);
     state.client_state.trip_type_edit = trip_type_query.edit;
 
-    let trip_types: Vec<model::TripsType> =
+    let mut trip_types: Vec<model::TripsType> =
         model::TripsType::all(&ctx, &state.database_pool).await?;
+    model::TripsType::load_todos(&ctx, &state.database_pool, &mut trip_types).await?;
 
     Ok(crate::view::Root::build(
         &ctx,
-        &view::types::TypeList::build(&state.client_state, trip_types),
+        &view::types::TypeList::build(&state.client_state, &trip_types),
         Some(&TopLevelPage::Trips),
     ))
 }
@@ -356,6 +523,98 @@ pub async fn edit_type_name(
     }
 }
 
+#[tracing::instrument]
+pub async fn delete_type(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_type_id): Path<Uuid>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    let deleted = model::TripsType::delete(&ctx, &state.database_pool, trip_type_id).await?;
+
+    if deleted {
+        Ok(Redirect::to("/trips/types/"))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("trip type with id {trip_type_id} not found"),
+        }))
+    }
+}
+
+#[tracing::instrument]
+pub async fn merge_type(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_type_id): Path<Uuid>,
+    Form(merge): Form<TripTypeMerge>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    let merged =
+        model::TripsType::merge(&ctx, &state.database_pool, trip_type_id, merge.into_id).await?;
+
+    if merged {
+        Ok(Redirect::to("/trips/types/"))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!(
+                "trip type with id {trip_type_id} or {} not found",
+                merge.into_id
+            ),
+        }))
+    }
+}
+
+#[tracing::instrument]
+pub async fn add_type_todo(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_type_id): Path<Uuid>,
+    Form(new_todo): Form<NewTripTypeTodo>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    if new_todo.description.is_empty() {
+        return Err(RunError::Request(RequestError::EmptyFormElement {
+            name: "description".to_string(),
+        }));
+    }
+
+    let exists = model::TripsType::add_todo(
+        &ctx,
+        &state.database_pool,
+        trip_type_id,
+        &new_todo.description,
+        new_todo.due_days_before,
+    )
+    .await?;
+
+    if exists {
+        Ok(Redirect::to("/trips/types/"))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("trip type with id {trip_type_id} not found"),
+        }))
+    }
+}
+
+#[tracing::instrument]
+pub async fn remove_type_todo(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_type_id, todo_id)): Path<(Uuid, Uuid)>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+    let exists =
+        model::TripsType::remove_todo(&ctx, &state.database_pool, trip_type_id, todo_id).await?;
+
+    if exists {
+        Ok(Redirect::to("/trips/types/"))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("default todo with id {todo_id} not found"),
+        }))
+    }
+}
+
 #[tracing::instrument]
 pub async fn select_category(
     Extension(current_user): Extension<User>,
@@ -363,14 +622,12 @@ pub async fn select_category(
     Path((trip_id, category_id)): Path<(Uuid, Uuid)>,
 ) -> Result<impl IntoResponse, RunError> {
     let ctx = Context::build(current_user);
-    let mut trip = model::Trip::find(&ctx, &state.database_pool, trip_id)
+    let trip = model::Trip::load(&ctx, &state.database_pool, trip_id, model::TripParts::ITEMS)
         .await?
         .ok_or(RunError::Request(RequestError::NotFound {
             message: format!("trip with id {trip_id} not found"),
         }))?;
 
-    trip.load_categories(&ctx, &state.database_pool).await?;
-
     let active_category = trip
         .categories()
         .iter()
@@ -398,16 +655,33 @@ pub fn router() -> Router<AppState> {
             .route("/", get(trips).post(create))
             .route("/types/", get(trip_types).post(create_type))
             .route("/types/{id}/edit/name/submit", post(edit_type_name))
+            .route("/types/{id}/delete", post(delete_type))
+            .route("/types/{id}/merge", post(merge_type))
+            .route("/types/{id}/todos/new", post(add_type_todo))
+            .route("/types/{id}/todos/{id}/delete", post(remove_type_todo))
             .route("/{id}/", get(trip))
             .route("/{id}/comment/submit", post(set_comment))
+            .route("/{id}/delete", post(delete))
+            .route("/{id}/archive", post(archive))
+            .route("/{id}/unarchive", post(unarchive))
+            .route("/{id}/export.md", get(super::export::markdown))
+            .route("/{id}/export.txt", get(super::export::text))
             .route("/{id}/categories/{id}/select", post(select_category))
+            .route("/{id}/state/history", get(state_history))
             .route("/{id}/state/{id}", post(set_state))
             .route("/{id}/total_weight", get(total_weight_htmx))
-            .route("/{id}/type/{id}/add", get(add_type))
-            .route("/{id}/type/{id}/remove", get(remove_type))
+            .route("/{id}/charts", get(charts_htmx))
+            .route("/{id}/type/{id}/add", auth::get_mutating(add_type))
+            .route("/{id}/type/{id}/remove", auth::get_mutating(remove_type))
             .nest("/{id}/packagelist/", super::packagelist::router())
+            .nest("/{id}/compare/", super::compare::router())
             .nest("/{id}/edit/", model::routes::router())
             .nest("/{id}/items/", super::items::router())
-            .nest("/{id}/todo/", todos::Todo::router()),
+            .nest("/{id}/todo/", todos::Todo::router())
+            .nest("/{id}/stages/", stages::Stage::router())
+            .nest("/{id}/expenses/", expenses::Expense::router())
+            .nest("/{id}/containers/", containers::router())
+            .nest("/{id}/tracks/", tracks::router())
+            .nest("/{id}/review/", review::router()),
     )
 }
@@ -0,0 +1,269 @@
+use maud::{Markup, html};
+use uuid::Uuid;
+
+use super::{Accommodation, Stage, StageItem};
+use crate::domains::trips::model::Trip;
+use crate::domains::view;
+
+#[derive(Debug)]
+pub struct List<'a> {
+    pub trip: &'a Trip,
+    pub stages: &'a Vec<Stage>,
+}
+
+#[derive(Debug)]
+pub struct BuildInput {}
+
+impl view::View for List<'_> {
+    type Input = BuildInput;
+
+    #[tracing::instrument]
+    fn build(&self, _input: Self::Input) -> Markup {
+        html!(
+            div #stagelist {
+                h1 ."text-xl" ."mb-5" { "Stages" }
+                table
+                    ."table"
+                    ."table-auto"
+                    ."border-collapse"
+                    ."border-spacing-0"
+                    ."border"
+                    ."w-full"
+                {
+                    thead ."bg-gray-200" {
+                        tr ."h-10" {
+                            th ."border" ."p-2" ."w-8" { "#" }
+                            th ."border" ."p-2" { "Date" }
+                            th ."border" ."p-2" { "Location" }
+                            th ."border" ."p-2" { "Accommodation" }
+                            th ."border" ."p-2" { "Temperature" }
+                            th ."border" ."p-2" { "Items" }
+                        }
+                    }
+                    tbody {
+                        @for (index, stage) in self.stages.iter().enumerate() {
+                            (stage.build(super::BuildInput {
+                                trip_id: self.trip.id,
+                                index,
+                                is_first: index == 0,
+                                is_last: index + 1 == self.stages.len(),
+                            }))
+                        }
+                        (NewStage::build(self.trip))
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct NewStage;
+
+impl NewStage {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip) -> Markup {
+        html!(
+            tr ."h-10" {
+                td ."border" ."p-0" {
+                    form
+                        name="new-stage"
+                        id="new-stage"
+                        action={
+                            "/trips/" (trip.id)
+                            "/stages/new"
+                        }
+                        target="_self"
+                        method="post"
+                        hx-post={
+                            "/trips/" (trip.id)
+                            "/stages/new"
+                        }
+                        hx-target="#stagelist"
+                        hx-swap="outerHTML"
+                    {}
+                    button
+                        type="submit"
+                        form="new-stage"
+                        ."w-full"
+                        ."h-full"
+                        ."p-2"
+                        ."bg-green-200"
+                        ."hover:bg-green-300"
+                    {
+                        span ."mdi" ."mdi-plus" ."text-xl" {}
+                    }
+                }
+                td ."border" ."p-1" {
+                    div ."flex" ."flex-row" ."gap-1" {
+                        input
+                            type="date"
+                            form="new-stage"
+                            name="new-stage-start-date"
+                            min=(trip.date.start)
+                            max=(trip.date.end)
+                            value=(trip.date.start)
+                            required
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                        {}
+                        input
+                            type="date"
+                            form="new-stage"
+                            name="new-stage-end-date"
+                            min=(trip.date.start)
+                            max=(trip.date.end)
+                            value=(trip.date.end)
+                            required
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                        {}
+                    }
+                }
+                td ."border" ."p-1" {
+                    input
+                        type="text"
+                        form="new-stage"
+                        name="new-stage-location"
+                        ."w-full"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                    {}
+                }
+                td ."border" ."p-1" {
+                    select
+                        form="new-stage"
+                        name="new-stage-accommodation"
+                        ."w-full"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                    {
+                        option value="" { "[None]" }
+                        @for accommodation in Accommodation::ALL {
+                            option value=(accommodation.id()) { (accommodation) }
+                        }
+                    }
+                }
+                td ."border" ."p-1" {
+                    div ."flex" ."flex-row" ."gap-1" {
+                        input
+                            type="number"
+                            form="new-stage"
+                            name="new-stage-temp-min"
+                            placeholder="min"
+                            ."w-1/2"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                        {}
+                        input
+                            type="number"
+                            form="new-stage"
+                            name="new-stage-temp-max"
+                            placeholder="max"
+                            ."w-1/2"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                        {}
+                    }
+                }
+                td ."border" ."p-2" {}
+            }
+        )
+    }
+}
+
+pub struct StagePage;
+
+impl StagePage {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip, stage: &Stage, items: &[StageItem]) -> Markup {
+        html!(
+            div ."p-8" ."flex" ."flex-col" ."gap-8" {
+                div ."flex" ."flex-row" ."items-center" ."gap-x-3" {
+                    a
+                        href={ "/trips/" (trip.id) "/" }
+                        ."text-2xl"
+                        ."mdi"
+                        ."mdi-arrow-left"
+                    {}
+                    h1 ."text-2xl" {
+                        (trip.name) ": Stage"
+                        @if let Some(ref location) = stage.location {
+                            " " (location)
+                        }
+                    }
+                    span ."text-gray-500" { (stage.date) }
+                }
+                p {
+                    "Select the items that are only needed for this stage. "
+                    "Items that are not assigned to any stage are needed for the whole trip."
+                }
+                @if items.is_empty() {
+                    p ."text-lg" ."text-center" ."py-5" ."text-gray-400" { "[No items picked]" }
+                } @else {
+                    table
+                        ."table"
+                        ."table-auto"
+                        ."border-collapse"
+                        ."border-spacing-0"
+                        ."border"
+                        ."w-full"
+                    {
+                        thead ."bg-gray-200" {
+                            tr ."h-10" {
+                                th ."border" ."p-2" ."w-8" {}
+                                th ."border" ."p-2" { "Category" }
+                                th ."border" ."p-2" { "Name" }
+                                th ."border" ."p-2" { "Weight" }
+                            }
+                        }
+                        tbody {
+                            @for item in items {
+                                (StageItemRow::build(trip.id, stage.id.0, item))
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct StageItemRow;
+
+impl StageItemRow {
+    #[tracing::instrument]
+    pub fn build(trip_id: Uuid, stage_id: Uuid, item: &StageItem) -> Markup {
+        let url = format!(
+            "/trips/{trip_id}/stages/{stage_id}/items/{item_id}/assigned",
+            item_id = item.id
+        );
+        html!(
+            tr ."h-10" {
+                td ."border" ."p-0" {
+                    form
+                        action={ (url) "/" (!item.assigned) }
+                        method="post"
+                        hx-post={ (url) "/htmx/" (!item.assigned) }
+                        hx-target="closest tr"
+                        hx-swap="outerHTML"
+                        ."flex"
+                        ."h-full"
+                        ."bg-green-200"[item.assigned]
+                        ."hover:bg-green-100"[!item.assigned]
+                    {
+                        button type="submit" ."w-full" ."h-full" ."p-2" {
+                            @if item.assigned {
+                                span ."mdi" ."mdi-check" ."text-xl" {}
+                            } @else {
+                                span ."mdi" ."mdi-checkbox-blank-outline" ."text-xl" {}
+                            }
+                        }
+                    }
+                }
+                td ."border" ."p-2" { (item.category_name) }
+                td ."border" ."p-2" { (item.name) }
+                td ."border" ."p-2" { (item.weight) }
+            }
+        )
+    }
+}
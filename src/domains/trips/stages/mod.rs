@@ -0,0 +1,1140 @@
+pub mod list;
+pub use list::List;
+
+use std::fmt;
+
+use axum::{
+    Extension,
+    body::Body,
+    extract::{Form, Path, State as StateExtractor},
+    http::HeaderMap,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use maud::{Markup, html};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState, Context, RequestError, TopLevelPage,
+    domains::{
+        crud::{self, Read, Update},
+        route::{self, Toggle},
+        view::{self, View},
+    },
+    error::RunError,
+    htmx,
+    models::User,
+    routing::{get_referer, parse_or_empty},
+};
+
+use async_trait::async_trait;
+
+use super::model::{Trip, TripDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "accommodation_type")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Accommodation {
+    Tent,
+    Hut,
+    Hotel,
+}
+
+impl Accommodation {
+    pub const ALL: [Self; 3] = [Self::Tent, Self::Hut, Self::Hotel];
+
+    #[must_use]
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Tent => "mdi-tent",
+            Self::Hut => "mdi-home-roof",
+            Self::Hotel => "mdi-bed",
+        }
+    }
+
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Tent => "tent",
+            Self::Hut => "hut",
+            Self::Hotel => "hotel",
+        }
+    }
+}
+
+impl fmt::Display for Accommodation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Tent => "Tent",
+                Self::Hut => "Hut",
+                Self::Hotel => "Hotel",
+            }
+        )
+    }
+}
+
+impl std::str::FromStr for Accommodation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|accommodation| accommodation.id() == s)
+            .ok_or_else(|| format!("unknown accommodation \"{s}\""))
+    }
+}
+
+#[derive(Debug)]
+pub struct Stage {
+    pub id: Id,
+    pub date: TripDate,
+    pub location: Option<String>,
+    pub accommodation: Option<Accommodation>,
+    pub temp_min: Option<i32>,
+    pub temp_max: Option<i32>,
+    /// Number of items that are only needed for this stage
+    pub item_count: i64,
+}
+
+struct StageRow {
+    id: Uuid,
+    date: TripDate,
+    location: Option<String>,
+    accommodation: Option<Accommodation>,
+    temp_min: Option<i32>,
+    temp_max: Option<i32>,
+    item_count: i64,
+}
+
+impl TryFrom<StageRow> for Stage {
+    type Error = RunError;
+
+    fn try_from(row: StageRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Id::new(row.id),
+            date: row.date,
+            location: row.location,
+            accommodation: row.accommodation,
+            temp_min: row.temp_min,
+            temp_max: row.temp_max,
+            item_count: row.item_count,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Container {
+    pub trip_id: Uuid,
+}
+
+impl crud::Container for Container {
+    type Id = Id;
+    type Reference = Reference;
+
+    fn with_id(&self, id: Self::Id) -> Self::Reference {
+        Reference {
+            id,
+            container: *self,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Reference {
+    pub id: Id,
+    pub container: Container,
+}
+
+impl From<(Uuid, Uuid)> for Reference {
+    fn from((trip_id, stage_id): (Uuid, Uuid)) -> Self {
+        Self {
+            id: Id::new(stage_id),
+            container: Container { trip_id },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Id(Uuid);
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Id {
+    #[must_use]
+    pub fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+#[async_trait]
+impl crud::Read for Stage {
+    type Reference = Reference;
+    type Container = Container;
+
+    #[tracing::instrument]
+    async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Container,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            StageRow,
+            Stage,
+            RunError,
+            r#"
+                SELECT
+                    stage.id AS id,
+                    stage.date AS date,
+                    stage.location AS location,
+                    stage.accommodation AS "accommodation: _",
+                    stage.temp_min AS temp_min,
+                    stage.temp_max AS temp_max,
+                    (
+                        SELECT COUNT(*)
+                        FROM trip_items_stages AS item_stage
+                        WHERE item_stage.stage_id = stage.id
+                    ) AS "item_count!"
+                FROM trip_stages AS stage
+                INNER JOIN trips
+                    ON trips.id = stage.trip_id
+                WHERE
+                    trips.id = $1
+                    AND trips.user_id = $2
+                ORDER BY stage.position
+            "#,
+            container.trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    async fn find(
+        ctx: &Context,
+        pool: &database::Pool,
+        reference: Reference,
+    ) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            StageRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    stage.id AS id,
+                    stage.date AS date,
+                    stage.location AS location,
+                    stage.accommodation AS "accommodation: _",
+                    stage.temp_min AS temp_min,
+                    stage.temp_max AS temp_max,
+                    (
+                        SELECT COUNT(*)
+                        FROM trip_items_stages AS item_stage
+                        WHERE item_stage.stage_id = stage.id
+                    ) AS "item_count!"
+                FROM trip_stages AS stage
+                INNER JOIN trips
+                    ON trips.id = stage.trip_id
+                WHERE
+                    trips.id = $1
+                    AND stage.id = $2
+                    AND trips.user_id = $3
+            "#,
+            reference.container.trip_id,
+            reference.id.0,
+            ctx.user.id,
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct StageNew {
+    pub date: TripDate,
+    pub location: Option<String>,
+    pub accommodation: Option<Accommodation>,
+    pub temp_min: Option<i32>,
+    pub temp_max: Option<i32>,
+}
+
+#[async_trait]
+impl crud::Create for Stage {
+    type Id = Id;
+    type Container = Container;
+    type Info = StageNew;
+
+    fn new_id() -> Self::Id {
+        Id::new(Uuid::new_v4())
+    }
+
+    #[tracing::instrument]
+    async fn create(
+        ctx: &Context,
+        pool: &database::Pool,
+        container: Self::Container,
+        info: Self::Info,
+    ) -> Result<Self::Id, RunError> {
+        let id = Self::new_id();
+        // new stages are always appended to the end
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            r"
+                INSERT INTO trip_stages
+                    (id, trip_id, position, date, location, accommodation, temp_min, temp_max)
+                SELECT
+                    $1,
+                    trips.id,
+                    COALESCE(
+                        (SELECT MAX(position) + 1 FROM trip_stages WHERE trip_id = trips.id),
+                        0
+                    ),
+                    $3,
+                    $4,
+                    $5,
+                    $6,
+                    $7
+                FROM trips
+                WHERE trips.id = $2 AND trips.user_id = $8
+            ",
+            id.0,
+            container.trip_id,
+            <TripDate as TryInto<sqlx::postgres::types::PgRange<time::Date>>>::try_into(
+                info.date
+            )?,
+            info.location,
+            info.accommodation as _,
+            info.temp_min,
+            info.temp_max,
+            ctx.user.id,
+        )
+        .await?;
+
+        Ok(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+#[derive(Debug)]
+pub enum UpdateElement {
+    /// Swap the position with the previous or next stage
+    Move(Direction),
+}
+
+#[async_trait]
+impl crud::Update for Stage {
+    type Reference = Reference;
+    type UpdateElement = UpdateElement;
+
+    #[tracing::instrument]
+    async fn update(
+        ctx: &Context,
+        pool: &database::Pool,
+        reference: Self::Reference,
+        update_element: Self::UpdateElement,
+    ) -> Result<Option<Self>, RunError> {
+        match update_element {
+            UpdateElement::Move(Direction::Up) => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    pool,
+                    RunError,
+                    r"
+                        WITH current_stage AS (
+                            SELECT stage.id, stage.position
+                            FROM trip_stages AS stage
+                            INNER JOIN trips
+                                ON trips.id = stage.trip_id
+                            WHERE stage.id = $1 AND trips.id = $2 AND trips.user_id = $3
+                        ), other_stage AS (
+                            SELECT stage.id, stage.position
+                            FROM trip_stages AS stage, current_stage
+                            WHERE stage.trip_id = $2 AND stage.position < current_stage.position
+                            ORDER BY stage.position DESC
+                            LIMIT 1
+                        )
+                        UPDATE trip_stages AS stage
+                        SET position = CASE
+                            WHEN stage.id = current_stage.id THEN other_stage.position
+                            ELSE current_stage.position
+                        END
+                        FROM current_stage, other_stage
+                        WHERE stage.id IN (current_stage.id, other_stage.id)
+                    ",
+                    reference.id.0,
+                    reference.container.trip_id,
+                    ctx.user.id,
+                )
+                .await?;
+            }
+            UpdateElement::Move(Direction::Down) => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Trips,
+                    },
+                    pool,
+                    RunError,
+                    r"
+                        WITH current_stage AS (
+                            SELECT stage.id, stage.position
+                            FROM trip_stages AS stage
+                            INNER JOIN trips
+                                ON trips.id = stage.trip_id
+                            WHERE stage.id = $1 AND trips.id = $2 AND trips.user_id = $3
+                        ), other_stage AS (
+                            SELECT stage.id, stage.position
+                            FROM trip_stages AS stage, current_stage
+                            WHERE stage.trip_id = $2 AND stage.position > current_stage.position
+                            ORDER BY stage.position ASC
+                            LIMIT 1
+                        )
+                        UPDATE trip_stages AS stage
+                        SET position = CASE
+                            WHEN stage.id = current_stage.id THEN other_stage.position
+                            ELSE current_stage.position
+                        END
+                        FROM current_stage, other_stage
+                        WHERE stage.id IN (current_stage.id, other_stage.id)
+                    ",
+                    reference.id.0,
+                    reference.container.trip_id,
+                    ctx.user.id,
+                )
+                .await?;
+            }
+        }
+
+        Self::find(ctx, pool, reference).await
+    }
+}
+
+#[async_trait]
+impl crud::Delete for Stage {
+    type Id = Id;
+    type Container = Container;
+    type Reference = Reference;
+
+    #[tracing::instrument]
+    async fn delete<'c, T>(ctx: &Context, db: T, reference: &Reference) -> Result<bool, RunError>
+    where
+        T: sqlx::Acquire<'c, Database = sqlx::Postgres> + Send + std::fmt::Debug,
+    {
+        let mut transaction = db.begin().await?;
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            r"
+                DELETE FROM trip_items_stages
+                WHERE
+                    stage_id = $1
+                    AND trip_id = $2
+                    AND EXISTS (SELECT 1 FROM trips WHERE id = $2 AND user_id = $3)
+            ",
+            reference.id.0,
+            reference.container.trip_id,
+            ctx.user.id,
+        )
+        .await?;
+
+        let results = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            &mut *transaction,
+            RunError,
+            r"
+                DELETE FROM trip_stages
+                WHERE
+                    id = $1
+                    AND trip_id = $2
+                    AND EXISTS (SELECT 1 FROM trips WHERE id = $2 AND user_id = $3)
+            ",
+            reference.id.0,
+            reference.container.trip_id,
+            ctx.user.id,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(results.rows_affected() != 0)
+    }
+}
+
+/// A picked item of the trip, together with the information whether it is
+/// needed for a specific stage
+#[derive(Debug)]
+pub struct StageItem {
+    pub id: Uuid,
+    pub name: String,
+    pub weight: i32,
+    pub category_name: String,
+    pub assigned: bool,
+}
+
+struct StageItemRow {
+    id: Uuid,
+    name: String,
+    weight: i32,
+    category_name: String,
+    assigned: bool,
+}
+
+impl TryFrom<StageItemRow> for StageItem {
+    type Error = RunError;
+
+    fn try_from(row: StageItemRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            weight: row.weight,
+            category_name: row.category_name,
+            assigned: row.assigned,
+        })
+    }
+}
+
+impl Stage {
+    #[tracing::instrument]
+    pub async fn items(
+        ctx: &Context,
+        pool: &database::Pool,
+        reference: Reference,
+    ) -> Result<Vec<StageItem>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            StageItemRow,
+            StageItem,
+            RunError,
+            r#"
+                SELECT
+                    i_item.id AS id,
+                    i_item.name AS name,
+                    i_item.weight AS weight,
+                    category.name AS category_name,
+                    EXISTS(
+                        SELECT 1
+                        FROM trip_items_stages AS item_stage
+                        WHERE
+                            item_stage.item_id = i_item.id
+                            AND item_stage.stage_id = $3
+                    ) AS "assigned!"
+                FROM trip_items AS t_item
+                INNER JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                INNER JOIN inventory_items_categories AS category
+                    ON category.id = i_item.category_id
+                WHERE
+                    t_item.trip_id = $1
+                    AND t_item.user_id = $2
+                    AND t_item.pick = true
+                ORDER BY category.name, i_item.name
+            "#,
+            reference.container.trip_id,
+            ctx.user.id,
+            reference.id.0,
+        )
+        .await
+    }
+
+    #[tracing::instrument]
+    pub async fn find_item(
+        ctx: &Context,
+        pool: &database::Pool,
+        reference: Reference,
+        item_id: Uuid,
+    ) -> Result<Option<StageItem>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            StageItemRow,
+            StageItem,
+            RunError,
+            r#"
+                SELECT
+                    i_item.id AS id,
+                    i_item.name AS name,
+                    i_item.weight AS weight,
+                    category.name AS category_name,
+                    EXISTS(
+                        SELECT 1
+                        FROM trip_items_stages AS item_stage
+                        WHERE
+                            item_stage.item_id = i_item.id
+                            AND item_stage.stage_id = $3
+                    ) AS "assigned!"
+                FROM trip_items AS t_item
+                INNER JOIN inventory_items AS i_item
+                    ON i_item.id = t_item.item_id
+                INNER JOIN inventory_items_categories AS category
+                    ON category.id = i_item.category_id
+                WHERE
+                    t_item.trip_id = $1
+                    AND t_item.user_id = $2
+                    AND i_item.id = $4
+            "#,
+            reference.container.trip_id,
+            ctx.user.id,
+            reference.id.0,
+            item_id,
+        )
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct ItemReference {
+    pub stage: Reference,
+    pub item_id: Uuid,
+}
+
+impl From<(Uuid, Uuid, Uuid)> for ItemReference {
+    fn from((trip_id, stage_id, item_id): (Uuid, Uuid, Uuid)) -> Self {
+        Self {
+            stage: (trip_id, stage_id).into(),
+            item_id,
+        }
+    }
+}
+
+/// Whether an item is needed for a specific stage
+#[derive(Debug)]
+pub struct ItemAssignment;
+
+#[async_trait]
+impl crud::Toggle for ItemAssignment {
+    type Reference = ItemReference;
+
+    #[tracing::instrument]
+    async fn set(
+        ctx: &Context,
+        pool: &database::Pool,
+        reference: Self::Reference,
+        value: bool,
+    ) -> Result<(), crate::RunError> {
+        if value {
+            database::execute!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Insert,
+                    component: crate::Component::Trips,
+                },
+                pool,
+                RunError,
+                r"
+                    INSERT INTO trip_items_stages
+                        (item_id, trip_id, stage_id)
+                    SELECT t_item.item_id, t_item.trip_id, stage.id
+                    FROM trip_items AS t_item
+                    INNER JOIN trip_stages AS stage
+                        ON stage.trip_id = t_item.trip_id
+                    WHERE
+                        t_item.item_id = $1
+                        AND t_item.trip_id = $2
+                        AND stage.id = $3
+                        AND t_item.user_id = $4
+                    ON CONFLICT DO NOTHING
+                ",
+                reference.item_id,
+                reference.stage.container.trip_id,
+                reference.stage.id.0,
+                ctx.user.id,
+            )
+            .await?;
+        } else {
+            database::execute!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Delete,
+                    component: crate::Component::Trips,
+                },
+                pool,
+                RunError,
+                r"
+                    DELETE FROM trip_items_stages
+                    WHERE
+                        item_id = $1
+                        AND trip_id = $2
+                        AND stage_id = $3
+                        AND EXISTS (SELECT 1 FROM trips WHERE id = $2 AND user_id = $4)
+                ",
+                reference.item_id,
+                reference.stage.container.trip_id,
+                reference.stage.id.0,
+                ctx.user.id,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct BuildInput {
+    pub trip_id: Uuid,
+    pub index: usize,
+    pub is_first: bool,
+    pub is_last: bool,
+}
+
+impl view::View for Stage {
+    type Input = BuildInput;
+
+    #[tracing::instrument]
+    fn build(&self, input: Self::Input) -> Markup {
+        let url = format!("/trips/{trip_id}/stages/{id}", trip_id = input.trip_id, id = self.id);
+        html!(
+            tr ."h-10" ."even:bg-gray-100" {
+                td ."border" ."p-2" ."text-center" { (input.index + 1) }
+                td ."border" ."p-2" { (self.date) }
+                td ."border" ."p-2" { (self.location.as_deref().unwrap_or_default()) }
+                td ."border" ."p-2" {
+                    @if let Some(accommodation) = self.accommodation {
+                        span .flex .flex-row .items-center ."gap-2" {
+                            span ."mdi" .(accommodation.icon()) ."text-xl" {}
+                            span { (accommodation) }
+                        }
+                    }
+                }
+                td ."border" ."p-2" {
+                    @match (self.temp_min, self.temp_max) {
+                        (Some(min), Some(max)) => { (min) " – " (max) " °C" },
+                        (Some(min), None) => { "≥ " (min) " °C" },
+                        (None, Some(max)) => { "≤ " (max) " °C" },
+                        (None, None) => {},
+                    }
+                }
+                td ."border" ."p-0" {
+                    a
+                        href={ (url) "/" }
+                        ."inline-block"
+                        ."p-2"
+                        ."w-full"
+                        ."hover:bg-blue-100"
+                    {
+                        span ."mdi" ."mdi-format-list-checks" ."mr-2" {}
+                        (self.item_count)
+                    }
+                }
+                td ."border-none" ."p-0" ."w-8" ."h-full" {
+                    @if !input.is_first {
+                        form
+                            action={ (url) "/up" }
+                            method="post"
+                            hx-post={ (url) "/up" }
+                            hx-target="#stagelist"
+                            hx-swap="outerHTML"
+                            ."flex"
+                            ."h-full"
+                            ."bg-blue-100"
+                            ."hover:bg-blue-200"
+                        {
+                            button type="submit" ."w-full" ."h-full" {
+                                span ."m-auto" ."mdi" ."mdi-arrow-up" ."text-xl" {}
+                            }
+                        }
+                    }
+                }
+                td ."border-none" ."p-0" ."w-8" ."h-full" {
+                    @if !input.is_last {
+                        form
+                            action={ (url) "/down" }
+                            method="post"
+                            hx-post={ (url) "/down" }
+                            hx-target="#stagelist"
+                            hx-swap="outerHTML"
+                            ."flex"
+                            ."h-full"
+                            ."bg-blue-100"
+                            ."hover:bg-blue-200"
+                        {
+                            button type="submit" ."w-full" ."h-full" {
+                                span ."m-auto" ."mdi" ."mdi-arrow-down" ."text-xl" {}
+                            }
+                        }
+                    }
+                }
+                td ."border-none" ."p-0" ."w-8" ."h-full" {
+                    form
+                        action={ (url) "/delete" }
+                        method="post"
+                        hx-post={ (url) "/delete" }
+                        hx-target="#stagelist"
+                        hx-swap="outerHTML"
+                        ."flex"
+                        ."h-full"
+                        ."bg-red-100"
+                        ."hover:bg-red-200"
+                    {
+                        button type="submit" ."w-full" ."h-full" {
+                            span ."m-auto" ."mdi" ."mdi-delete-outline" ."text-xl" {}
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TripStageNew {
+    #[serde(rename = "new-stage-start-date")]
+    date_start: time::Date,
+    #[serde(rename = "new-stage-end-date")]
+    date_end: time::Date,
+    #[serde(rename = "new-stage-location")]
+    location: String,
+    #[serde(rename = "new-stage-accommodation", deserialize_with = "parse_or_empty")]
+    accommodation: Option<Accommodation>,
+    #[serde(rename = "new-stage-temp-min", deserialize_with = "parse_or_empty")]
+    temp_min: Option<i32>,
+    #[serde(rename = "new-stage-temp-max", deserialize_with = "parse_or_empty")]
+    temp_max: Option<i32>,
+}
+
+#[tracing::instrument]
+async fn stage_list(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+) -> Result<Response<Body>, crate::RunError> {
+    let trip = Trip::find(ctx, &state.database_pool, trip_id).await?;
+    match trip {
+        None => Err(crate::RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        })),
+        Some(mut trip) => {
+            trip.load_stages(ctx, &state.database_pool).await?;
+            Ok(list::List {
+                trip: &trip,
+                stages: trip.stages(),
+            }
+            .build(list::BuildInput {})
+            .into_response())
+        }
+    }
+}
+
+#[async_trait]
+impl route::Create for Stage {
+    type Form = TripStageNew;
+    type UrlParams = (Uuid,);
+
+    #[tracing::instrument]
+    async fn create(
+        Extension(current_user): Extension<User>,
+        StateExtractor(state): StateExtractor<AppState>,
+        headers: HeaderMap,
+        Path((trip_id,)): Path<Self::UrlParams>,
+        Form(form): Form<Self::Form>,
+    ) -> Result<Response<Body>, crate::RunError> {
+        let ctx = Context::build(current_user);
+
+        let trip = Trip::find(&ctx, &state.database_pool, trip_id)
+            .await?
+            .ok_or_else(|| {
+                crate::RunError::Request(RequestError::NotFound {
+                    message: format!("trip with id {trip_id} not found"),
+                })
+            })?;
+
+        if form.date_end < form.date_start {
+            return Err(crate::RunError::Request(
+                RequestError::InvalidFormElement {
+                    name: "end date".to_string(),
+                    message: "stage cannot end before it starts".to_string(),
+                },
+            ));
+        }
+
+        if form.date_start < trip.date.start || form.date_end > trip.date.end {
+            return Err(crate::RunError::Request(
+                RequestError::InvalidFormElement {
+                    name: "date".to_string(),
+                    message: format!("stage has to be within the trip date ({})", trip.date),
+                },
+            ));
+        }
+
+        if let (Some(min), Some(max)) = (form.temp_min, form.temp_max)
+            && min > max
+        {
+            return Err(crate::RunError::Request(
+                RequestError::InvalidFormElement {
+                    name: "temperature".to_string(),
+                    message: "minimum temperature is above the maximum".to_string(),
+                },
+            ));
+        }
+
+        let _stage_id = <Self as crud::Create>::create(
+            &ctx,
+            &state.database_pool,
+            Container { trip_id },
+            StageNew {
+                date: TripDate {
+                    start: form.date_start,
+                    end: form.date_end,
+                },
+                location: Some(form.location).filter(|location| !location.is_empty()),
+                accommodation: form.accommodation,
+                temp_min: form.temp_min,
+                temp_max: form.temp_max,
+            },
+        )
+        .await?;
+
+        if htmx::is_htmx(&headers) {
+            stage_list(&ctx, &state, trip_id).await
+        } else {
+            Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+        }
+    }
+}
+
+#[async_trait]
+impl route::Delete for Stage {
+    type UrlParams = (Uuid, Uuid);
+
+    #[tracing::instrument]
+    async fn delete(
+        Extension(current_user): Extension<User>,
+        StateExtractor(state): StateExtractor<AppState>,
+        headers: HeaderMap,
+        Path((trip_id, stage_id)): Path<Self::UrlParams>,
+    ) -> Result<Response<Body>, crate::RunError> {
+        let ctx = Context::build(current_user);
+        let deleted = <Self as crud::Delete>::delete(
+            &ctx,
+            &state.database_pool,
+            &(trip_id, stage_id).into(),
+        )
+        .await?;
+
+        if !deleted {
+            return Err(crate::RunError::Request(RequestError::NotFound {
+                message: format!("stage with id {stage_id} not found"),
+            }));
+        }
+
+        if htmx::is_htmx(&headers) {
+            stage_list(&ctx, &state, trip_id).await
+        } else {
+            Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+        }
+    }
+}
+
+impl route::Router for Stage {
+    fn router() -> axum::Router<AppState> {
+        axum::Router::new()
+            .route("/new", post(<Self as route::Create>::create))
+            .route("/{id}/", get(stage))
+            .route("/{id}/up", post(move_up))
+            .route("/{id}/down", post(move_down))
+            .route("/{id}/delete", post(<Self as route::Delete>::delete))
+            .merge(ItemAssignment::router())
+    }
+}
+
+#[tracing::instrument]
+async fn move_stage(
+    current_user: User,
+    state: AppState,
+    headers: HeaderMap,
+    (trip_id, stage_id): (Uuid, Uuid),
+    direction: Direction,
+) -> Result<Response<Body>, crate::RunError> {
+    let ctx = Context::build(current_user);
+    Stage::update(
+        &ctx,
+        &state.database_pool,
+        (trip_id, stage_id).into(),
+        UpdateElement::Move(direction),
+    )
+    .await?
+    .ok_or_else(|| {
+        crate::RunError::Request(RequestError::NotFound {
+            message: format!("stage with id {stage_id} not found"),
+        })
+    })?;
+
+    if htmx::is_htmx(&headers) {
+        stage_list(&ctx, &state, trip_id).await
+    } else {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+    }
+}
+
+#[tracing::instrument]
+pub async fn move_up(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path(params): Path<(Uuid, Uuid)>,
+) -> Result<Response<Body>, crate::RunError> {
+    move_stage(current_user, state, headers, params, Direction::Up).await
+}
+
+#[tracing::instrument]
+pub async fn move_down(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path(params): Path<(Uuid, Uuid)>,
+) -> Result<Response<Body>, crate::RunError> {
+    move_stage(current_user, state, headers, params, Direction::Down).await
+}
+
+#[tracing::instrument]
+pub async fn stage(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    Path((trip_id, stage_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, crate::RunError> {
+    let ctx = Context::build(current_user);
+    let reference: Reference = (trip_id, stage_id).into();
+
+    let trip = Trip::find(&ctx, &state.database_pool, trip_id)
+        .await?
+        .ok_or_else(|| {
+            crate::RunError::Request(RequestError::NotFound {
+                message: format!("trip with id {trip_id} not found"),
+            })
+        })?;
+
+    let current_stage = Stage::find(&ctx, &state.database_pool, reference)
+        .await?
+        .ok_or_else(|| {
+            crate::RunError::Request(RequestError::NotFound {
+                message: format!("stage with id {stage_id} not found"),
+            })
+        })?;
+
+    let items = Stage::items(&ctx, &state.database_pool, reference).await?;
+
+    Ok(crate::view::Root::build(
+        &ctx,
+        &list::StagePage::build(&trip, &current_stage, &items),
+        Some(&TopLevelPage::Trips),
+    ))
+}
+
+#[async_trait]
+impl route::ToggleFallback for ItemAssignment {
+    type UrlParams = (Uuid, Uuid, Uuid);
+
+    const URL_TRUE: &'static str = "/{id}/items/{id}/assigned/true";
+    const URL_FALSE: &'static str = "/{id}/items/{id}/assigned/false";
+
+    async fn set(
+        current_user: User,
+        state: AppState,
+        headers: HeaderMap,
+        params: Self::UrlParams,
+        value: bool,
+    ) -> Result<Response<Body>, crate::RunError> {
+        let ctx = Context::build(current_user);
+        <Self as crud::Toggle>::set(&ctx, &state.database_pool, params.into(), value).await?;
+
+        Ok(Redirect::to(get_referer(&headers)?).into_response())
+    }
+
+    fn router() -> axum::Router<AppState> {
+        axum::Router::new()
+            .route(Self::URL_TRUE, post(Self::set_true))
+            .route(Self::URL_FALSE, post(Self::set_false))
+    }
+}
+
+#[async_trait]
+impl route::ToggleHtmx for ItemAssignment {
+    type UrlParams = (Uuid, Uuid, Uuid);
+
+    const URL_TRUE: &'static str = "/{id}/items/{id}/assigned/htmx/true";
+    const URL_FALSE: &'static str = "/{id}/items/{id}/assigned/htmx/false";
+
+    async fn set(
+        current_user: User,
+        state: AppState,
+        params: Self::UrlParams,
+        value: bool,
+    ) -> Result<(crate::Context, AppState, Self::UrlParams), crate::RunError> {
+        let ctx = Context::build(current_user);
+        <Self as crud::Toggle>::set(&ctx, &state.database_pool, params.into(), value).await?;
+
+        Ok((ctx, state, params))
+    }
+
+    async fn response(
+        ctx: &Context,
+        state: AppState,
+        (trip_id, stage_id, item_id): Self::UrlParams,
+    ) -> Result<Response<Body>, crate::RunError> {
+        let item = Stage::find_item(
+            ctx,
+            &state.database_pool,
+            (trip_id, stage_id).into(),
+            item_id,
+        )
+        .await?
+        .ok_or_else(|| {
+            crate::RunError::Request(RequestError::NotFound {
+                message: format!("item with id {item_id} not found for trip {trip_id}"),
+            })
+        })?;
+
+        Ok(list::StageItemRow::build(trip_id, stage_id, &item).into_response())
+    }
+
+    fn router() -> axum::Router<AppState> {
+        axum::Router::new()
+            .route(Self::URL_TRUE, post(Self::on))
+            .route(Self::URL_FALSE, post(Self::off))
+    }
+}
+
+#[async_trait]
+impl route::Toggle for ItemAssignment {}
@@ -1,7 +1,7 @@
 use maud::{html, Markup};
 use uuid::Uuid;
 
-use super::Todo;
+use super::{Order, Todo};
 use crate::domains::trips::model::Trip;
 use crate::domains::view;
 
@@ -9,6 +9,8 @@ use crate::domains::view;
 pub struct List<'a> {
     pub trip: &'a Trip,
     pub todos: &'a Vec<Todo>,
+    /// Todos can be assigned to the participants of the trip
+    pub participants: &'a [String],
 }
 
 #[derive(Debug)]
@@ -23,7 +25,10 @@ impl view::View for List<'_> {
     fn build(&self, input: Self::Input) -> Markup {
         html!(
             div #todolist {
-                h1 ."text-xl" ."mb-5" { "Todos" }
+                div ."flex" ."flex-row" ."items-center" ."justify-between" ."mb-5" {
+                    h1 ."text-xl" { "Todos" }
+                    (OrderSelection::build(self.trip))
+                }
                 ul
                     ."flex"
                     ."flex-col"
@@ -31,11 +36,15 @@ impl view::View for List<'_> {
                     @for todo in self.todos {
                         @let state = input.edit_todo
                             .map_or(super::UiState::Default, |id| if todo.id == super::Id::new(id) {
-                                super::UiState::Edit
+                                super::UiState::Edit { participants: self.participants.to_vec() }
                             } else {
                                 super::UiState::Default
                             });
-                        (todo.build(super::BuildInput{trip_id:self.trip.id, state}))
+                        (todo.build(super::BuildInput{
+                            trip_id: self.trip.id,
+                            state,
+                            movable: self.trip.todo_order == Order::Manual,
+                        }))
                     }
                     (NewTodo::build(&self.trip.id))
                 }
@@ -107,3 +116,37 @@ impl NewTodo {
         )
     }
 }
+
+pub struct OrderSelection;
+
+impl OrderSelection {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip) -> Markup {
+        html!(
+            div ."flex" ."flex-row" ."items-center" ."gap-2" {
+                span ."text-gray-500" { "Sort by" }
+                @for (order, id, name) in [(Order::Manual, "manual", "Manual"), (Order::Due, "due", "Due date")] {
+                    @let active = trip.todo_order == order;
+                    form
+                        action={ "/trips/" (trip.id) "/todo/order/" (id) }
+                        method="post"
+                        hx-post={ "/trips/" (trip.id) "/todo/order/" (id) }
+                        hx-target="#todolist"
+                        hx-swap="outerHTML"
+                    {
+                        button
+                            type="submit"
+                            disabled[active]
+                            ."px-2"
+                            ."border"
+                            ."bg-blue-200"[active]
+                            ."hover:bg-blue-100"[!active]
+                        {
+                            (name)
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
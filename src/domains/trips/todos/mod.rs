@@ -1,6 +1,8 @@
 pub mod list;
 pub use list::List;
 
+use std::fmt;
+
 use axum::{
     Extension,
     body::Body,
@@ -24,12 +26,12 @@ use crate::{
     error::RunError,
     htmx,
     models::User,
-    routing::get_referer,
+    routing::{date_or_empty, get_referer, parse_or_empty},
 };
 
 use async_trait::async_trait;
 
-use super::model::Trip;
+use super::{expenses::Participant, model::Trip, stages::Direction};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum State {
@@ -52,17 +54,106 @@ impl From<State> for bool {
     }
 }
 
+/// How the todos of a trip are sorted. Todos without a due date always come
+/// last when sorting by due date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "todo_order")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Manual,
+    Due,
+}
+
+impl Order {
+    #[tracing::instrument]
+    pub async fn set(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        order: Self,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Update,
+                component: crate::Component::Todo,
+            },
+            pool,
+            RunError,
+            r"
+                UPDATE trips
+                SET todo_order = $1
+                WHERE id = $2 AND user_id = $3
+            ",
+            order as _,
+            trip_id,
+            ctx.user.id,
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due {
+    Date(time::Date),
+    /// Days before the start of the trip, negative values are after the start
+    BeforeStart(i32),
+}
+
+impl Due {
+    fn from_columns(date: Option<time::Date>, days_before: Option<i32>) -> Option<Self> {
+        match (date, days_before) {
+            (Some(date), _) => Some(Self::Date(date)),
+            (None, Some(days)) => Some(Self::BeforeStart(days)),
+            (None, None) => None,
+        }
+    }
+
+    fn into_columns(due: Option<Self>) -> (Option<time::Date>, Option<i32>) {
+        match due {
+            Some(Self::Date(date)) => (Some(date), None),
+            Some(Self::BeforeStart(days)) => (None, Some(days)),
+            None => (None, None),
+        }
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Date(date) => write!(f, "{date}"),
+            Self::BeforeStart(0) => write!(f, "on the first day"),
+            Self::BeforeStart(1) => write!(f, "1 day before"),
+            Self::BeforeStart(-1) => write!(f, "1 day after start"),
+            Self::BeforeStart(days) if days < 0 => write!(f, "{} days after start", -days),
+            Self::BeforeStart(days) => write!(f, "{days} days before"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Todo {
     pub id: Id,
     pub description: String,
     pub state: State,
+    pub due: Option<Due>,
+    /// The due date, with relative due dates resolved against the trip start
+    pub due_at: Option<time::Date>,
+    pub overdue: bool,
+    pub assignee: Option<String>,
 }
 
 struct TodoRow {
     id: Uuid,
     description: String,
     done: bool,
+    due_date: Option<time::Date>,
+    due_days_before: Option<i32>,
+    due_at: Option<time::Date>,
+    overdue: bool,
+    assignee: Option<String>,
 }
 
 impl TryFrom<TodoRow> for Todo {
@@ -73,6 +164,10 @@ impl TryFrom<TodoRow> for Todo {
             id: Id::new(row.id),
             description: row.description,
             state: row.done.into(),
+            due: Due::from_columns(row.due_date, row.due_days_before),
+            due_at: row.due_at,
+            overdue: row.overdue,
+            assignee: row.assignee,
         })
     }
 }
@@ -151,18 +246,31 @@ impl crud::Read for Todo {
             TodoRow,
             Todo,
             RunError,
-            r"
+            r#"
                 SELECT
                     todo.id AS id,
                     todo.description AS description,
-                    todo.done AS done
+                    todo.done AS done,
+                    todo.due_date AS due_date,
+                    todo.due_days_before AS due_days_before,
+                    COALESCE(todo.due_date, lower(trips.date) - todo.due_days_before) AS due_at,
+                    COALESCE(
+                        COALESCE(todo.due_date, lower(trips.date) - todo.due_days_before) < CURRENT_DATE,
+                        false
+                    ) AND NOT todo.done AS "overdue!",
+                    todo.assignee AS assignee
                 FROM trip_todos AS todo
                 INNER JOIN trips
                     ON trips.id = todo.trip_id
                 WHERE
                     trips.id = $1
                     AND trips.user_id = $2
-            ",
+                ORDER BY
+                    CASE WHEN trips.todo_order = 'due'
+                        THEN COALESCE(todo.due_date, lower(trips.date) - todo.due_days_before)
+                    END ASC NULLS LAST,
+                    todo.position ASC
+            "#,
             container.trip_id,
             ctx.user.id
         )
@@ -186,11 +294,19 @@ impl crud::Read for Todo {
             TodoRow,
             Self,
             RunError,
-            r"
+            r#"
                 SELECT
                     todo.id AS id,
                     todo.description AS description,
-                    todo.done AS done
+                    todo.done AS done,
+                    todo.due_date AS due_date,
+                    todo.due_days_before AS due_days_before,
+                    COALESCE(todo.due_date, lower(trips.date) - todo.due_days_before) AS due_at,
+                    COALESCE(
+                        COALESCE(todo.due_date, lower(trips.date) - todo.due_days_before) < CURRENT_DATE,
+                        false
+                    ) AND NOT todo.done AS "overdue!",
+                    todo.assignee AS assignee
                 FROM trip_todos AS todo
                 INNER JOIN trips
                     ON trips.id = todo.trip_id
@@ -198,7 +314,7 @@ impl crud::Read for Todo {
                     trips.id = $1
                     AND todo.id = $2
                     AND trips.user_id = $3
-            ",
+            "#,
             reference.container.trip_id,
             reference.id.0,
             ctx.user.id,
@@ -209,6 +325,8 @@ impl crud::Read for Todo {
 
 pub struct TodoNew {
     pub description: String,
+    pub due: Option<Due>,
+    pub assignee: Option<String>,
 }
 
 #[async_trait]
@@ -229,6 +347,7 @@ impl crud::Create for Todo {
     ) -> Result<Self::Id, RunError> {
         let id = Self::new_id();
         tracing::info!("adding new todo with id {id}");
+        let (due_date, due_days_before) = Due::into_columns(info.due);
         database::execute!(
             &database::QueryClassification {
                 query_type: database::QueryType::Insert,
@@ -238,16 +357,29 @@ impl crud::Create for Todo {
             RunError,
             r"
                 INSERT INTO trip_todos
-                    (id, description, done, trip_id)
-                SELECT $1, $2, false, id as trip_id
+                    (id, description, done, trip_id, position, due_date, due_days_before, assignee)
+                SELECT
+                    $1,
+                    $2,
+                    false,
+                    trips.id,
+                    COALESCE(
+                        (SELECT MAX(position) + 1 FROM trip_todos WHERE trip_id = trips.id),
+                        0
+                    ),
+                    $5,
+                    $6,
+                    $7
                 FROM trips
-                WHERE id = $3 AND EXISTS(SELECT 1 FROM trips WHERE id = $3 and user_id = $4)
-                LIMIT 1
+                WHERE trips.id = $3 AND trips.user_id = $4
             ",
             id.0,
             info.description,
             container.trip_id,
             ctx.user.id,
+            due_date,
+            due_days_before,
+            info.assignee,
         )
         .await?;
 
@@ -283,10 +415,32 @@ impl From<String> for DescriptionUpdate {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct DueUpdate(Option<Due>);
+
+impl From<Option<Due>> for DueUpdate {
+    fn from(new_due: Option<Due>) -> Self {
+        Self(new_due)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AssigneeUpdate(Option<String>);
+
+impl From<Option<String>> for AssigneeUpdate {
+    fn from(new_assignee: Option<String>) -> Self {
+        Self(new_assignee)
+    }
+}
+
 #[derive(Debug)]
 pub enum UpdateElement {
     State(StateUpdate),
     Description(DescriptionUpdate),
+    Due(DueUpdate),
+    Assignee(AssigneeUpdate),
+    /// Swap the manual position with the previous or next todo
+    Move(Direction),
 }
 
 #[async_trait]
@@ -305,14 +459,12 @@ impl crud::Update for Todo {
             UpdateElement::State(state) => {
                 let done = state == State::Done.into();
 
-                let result = database::query_one!(
+                database::execute!(
                     &database::QueryClassification {
                         query_type: database::QueryType::Update,
-                        component: crate::Component::Trips,
+                        component: crate::Component::Todo,
                     },
                     pool,
-                    TodoRow,
-                    Todo,
                     RunError,
                     r"
                         UPDATE trip_todos
@@ -320,10 +472,6 @@ impl crud::Update for Todo {
                         WHERE trip_id = $2
                         AND id = $3
                         AND EXISTS(SELECT 1 FROM trips WHERE id = $2 AND user_id = $4)
-                        RETURNING
-                            id,
-                            description,
-                            done
                     ",
                     done,
                     reference.container.trip_id,
@@ -331,18 +479,14 @@ impl crud::Update for Todo {
                     ctx.user.id
                 )
                 .await?;
-
-                Ok(result)
             }
             UpdateElement::Description(new_description) => {
-                let result = database::query_one!(
+                database::execute!(
                     &database::QueryClassification {
                         query_type: database::QueryType::Update,
                         component: crate::Component::Todo,
                     },
                     pool,
-                    TodoRow,
-                    Todo,
                     RunError,
                     r"
                         UPDATE trip_todos
@@ -350,11 +494,7 @@ impl crud::Update for Todo {
                         WHERE
                             id = $2
                             AND trip_id = $3
-                            AND EXISTS(SELECT 1 FROM trips WHERE trip_id = $3 AND user_id = $4)
-                        RETURNING
-                            id,
-                            description,
-                            done
+                            AND EXISTS(SELECT 1 FROM trips WHERE id = $3 AND user_id = $4)
                     ",
                     new_description.0,
                     reference.id.0,
@@ -362,10 +502,132 @@ impl crud::Update for Todo {
                     ctx.user.id,
                 )
                 .await?;
-
-                Ok(result)
+            }
+            UpdateElement::Due(new_due) => {
+                let (due_date, due_days_before) = Due::into_columns(new_due.0);
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Todo,
+                    },
+                    pool,
+                    RunError,
+                    r"
+                        UPDATE trip_todos
+                        SET
+                            due_date = $1,
+                            due_days_before = $2
+                        WHERE
+                            id = $3
+                            AND trip_id = $4
+                            AND EXISTS(SELECT 1 FROM trips WHERE id = $4 AND user_id = $5)
+                    ",
+                    due_date,
+                    due_days_before,
+                    reference.id.0,
+                    reference.container.trip_id,
+                    ctx.user.id,
+                )
+                .await?;
+            }
+            UpdateElement::Assignee(new_assignee) => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Todo,
+                    },
+                    pool,
+                    RunError,
+                    r"
+                        UPDATE trip_todos
+                        SET assignee = $1
+                        WHERE
+                            id = $2
+                            AND trip_id = $3
+                            AND EXISTS(SELECT 1 FROM trips WHERE id = $3 AND user_id = $4)
+                    ",
+                    new_assignee.0,
+                    reference.id.0,
+                    reference.container.trip_id,
+                    ctx.user.id,
+                )
+                .await?;
+            }
+            UpdateElement::Move(Direction::Up) => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Todo,
+                    },
+                    pool,
+                    RunError,
+                    r"
+                        WITH current_todo AS (
+                            SELECT todo.id, todo.position
+                            FROM trip_todos AS todo
+                            INNER JOIN trips
+                                ON trips.id = todo.trip_id
+                            WHERE todo.id = $1 AND trips.id = $2 AND trips.user_id = $3
+                        ), other_todo AS (
+                            SELECT todo.id, todo.position
+                            FROM trip_todos AS todo, current_todo
+                            WHERE todo.trip_id = $2 AND todo.position < current_todo.position
+                            ORDER BY todo.position DESC
+                            LIMIT 1
+                        )
+                        UPDATE trip_todos AS todo
+                        SET position = CASE
+                            WHEN todo.id = current_todo.id THEN other_todo.position
+                            ELSE current_todo.position
+                        END
+                        FROM current_todo, other_todo
+                        WHERE todo.id IN (current_todo.id, other_todo.id)
+                    ",
+                    reference.id.0,
+                    reference.container.trip_id,
+                    ctx.user.id,
+                )
+                .await?;
+            }
+            UpdateElement::Move(Direction::Down) => {
+                database::execute!(
+                    &database::QueryClassification {
+                        query_type: database::QueryType::Update,
+                        component: crate::Component::Todo,
+                    },
+                    pool,
+                    RunError,
+                    r"
+                        WITH current_todo AS (
+                            SELECT todo.id, todo.position
+                            FROM trip_todos AS todo
+                            INNER JOIN trips
+                                ON trips.id = todo.trip_id
+                            WHERE todo.id = $1 AND trips.id = $2 AND trips.user_id = $3
+                        ), other_todo AS (
+                            SELECT todo.id, todo.position
+                            FROM trip_todos AS todo, current_todo
+                            WHERE todo.trip_id = $2 AND todo.position > current_todo.position
+                            ORDER BY todo.position ASC
+                            LIMIT 1
+                        )
+                        UPDATE trip_todos AS todo
+                        SET position = CASE
+                            WHEN todo.id = current_todo.id THEN other_todo.position
+                            ELSE current_todo.position
+                        END
+                        FROM current_todo, other_todo
+                        WHERE todo.id IN (current_todo.id, other_todo.id)
+                    ",
+                    reference.id.0,
+                    reference.container.trip_id,
+                    ctx.user.id,
+                )
+                .await?;
             }
         }
+
+        Self::find(ctx, pool, reference).await
     }
 }
 
@@ -406,13 +668,18 @@ impl crud::Delete for Todo {
 #[derive(Debug, PartialEq, Eq)]
 pub enum UiState {
     Default,
-    Edit,
+    /// The assignee is chosen from the participants of the trip
+    Edit {
+        participants: Vec<String>,
+    },
 }
 
 #[derive(Debug)]
 pub struct BuildInput {
     pub trip_id: Uuid,
     pub state: UiState,
+    /// Only todos in manual order can be moved
+    pub movable: bool,
 }
 
 impl view::View for Todo {
@@ -428,10 +695,11 @@ impl view::View for Todo {
                 ."justify-start"
                 ."items-stretch"
                 ."bg-green-50"[done]
-                ."bg-red-50"[!done]
+                ."bg-red-50"[!done && !self.overdue]
+                ."bg-red-200"[self.overdue]
                 ."h-full"
             {
-                @if input.state == UiState::Edit {
+                @if let UiState::Edit { ref participants } = input.state {
                     form
                         name="edit-todo"
                         id="edit-todo"
@@ -447,7 +715,7 @@ impl view::View for Todo {
                             "/todo/" (self.id)
                             "/edit/save"
                         }
-                        hx-target="closest li"
+                        hx-target="#todolist"
                         hx-swap="outerHTML"
                     {}
                     div
@@ -465,16 +733,56 @@ impl view::View for Todo {
                     }
                     div
                         ."p-2"
+                        ."flex"
+                        ."flex-row"
+                        ."gap-2"
                         .grow
                     {
                         input
-                            ."w-full"
+                            ."grow"
                             type="text"
                             form="edit-todo"
                             id="todo-description"
                             name="todo-description"
                             value=(self.description)
                         {}
+                        input
+                            type="date"
+                            form="edit-todo"
+                            name="todo-due-date"
+                            title="Due date"
+                            value=[self.due.and_then(|due| match due {
+                                Due::Date(date) => Some(date),
+                                Due::BeforeStart(_) => None,
+                            })]
+                        {}
+                        input
+                            type="number"
+                            form="edit-todo"
+                            name="todo-due-days-before"
+                            title="Due days before the trip starts"
+                            placeholder="days before"
+                            ."w-28"
+                            value=[self.due.and_then(|due| match due {
+                                Due::Date(_) => None,
+                                Due::BeforeStart(days) => Some(days),
+                            })]
+                        {}
+                        select
+                            form="edit-todo"
+                            name="todo-assignee"
+                            title="Assignee"
+                        {
+                            option value="" { "[Nobody]" }
+                            @for participant in participants {
+                                option
+                                    value=(participant)
+                                    selected[self.assignee.as_ref() == Some(participant)]
+                                {
+                                    (participant)
+                                }
+                            }
+                        }
                     }
                     button
                         type="submit"
@@ -574,6 +882,62 @@ impl view::View for Todo {
                     {
                         (self.description)
                     }
+                    @if let Some(ref assignee) = self.assignee {
+                        span
+                            ."p-2"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                            ."text-gray-600"
+                        {
+                            span ."mdi" ."mdi-account" {}
+                            (assignee)
+                        }
+                    }
+                    @if let Some(due) = self.due {
+                        span
+                            ."p-2"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                            ."text-gray-600"[!self.overdue]
+                            ."text-red-700"[self.overdue]
+                            ."font-bold"[self.overdue]
+                            title=[self.due_at]
+                        {
+                            span ."mdi" ."mdi-calendar-clock" {}
+                            (due)
+                        }
+                    }
+                    @if input.movable {
+                        @for (direction, icon) in [("up", "mdi-arrow-up"), ("down", "mdi-arrow-down")] {
+                            form
+                                action={
+                                    "/trips/" (input.trip_id)
+                                    "/todo/" (self.id)
+                                    "/" (direction)
+                                }
+                                method="post"
+                                hx-post={
+                                    "/trips/" (input.trip_id)
+                                    "/todo/" (self.id)
+                                    "/" (direction)
+                                }
+                                hx-target="#todolist"
+                                hx-swap="outerHTML"
+                                ."flex"
+                                ."aspect-square"
+                                ."bg-blue-100"
+                                ."hover:bg-blue-200"
+                            {
+                                button type="submit" ."w-full" ."h-full" {
+                                    span ."m-auto" ."mdi" .(icon) ."text-xl" {}
+                                }
+                            }
+                        }
+                    }
                     a
                         ."flex"
                         ."flex-row"
@@ -621,6 +985,49 @@ pub struct TripTodoNew {
     description: String,
 }
 
+#[tracing::instrument]
+async fn todo_list(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+) -> Result<Response<Body>, crate::RunError> {
+    let trip = Trip::find(ctx, &state.database_pool, trip_id).await?;
+    match trip {
+        None => Err(crate::RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        })),
+        Some(mut trip) => {
+            trip.load_todos(ctx, &state.database_pool).await?;
+            let participants = Participant::findall(
+                ctx,
+                &state.database_pool,
+                super::expenses::Container { trip_id },
+            )
+            .await?;
+            Ok(list::List {
+                trip: &trip,
+                todos: trip.todos(),
+                participants: &participants,
+            }
+            .build(list::BuildInput { edit_todo: None })
+            .into_response())
+        }
+    }
+}
+
+/// Whether the todos of the trip are in manual order, so single todos can be
+/// rendered with the controls to move them
+#[tracing::instrument]
+async fn is_movable(
+    ctx: &Context,
+    state: &AppState,
+    trip_id: Uuid,
+) -> Result<bool, crate::RunError> {
+    Ok(Trip::find(ctx, &state.database_pool, trip_id)
+        .await?
+        .is_some_and(|trip| trip.todo_order == Order::Manual))
+}
+
 #[async_trait]
 impl route::Create for Todo {
     type Form = TripTodoNew;
@@ -644,26 +1051,14 @@ impl route::Create for Todo {
             Container { trip_id },
             TodoNew {
                 description: form.description,
+                due: None,
+                assignee: None,
             },
         )
         .await?;
 
         if htmx::is_htmx(&headers) {
-            let trip = Trip::find(&ctx, &state.database_pool, trip_id).await?;
-            match trip {
-                None => Err(crate::RunError::Request(RequestError::NotFound {
-                    message: format!("trip with id {trip_id} not found"),
-                })),
-                Some(mut trip) => {
-                    trip.load_todos(&ctx, &state.database_pool).await?;
-                    Ok(list::List {
-                        trip: &trip,
-                        todos: trip.todos(),
-                    }
-                    .build(list::BuildInput { edit_todo: None })
-                    .into_response())
-                }
-            }
+            todo_list(&ctx, &state, trip_id).await
         } else {
             Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
         }
@@ -700,21 +1095,7 @@ impl route::Delete for Todo {
             }));
         }
 
-        let trip = super::model::Trip::find(&ctx, &state.database_pool, trip_id).await?;
-        match trip {
-            None => Err(crate::RunError::Request(RequestError::NotFound {
-                message: format!("trip with id {trip_id} not found"),
-            })),
-            Some(mut trip) => {
-                trip.load_todos(&ctx, &state.database_pool).await?;
-                Ok(list::List {
-                    trip: &trip,
-                    todos: trip.todos(),
-                }
-                .build(list::BuildInput { edit_todo: None })
-                .into_response())
-            }
-        }
+        todo_list(&ctx, &state, trip_id).await
     }
 }
 
@@ -724,6 +1105,9 @@ impl route::Router for Todo {
             .route("/{id}/edit", post(edit_todo))
             .route("/{id}/edit/save", post(edit_todo_save))
             .route("/{id}/edit/cancel", post(edit_todo_cancel))
+            .route("/{id}/up", post(move_up))
+            .route("/{id}/down", post(move_down))
+            .route("/order/{order}", post(set_order))
             .route("/new", axum::routing::post(<Self as route::Create>::create))
             .route(
                 "/{id}/delete",
@@ -733,6 +1117,76 @@ impl route::Router for Todo {
     }
 }
 
+#[tracing::instrument]
+async fn move_todo(
+    current_user: User,
+    state: AppState,
+    headers: HeaderMap,
+    (trip_id, todo_id): (Uuid, Uuid),
+    direction: Direction,
+) -> Result<Response<Body>, crate::RunError> {
+    let ctx = Context::build(current_user);
+    Todo::update(
+        &ctx,
+        &state.database_pool,
+        (trip_id, todo_id).into(),
+        UpdateElement::Move(direction),
+    )
+    .await?
+    .ok_or_else(|| {
+        crate::RunError::Request(RequestError::NotFound {
+            message: format!("todo with id {todo_id} not found"),
+        })
+    })?;
+
+    if htmx::is_htmx(&headers) {
+        todo_list(&ctx, &state, trip_id).await
+    } else {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+    }
+}
+
+#[tracing::instrument]
+pub async fn move_up(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path(params): Path<(Uuid, Uuid)>,
+) -> Result<Response<Body>, crate::RunError> {
+    move_todo(current_user, state, headers, params, Direction::Up).await
+}
+
+#[tracing::instrument]
+pub async fn move_down(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path(params): Path<(Uuid, Uuid)>,
+) -> Result<Response<Body>, crate::RunError> {
+    move_todo(current_user, state, headers, params, Direction::Down).await
+}
+
+#[tracing::instrument]
+pub async fn set_order(
+    Extension(current_user): Extension<User>,
+    StateExtractor(state): StateExtractor<AppState>,
+    headers: HeaderMap,
+    Path((trip_id, order)): Path<(Uuid, Order)>,
+) -> Result<Response<Body>, crate::RunError> {
+    let ctx = Context::build(current_user);
+    if !Order::set(&ctx, &state.database_pool, trip_id, order).await? {
+        return Err(crate::RunError::Request(RequestError::NotFound {
+            message: format!("trip with id {trip_id} not found"),
+        }));
+    }
+
+    if htmx::is_htmx(&headers) {
+        todo_list(&ctx, &state, trip_id).await
+    } else {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
+    }
+}
+
 #[tracing::instrument]
 #[allow(dead_code)]
 pub async fn trip_todo_done(
@@ -793,6 +1247,7 @@ pub async fn trip_todo_undone_htmx(
     Ok(todo_item.build(BuildInput {
         trip_id,
         state: UiState::Default,
+        movable: is_movable(&ctx, &state, trip_id).await?,
     }))
 }
 
@@ -821,9 +1276,15 @@ pub async fn trip_todo_undone(
 
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
-pub struct TripTodoDescription {
+pub struct TripTodoEdit {
     #[serde(rename = "todo-description")]
     description: String,
+    #[serde(rename = "todo-due-date", deserialize_with = "date_or_empty")]
+    due_date: Option<time::Date>,
+    #[serde(rename = "todo-due-days-before", deserialize_with = "parse_or_empty")]
+    due_days_before: Option<i32>,
+    #[serde(rename = "todo-assignee")]
+    assignee: String,
 }
 
 #[tracing::instrument]
@@ -848,12 +1309,21 @@ pub async fn edit_todo(
         None => Err(crate::RunError::Request(RequestError::NotFound {
             message: format!("todo with id {todo_id} not found"),
         })),
-        Some(todo_item) => Ok(todo_item
-            .build(BuildInput {
-                trip_id,
-                state: UiState::Edit,
-            })
-            .into_response()),
+        Some(todo_item) => {
+            let participants = Participant::findall(
+                &ctx,
+                &state.database_pool,
+                super::expenses::Container { trip_id },
+            )
+            .await?;
+            Ok(todo_item
+                .build(BuildInput {
+                    trip_id,
+                    state: UiState::Edit { participants },
+                    movable: false,
+                })
+                .into_response())
+        }
     }
 }
 
@@ -863,36 +1333,66 @@ pub async fn edit_todo_save(
     StateExtractor(state): StateExtractor<AppState>,
     headers: HeaderMap,
     Path((trip_id, todo_id)): Path<(Uuid, Uuid)>,
-    Form(form): Form<TripTodoDescription>,
+    Form(form): Form<TripTodoEdit>,
 ) -> Result<impl IntoResponse, crate::RunError> {
     let ctx = Context::build(current_user);
-    let todo_item = Todo::update(
-        &ctx,
-        &state.database_pool,
-        Reference {
-            id: Id(todo_id),
-            container: Container { trip_id },
-        },
+
+    let due = match (form.due_date, form.due_days_before) {
+        (Some(_), Some(_)) => {
+            return Err(crate::RunError::Request(RequestError::InvalidFormElement {
+                name: "todo-due-date".to_string(),
+                message: "a todo is either due on a date or relative to the trip start".to_string(),
+            }));
+        }
+        (date, days_before) => Due::from_columns(date, days_before),
+    };
+
+    let assignee = Some(form.assignee).filter(|assignee| !assignee.is_empty());
+    if let Some(ref assignee) = assignee {
+        let participants = Participant::findall(
+            &ctx,
+            &state.database_pool,
+            super::expenses::Container { trip_id },
+        )
+        .await?;
+        if !participants.contains(assignee) {
+            return Err(crate::RunError::Request(RequestError::InvalidFormElement {
+                name: "todo-assignee".to_string(),
+                message: format!("{assignee} is not a participant of the trip"),
+            }));
+        }
+    }
+
+    let mut todo_item = None;
+    for update in [
         UpdateElement::Description(form.description.into()),
-    )
-    .await?;
+        UpdateElement::Due(due.into()),
+        UpdateElement::Assignee(assignee.into()),
+    ] {
+        todo_item = Todo::update(
+            &ctx,
+            &state.database_pool,
+            Reference {
+                id: Id(todo_id),
+                container: Container { trip_id },
+            },
+            update,
+        )
+        .await?;
+    }
 
-    match todo_item {
-        None => Err(crate::RunError::Request(RequestError::NotFound {
+    if todo_item.is_none() {
+        return Err(crate::RunError::Request(RequestError::NotFound {
             message: format!("todo with id {todo_id} not found"),
-        })),
-        Some(todo_item) => {
-            if htmx::is_htmx(&headers) {
-                Ok(todo_item
-                    .build(BuildInput {
-                        trip_id,
-                        state: UiState::Default,
-                    })
-                    .into_response())
-            } else {
-                Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
-            }
-        }
+        }));
+    }
+
+    // the position of the todo may change with its due date, so the whole list
+    // is rendered again
+    if htmx::is_htmx(&headers) {
+        todo_list(&ctx, &state, trip_id).await
+    } else {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")).into_response())
     }
 }
 
@@ -922,6 +1422,7 @@ pub async fn edit_todo_cancel(
             .build(BuildInput {
                 trip_id,
                 state: UiState::Default,
+                movable: is_movable(&ctx, &state, trip_id).await?,
             })
             .into_response()),
     }
@@ -1021,6 +1522,7 @@ impl route::ToggleHtmx for StateUpdate {
             .build(BuildInput {
                 trip_id,
                 state: UiState::Default,
+                movable: is_movable(ctx, &state, trip_id).await?,
             })
             .into_response())
     }
@@ -0,0 +1,750 @@
+//! GPX tracks attached to a trip or to one of its stages. The GPX file is only
+//! parsed on upload, we keep the statistics and a downsampled elevation
+//! profile.
+
+pub mod view;
+
+use axum::{
+    Extension, Router,
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    response::Redirect,
+    routing::post,
+};
+use uuid::Uuid;
+
+use crate::{AppState, Context, RequestError, RunError, models::User};
+
+/// GPX files of multi-day recordings easily get a few megabytes big
+const MAX_FILE_SIZE: usize = 32 * 1024 * 1024;
+
+/// Changes in elevation smaller than this (in meters) are ignored when summing
+/// up gain and loss, as recorded altitudes jitter by a few meters even when
+/// standing still
+const ELEVATION_THRESHOLD: f64 = 5.0;
+
+/// Maximum number of points of the stored elevation profile
+const PROFILE_POINTS: usize = 200;
+
+/// Mean radius of the earth in meters
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Coordinate {
+    lat: f64,
+    lon: f64,
+    elevation: Option<f64>,
+}
+
+impl Coordinate {
+    /// Great-circle distance in meters, using the haversine formula
+    fn distance(&self, other: &Self) -> f64 {
+        let d_lat = (other.lat - self.lat).to_radians();
+        let d_lon = (other.lon - self.lon).to_radians();
+
+        let a = (self.lat.to_radians().cos() * other.lat.to_radians().cos())
+            .mul_add((d_lon / 2.0).sin().powi(2), (d_lat / 2.0).sin().powi(2));
+
+        2.0 * EARTH_RADIUS * a.sqrt().asin()
+    }
+}
+
+fn invalid_file(message: impl Into<String>) -> RunError {
+    RunError::Request(RequestError::InvalidFormElement {
+        name: "track-file".to_owned(),
+        message: message.into(),
+    })
+}
+
+/// Returns the name of the GPX and its segments. The distance between two
+/// segments is not part of the track, e.g. when the recording was paused.
+///
+/// Recorded tracks are preferred over planned routes, so a file containing
+/// both is not counted twice.
+fn parse(input: &str) -> Result<(Option<String>, Vec<Vec<Coordinate>>), RunError> {
+    let document =
+        roxmltree::Document::parse(input).map_err(|error| invalid_file(error.to_string()))?;
+
+    if !document.root_element().has_tag_name("gpx") {
+        return Err(invalid_file("not a GPX file"));
+    }
+
+    let segments = |segment_tag: &str, point_tag: &str| {
+        document
+            .descendants()
+            .filter(|node| node.has_tag_name(segment_tag))
+            .map(|segment| {
+                segment
+                    .children()
+                    .filter(|node| node.has_tag_name(point_tag))
+                    .map(|point| {
+                        // also rejects values that are not finite
+                        let coordinate = |name: &str, limit: f64| {
+                            point
+                                .attribute(name)
+                                .and_then(|value| value.trim().parse::<f64>().ok())
+                                .filter(|value| (-limit..=limit).contains(value))
+                                .ok_or_else(|| {
+                                    invalid_file(format!("{point_tag} without valid {name}"))
+                                })
+                        };
+
+                        Ok(Coordinate {
+                            lat: coordinate("lat", 90.0)?,
+                            lon: coordinate("lon", 180.0)?,
+                            elevation: point
+                                .children()
+                                .find(|node| node.has_tag_name("ele"))
+                                .and_then(|node| node.text())
+                                .and_then(|value| value.trim().parse::<f64>().ok())
+                                .filter(|value| value.is_finite()),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, RunError>>()
+            })
+            .filter(|segment| !segment.as_ref().is_ok_and(Vec::is_empty))
+            .collect::<Result<Vec<_>, RunError>>()
+    };
+
+    let mut result = segments("trkseg", "trkpt")?;
+    if result.is_empty() {
+        result = segments("rte", "rtept")?;
+    }
+
+    if result.is_empty() {
+        return Err(invalid_file(
+            "the GPX does not contain any track or route points",
+        ));
+    }
+
+    let name = ["metadata", "trk", "rte"].into_iter().find_map(|parent| {
+        document
+            .root_element()
+            .children()
+            .find(|node| node.has_tag_name(parent))?
+            .children()
+            .find(|node| node.has_tag_name("name"))?
+            .text()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(ToOwned::to_owned)
+    });
+
+    Ok((name, result))
+}
+
+/// Elevations and the summed up distance are only bounded by the file, so
+/// they can exceed what is stored
+#[allow(clippy::cast_possible_truncation)]
+fn meters(value: f64) -> Result<i32, RunError> {
+    // the cast saturates, so anything too big fails the conversion
+    i32::try_from(value.round() as i64)
+        .map_err(|_| invalid_file(format!("{value} meters are out of range")))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProfilePoint {
+    /// Distance from the start in meters
+    pub distance: i32,
+    /// In meters
+    pub elevation: i32,
+}
+
+#[derive(Debug)]
+pub struct Statistics {
+    /// In meters
+    pub distance: i32,
+    pub elevation_gain: i32,
+    pub elevation_loss: i32,
+    pub max_elevation: Option<i32>,
+    pub bounds: Bounds,
+    pub profile: Vec<ProfilePoint>,
+}
+
+impl Statistics {
+    fn compute(segments: &[Vec<Coordinate>]) -> Result<Self, RunError> {
+        let mut distance = 0.0;
+        let mut gain = 0.0;
+        let mut loss = 0.0;
+        let mut max_elevation: Option<f64> = None;
+        let mut bounds = Bounds {
+            min_lat: f64::INFINITY,
+            min_lon: f64::INFINITY,
+            max_lat: f64::NEG_INFINITY,
+            max_lon: f64::NEG_INFINITY,
+        };
+        let mut profile = vec![];
+
+        for segment in segments {
+            let mut previous: Option<&Coordinate> = None;
+            // the last elevation that was counted, see ELEVATION_THRESHOLD
+            let mut reference: Option<f64> = None;
+
+            for point in segment {
+                if let Some(previous) = previous {
+                    distance += previous.distance(point);
+                }
+                previous = Some(point);
+
+                bounds.min_lat = bounds.min_lat.min(point.lat);
+                bounds.min_lon = bounds.min_lon.min(point.lon);
+                bounds.max_lat = bounds.max_lat.max(point.lat);
+                bounds.max_lon = bounds.max_lon.max(point.lon);
+
+                let Some(elevation) = point.elevation else {
+                    continue;
+                };
+
+                max_elevation = Some(max_elevation.map_or(elevation, |max| max.max(elevation)));
+                profile.push(ProfilePoint {
+                    distance: meters(distance)?,
+                    elevation: meters(elevation)?,
+                });
+
+                match reference {
+                    None => reference = Some(elevation),
+                    Some(previous) if elevation - previous >= ELEVATION_THRESHOLD => {
+                        gain += elevation - previous;
+                        reference = Some(elevation);
+                    }
+                    Some(previous) if previous - elevation >= ELEVATION_THRESHOLD => {
+                        loss += previous - elevation;
+                        reference = Some(elevation);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        // keep the first and the last point, and evenly spaced ones in between
+        if profile.len() > PROFILE_POINTS {
+            let last = profile.len() - 1;
+            profile = (0..PROFILE_POINTS)
+                .map(|index| profile[index * last / (PROFILE_POINTS - 1)])
+                .collect();
+        }
+
+        Ok(Self {
+            distance: meters(distance)?,
+            elevation_gain: meters(gain)?,
+            elevation_loss: meters(loss)?,
+            max_elevation: max_elevation.map(meters).transpose()?,
+            bounds,
+            profile,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Track {
+    pub id: Uuid,
+    pub name: String,
+    pub stage_id: Option<Uuid>,
+    pub statistics: Statistics,
+}
+
+struct TrackRow {
+    id: Uuid,
+    name: String,
+    stage_id: Option<Uuid>,
+    distance: i32,
+    elevation_gain: i32,
+    elevation_loss: i32,
+    max_elevation: Option<i32>,
+    min_lat: f64,
+    min_lon: f64,
+    max_lat: f64,
+    max_lon: f64,
+    profile_distance: Vec<i32>,
+    profile_elevation: Vec<i32>,
+}
+
+impl TryFrom<TrackRow> for Track {
+    type Error = RunError;
+
+    fn try_from(row: TrackRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            stage_id: row.stage_id,
+            statistics: Statistics {
+                distance: row.distance,
+                elevation_gain: row.elevation_gain,
+                elevation_loss: row.elevation_loss,
+                max_elevation: row.max_elevation,
+                bounds: Bounds {
+                    min_lat: row.min_lat,
+                    min_lon: row.min_lon,
+                    max_lat: row.max_lat,
+                    max_lon: row.max_lon,
+                },
+                profile: row
+                    .profile_distance
+                    .into_iter()
+                    .zip(row.profile_elevation)
+                    .map(|(distance, elevation)| ProfilePoint {
+                        distance,
+                        elevation,
+                    })
+                    .collect(),
+            },
+        })
+    }
+}
+
+/// Total distance of all tracks of trips starting in the given year
+#[derive(Debug)]
+pub struct YearDistance {
+    pub year: i32,
+    /// In meters
+    pub distance: i64,
+}
+
+struct YearDistanceRow {
+    year: i32,
+    distance: i64,
+}
+
+impl TryFrom<YearDistanceRow> for YearDistance {
+    type Error = RunError;
+
+    fn try_from(row: YearDistanceRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            year: row.year,
+            distance: row.distance,
+        })
+    }
+}
+
+impl Track {
+    #[tracing::instrument]
+    pub async fn findall(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+    ) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            TrackRow,
+            Self,
+            RunError,
+            "
+                SELECT
+                    track.id AS id,
+                    track.name AS name,
+                    track.stage_id AS stage_id,
+                    track.distance AS distance,
+                    track.elevation_gain AS elevation_gain,
+                    track.elevation_loss AS elevation_loss,
+                    track.max_elevation AS max_elevation,
+                    track.min_lat AS min_lat,
+                    track.min_lon AS min_lon,
+                    track.max_lat AS max_lat,
+                    track.max_lon AS max_lon,
+                    track.profile_distance AS profile_distance,
+                    track.profile_elevation AS profile_elevation
+                FROM trip_tracks AS track
+                LEFT JOIN trip_stages AS stage
+                    ON stage.id = track.stage_id
+                WHERE track.trip_id = $1 AND track.user_id = $2
+                ORDER BY stage.position NULLS FIRST, track.name
+            ",
+            trip_id,
+            ctx.user.id
+        )
+        .await
+    }
+
+    /// Tracks of a stage count for the year the stage starts in, all others
+    /// for the start of the trip
+    #[tracing::instrument]
+    pub async fn distance_per_year(
+        ctx: &Context,
+        pool: &database::Pool,
+    ) -> Result<Vec<YearDistance>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::Trips,
+            },
+            pool,
+            YearDistanceRow,
+            YearDistance,
+            RunError,
+            r#"
+                SELECT
+                    CAST(
+                        EXTRACT(YEAR FROM lower(COALESCE(stage.date, trip.date)))
+                        AS INTEGER
+                    ) AS "year!",
+                    CAST(SUM(track.distance) AS BIGINT) AS "distance!"
+                FROM trip_tracks AS track
+                INNER JOIN trips AS trip
+                    ON trip.id = track.trip_id
+                LEFT JOIN trip_stages AS stage
+                    ON stage.id = track.stage_id
+                WHERE track.user_id = $1
+                GROUP BY 1
+                ORDER BY 1
+            "#,
+            ctx.user.id
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip(statistics))]
+    pub async fn create(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        stage_id: Option<Uuid>,
+        name: &str,
+        statistics: &Statistics,
+    ) -> Result<Uuid, RunError> {
+        if let Some(stage_id) = stage_id {
+            let exists: bool = database::query_exists!(
+                &database::QueryClassification {
+                    query_type: database::QueryType::Select,
+                    component: crate::Component::Trips,
+                },
+                pool,
+                "SELECT stage.id
+                FROM trip_stages AS stage
+                INNER JOIN trips AS trip
+                    ON trip.id = stage.trip_id
+                WHERE stage.id = $1 AND trip.id = $2 AND trip.user_id = $3",
+                stage_id,
+                trip_id,
+                ctx.user.id
+            )
+            .await
+            .map_err(|e: sqlx::Error| -> RunError { e.into() })?;
+
+            if !exists {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: "track-stage-id".to_owned(),
+                    message: format!("stage with id {stage_id} is not part of the trip"),
+                }));
+            }
+        }
+
+        let (profile_distance, profile_elevation): (Vec<i32>, Vec<i32>) = statistics
+            .profile
+            .iter()
+            .map(|point| (point.distance, point.elevation))
+            .unzip();
+
+        let id = Uuid::new_v4();
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "INSERT INTO trip_tracks (
+                id, trip_id, user_id, stage_id, name,
+                distance, elevation_gain, elevation_loss, max_elevation,
+                min_lat, min_lon, max_lat, max_lon,
+                profile_distance, profile_elevation
+            )
+            SELECT $1, id, user_id, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
+            FROM trips
+            WHERE id = $14 AND user_id = $15",
+            id,
+            stage_id,
+            name,
+            statistics.distance,
+            statistics.elevation_gain,
+            statistics.elevation_loss,
+            statistics.max_elevation,
+            statistics.bounds.min_lat,
+            statistics.bounds.min_lon,
+            statistics.bounds.max_lat,
+            statistics.bounds.max_lon,
+            &profile_distance,
+            &profile_elevation,
+            trip_id,
+            ctx.user.id
+        )
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RunError::Request(RequestError::NotFound {
+                message: format!("trip with id {trip_id} not found"),
+            }));
+        }
+
+        Ok(id)
+    }
+
+    #[tracing::instrument]
+    pub async fn delete(
+        ctx: &Context,
+        pool: &database::Pool,
+        trip_id: Uuid,
+        id: Uuid,
+    ) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::Trips,
+            },
+            pool,
+            RunError,
+            "DELETE FROM trip_tracks
+            WHERE id = $1 AND trip_id = $2 AND user_id = $3",
+            id,
+            trip_id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+}
+
+#[tracing::instrument(skip(multipart))]
+pub async fn upload(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path(trip_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+
+    let mut file: Option<(Option<String>, String)> = None;
+    let mut stage_id: Option<Uuid> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|error| invalid_file(error.body_text()))?
+    {
+        match field.name() {
+            Some("track-file") => {
+                let file_name = field.file_name().map(ToOwned::to_owned);
+                let content = field
+                    .text()
+                    .await
+                    .map_err(|error| invalid_file(error.body_text()))?;
+                file = Some((file_name, content));
+            }
+            Some("track-stage-id") => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|error| invalid_file(error.body_text()))?;
+                if !value.is_empty() {
+                    stage_id = Some(Uuid::parse_str(&value).map_err(|error| {
+                        RunError::Request(RequestError::InvalidFormElement {
+                            name: "track-stage-id".to_owned(),
+                            message: error.to_string(),
+                        })
+                    })?);
+                }
+            }
+            name => {
+                return Err(RunError::Request(RequestError::InvalidFormElement {
+                    name: name.unwrap_or_default().to_owned(),
+                    message: "unknown form element".to_owned(),
+                }));
+            }
+        }
+    }
+
+    let Some((file_name, content)) = file.filter(|(_, content)| !content.is_empty()) else {
+        return Err(RunError::Request(RequestError::EmptyFormElement {
+            name: "track-file".to_owned(),
+        }));
+    };
+
+    let (name, segments) = parse(&content)?;
+    let statistics = Statistics::compute(&segments)?;
+
+    // fall back to the file name without its extension
+    let name = name
+        .or_else(|| {
+            file_name
+                .as_deref()
+                .map(|file_name| file_name.trim_end_matches(".gpx").trim().to_owned())
+                .filter(|file_name| !file_name.is_empty())
+        })
+        .unwrap_or_else(|| "Track".to_owned());
+
+    Track::create(
+        &ctx,
+        &state.database_pool,
+        trip_id,
+        stage_id,
+        &name,
+        &statistics,
+    )
+    .await?;
+
+    Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+}
+
+#[tracing::instrument]
+pub async fn delete(
+    Extension(current_user): Extension<User>,
+    State(state): State<AppState>,
+    Path((trip_id, track_id)): Path<(Uuid, Uuid)>,
+) -> Result<Redirect, RunError> {
+    let ctx = Context::build(current_user);
+
+    if Track::delete(&ctx, &state.database_pool, trip_id, track_id).await? {
+        Ok(Redirect::to(&format!("/trips/{trip_id}/")))
+    } else {
+        Err(RunError::Request(RequestError::NotFound {
+            message: format!("track with id {track_id} not found"),
+        }))
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/new",
+            post(upload).layer(DefaultBodyLimit::max(MAX_FILE_SIZE)),
+        )
+        .route("/{id}/delete", post(delete))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Length of one degree along a great circle
+    const DEGREE: f64 = EARTH_RADIUS * std::f64::consts::PI / 180.0;
+
+    fn point(lat: f64, lon: f64, elevation: Option<f64>) -> Coordinate {
+        Coordinate {
+            lat,
+            lon,
+            elevation,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn distance() {
+        let origin = point(0.0, 0.0, None);
+
+        assert_close(origin.distance(&origin), 0.0);
+        assert_close(origin.distance(&point(1.0, 0.0, None)), DEGREE);
+        assert_close(origin.distance(&point(0.0, 1.0, None)), DEGREE);
+        assert_close(point(1.0, 0.0, None).distance(&origin), DEGREE);
+        assert_close(origin.distance(&point(0.0, 180.0, None)), 180.0 * DEGREE);
+        assert_close(
+            point(-90.0, 0.0, None).distance(&point(90.0, 0.0, None)),
+            180.0 * DEGREE,
+        );
+        // at 60°, the parallel is half as long as the equator, and the great
+        // circle is a bit shorter than the way along the parallel
+        let distance = point(60.0, 0.0, None).distance(&point(60.0, 1.0, None));
+        assert!(distance < 0.5 * DEGREE);
+        assert!(distance > 0.4999 * DEGREE);
+    }
+
+    #[test]
+    fn statistics_empty() {
+        let statistics = Statistics::compute(&[]).unwrap();
+
+        assert_eq!(statistics.distance, 0);
+        assert_eq!(statistics.elevation_gain, 0);
+        assert_eq!(statistics.elevation_loss, 0);
+        assert_eq!(statistics.max_elevation, None);
+        assert!(statistics.profile.is_empty());
+    }
+
+    #[test]
+    fn statistics_single_point() {
+        let statistics = Statistics::compute(&[vec![point(47.0, 11.0, Some(1800.4))]]).unwrap();
+
+        assert_eq!(statistics.distance, 0);
+        assert_eq!(statistics.elevation_gain, 0);
+        assert_eq!(statistics.elevation_loss, 0);
+        assert_eq!(statistics.max_elevation, Some(1800));
+        assert_eq!(statistics.profile.len(), 1);
+        assert_close(statistics.bounds.min_lat, 47.0);
+        assert_close(statistics.bounds.max_lon, 11.0);
+    }
+
+    #[test]
+    fn statistics_ascent_and_descent() {
+        // changes below ELEVATION_THRESHOLD are ignored until they add up
+        let statistics = Statistics::compute(&[vec![
+            point(0.0, 0.0, Some(100.0)),
+            point(0.0, 0.001, Some(102.0)),
+            point(0.0, 0.002, Some(110.0)),
+            point(0.0, 0.003, None),
+            point(0.0, 0.004, Some(104.0)),
+            point(0.0, 0.005, Some(90.0)),
+        ]])
+        .unwrap();
+
+        assert_eq!(statistics.elevation_gain, 10);
+        assert_eq!(statistics.elevation_loss, 20);
+        assert_eq!(statistics.max_elevation, Some(110));
+        assert_eq!(statistics.profile.len(), 5);
+        assert_eq!(statistics.distance, meters(0.005 * DEGREE).unwrap());
+    }
+
+    #[test]
+    fn statistics_gap_between_segments() {
+        let statistics = Statistics::compute(&[
+            vec![point(0.0, 0.0, None), point(1.0, 0.0, None)],
+            vec![point(10.0, 0.0, None), point(11.0, 0.0, None)],
+        ])
+        .unwrap();
+
+        assert_eq!(statistics.distance, meters(2.0 * DEGREE).unwrap());
+    }
+
+    #[test]
+    fn statistics_out_of_range() {
+        assert!(Statistics::compute(&[vec![point(0.0, 0.0, Some(1e12))]]).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_coordinates() {
+        let gpx = |lat: &str, lon: &str| {
+            format!(r#"<gpx><trk><trkseg><trkpt lat="{lat}" lon="{lon}"/></trkseg></trk></gpx>"#)
+        };
+
+        assert!(parse(&gpx("47.1", "-11.2")).is_ok());
+        assert!(parse(&gpx("-90", "180")).is_ok());
+        for (lat, lon) in [
+            ("90.5", "0"),
+            ("-91", "0"),
+            ("0", "180.1"),
+            ("0", "-200"),
+            ("NaN", "0"),
+            ("0", "inf"),
+            ("", "0"),
+        ] {
+            assert!(
+                parse(&gpx(lat, lon)).is_err(),
+                "{lat}/{lon} should be rejected"
+            );
+        }
+    }
+}
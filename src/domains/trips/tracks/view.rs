@@ -0,0 +1,220 @@
+use framework::components::{
+    Area, Render as _, StackedBars,
+    chart::{Bar, Point},
+};
+use maud::{Markup, html};
+
+use super::{Statistics, Track, YearDistance};
+use crate::domains::trips::{model::Trip, stages};
+
+fn kilometers(meters: i32) -> String {
+    format!("{:.1} km", f64::from(meters) / 1000.0)
+}
+
+/// Summary of all tracks of the trip, shown in the trip info
+pub struct TrackSummaryRow;
+
+impl TrackSummaryRow {
+    #[tracing::instrument]
+    pub fn build(tracks: &[Track]) -> Markup {
+        let distance = tracks.iter().map(|track| track.statistics.distance).sum();
+        let gain = tracks
+            .iter()
+            .map(|track| track.statistics.elevation_gain)
+            .sum::<i32>();
+        let loss = tracks
+            .iter()
+            .map(|track| track.statistics.elevation_loss)
+            .sum::<i32>();
+        let max_elevation = tracks
+            .iter()
+            .filter_map(|track| track.statistics.max_elevation)
+            .max();
+
+        html!(
+            tr .h-full {
+                td ."border" ."p-2" { "Route" }
+                td
+                    colspan="2"
+                    ."border"
+                    ."p-2"
+                {
+                    div ."flex" ."flex-row" ."flex-wrap" ."gap-4" {
+                        span title="Distance" {
+                            span ."mdi" ."mdi-map-marker-distance" ."mr-1" {}
+                            (kilometers(distance))
+                        }
+                        span title="Elevation gain" {
+                            span ."mdi" ."mdi-arrow-top-right" ."mr-1" {}
+                            (gain) " m"
+                        }
+                        span title="Elevation loss" {
+                            span ."mdi" ."mdi-arrow-bottom-right" ."mr-1" {}
+                            (loss) " m"
+                        }
+                        @if let Some(max_elevation) = max_elevation {
+                            span title="Maximum altitude" {
+                                span ."mdi" ."mdi-summit" ."mr-1" {}
+                                (max_elevation) " m"
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct ElevationProfile;
+
+impl ElevationProfile {
+    #[tracing::instrument]
+    pub fn build(statistics: &Statistics) -> Markup {
+        Area {
+            title: "Elevation profile",
+            x_unit: "km",
+            y_unit: "m",
+            points: statistics
+                .profile
+                .iter()
+                .map(|point| Point {
+                    x: f64::from(point.distance) / 1000.0,
+                    y: f64::from(point.elevation),
+                })
+                .collect(),
+        }
+        .render()
+    }
+}
+
+pub struct TrackSection;
+
+impl TrackSection {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip) -> Markup {
+        let stages = trip.stages();
+
+        html!(
+            div ."flex" ."flex-col" ."gap-5" {
+                h1 ."text-xl" { "Tracks" }
+                @if trip.tracks().is_empty() {
+                    p ."text-gray-400" { "[No tracks]" }
+                }
+                @for track in trip.tracks() {
+                    @let bounds = &track.statistics.bounds;
+                    div ."flex" ."flex-col" ."gap-2" ."border" ."p-3" {
+                        div ."flex" ."flex-row" ."items-center" ."gap-3" {
+                            h2 ."text-lg" ."grow" {
+                                (track.name)
+                                @if let Some(stage) = track.stage_id.and_then(|id| stages.iter().find(|stage| stage.id == stages::Id::new(id))) {
+                                    span ."text-gray-500" ."ml-2" {
+                                        "Stage "
+                                        @match stage.location {
+                                            Some(ref location) => (location),
+                                            None => (stage.date),
+                                        }
+                                    }
+                                }
+                            }
+                            form
+                                action={ "/trips/" (trip.id) "/tracks/" (track.id) "/delete" }
+                                method="post"
+                            {
+                                button
+                                    type="submit"
+                                    title="Delete track"
+                                    ."p-2"
+                                    ."bg-red-200"
+                                    ."hover:bg-red-400"
+                                {
+                                    span ."mdi" ."mdi-delete" ."text-xl" {}
+                                }
+                            }
+                        }
+                        div ."flex" ."flex-row" ."flex-wrap" ."gap-4" ."text-sm" {
+                            span { "Distance: " (kilometers(track.statistics.distance)) }
+                            span { "Gain: " (track.statistics.elevation_gain) " m" }
+                            span { "Loss: " (track.statistics.elevation_loss) " m" }
+                            @if let Some(max_elevation) = track.statistics.max_elevation {
+                                span { "Maximum altitude: " (max_elevation) " m" }
+                            }
+                            span ."text-gray-500" {
+                                (format!(
+                                    "{:.4}, {:.4} – {:.4}, {:.4}",
+                                    bounds.min_lat, bounds.min_lon, bounds.max_lat, bounds.max_lon
+                                ))
+                            }
+                        }
+                        (ElevationProfile::build(&track.statistics))
+                    }
+                }
+                form
+                    action={ "/trips/" (trip.id) "/tracks/new" }
+                    method="post"
+                    enctype="multipart/form-data"
+                    ."flex"
+                    ."flex-row"
+                    ."flex-wrap"
+                    ."items-center"
+                    ."gap-3"
+                {
+                    input
+                        type="file"
+                        name="track-file"
+                        accept=".gpx,application/gpx+xml"
+                        required
+                    {}
+                    select
+                        name="track-stage-id"
+                        aria-label="Stage"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {
+                        option value="" { "[Whole trip]" }
+                        @for stage in stages {
+                            option value=(stage.id) {
+                                (stage.date)
+                                @if let Some(ref location) = stage.location {
+                                    " " (location)
+                                }
+                            }
+                        }
+                    }
+                    button
+                        type="submit"
+                        ."py-1"
+                        ."px-2"
+                        ."bg-green-200"
+                        ."hover:bg-green-300"
+                    {
+                        span ."mdi" ."mdi-upload" ."mr-1" {}
+                        "Upload GPX"
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct DistancePerYear;
+
+impl DistancePerYear {
+    #[tracing::instrument]
+    pub fn build(years: &[YearDistance]) -> Markup {
+        StackedBars {
+            title: "Distance per year",
+            unit: "km",
+            series: vec!["Distance"],
+            bars: years
+                .iter()
+                .map(|year| Bar {
+                    label: year.year.to_string(),
+                    values: vec![i32::try_from(year.distance / 1000).unwrap_or(i32::MAX)],
+                    highlight: false,
+                })
+                .collect(),
+        }
+        .render()
+    }
+}
@@ -0,0 +1,186 @@
+use maud::{Markup, html};
+
+use super::super::compare::{Comparison, Side};
+use super::super::model;
+
+fn delta(value: i32) -> Markup {
+    html!(
+        span
+            ."text-red-600"[value > 0]
+            ."text-green-600"[value < 0]
+            ."text-gray-500"[value == 0]
+        {
+            @if value > 0 { "+" } (value) " g"
+        }
+    )
+}
+
+fn picked(is_picked: bool) -> Markup {
+    html!(
+        @if is_picked {
+            span ."mdi" ."mdi-check" ."text-xl" {}
+        }
+    )
+}
+
+pub struct TripComparison;
+
+impl TripComparison {
+    #[tracing::instrument]
+    pub fn build(
+        target: &model::Trip,
+        comparison: Option<(&model::Trip, &Comparison)>,
+        trips: &[model::Trip],
+    ) -> Markup {
+        let source_id = comparison.map(|(source, _)| source.id);
+        html!(
+            div ."p-8" ."flex" ."flex-col" ."gap-8" {
+                div ."flex" ."flex-row" ."items-center" ."gap-x-3" {
+                    a
+                        href={ "/trips/" (target.id) "/" }
+                        ."text-2xl"
+                        ."mdi"
+                        ."mdi-arrow-left"
+                    {}
+                    h1 ."text-2xl" { "Compare " (target.name) " with" }
+                    form
+                        action={ "/trips/" (target.id) "/compare/" }
+                        method="get"
+                        ."flex"
+                        ."flex-row"
+                        ."gap-x-3"
+                    {
+                        select
+                            name="with"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                            ."p-1"
+                        {
+                            @for trip in trips.iter().rev().filter(|trip| trip.id != target.id) {
+                                option
+                                    value=(trip.id)
+                                    selected[source_id == Some(trip.id)]
+                                {
+                                    (trip.name) " (" (trip.date) ")"
+                                }
+                            }
+                        }
+                        button
+                            type="submit"
+                            ."p-1"
+                            ."px-3"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                        {
+                            "Compare"
+                        }
+                    }
+                }
+                @if let Some((source, comparison)) = comparison {
+                    (Self::table(target, source, comparison))
+                }
+            }
+        )
+    }
+
+    fn table(target: &model::Trip, source: &model::Trip, comparison: &Comparison) -> Markup {
+        html!(
+            @if comparison.categories.is_empty() {
+                p ."text-lg" ."text-center" ."py-5" ."text-gray-400" { "[No items picked]" }
+            } @else {
+                form
+                    action={ "/trips/" (target.id) "/compare/copy" }
+                    method="post"
+                    ."flex"
+                    ."flex-col"
+                    ."gap-5"
+                {
+                    input type="hidden" name="with" value=(source.id) {}
+                    table
+                        ."table"
+                        ."table-auto"
+                        ."border-collapse"
+                        ."border-spacing-0"
+                        ."border"
+                        ."w-full"
+                    {
+                        thead ."bg-gray-200" {
+                            tr ."h-10" {
+                                th ."border" ."p-2" ."w-8" {}
+                                th ."border" ."p-2" { "Name" }
+                                th ."border" ."p-2" ."w-1/6" { (target.name) }
+                                th ."border" ."p-2" ."w-1/6" { (source.name) }
+                                th ."border" ."p-2" ."w-1/6" { "Difference" }
+                            }
+                        }
+                        tbody {
+                            @for category in &comparison.categories {
+                                tr ."h-10" ."bg-gray-100" ."font-bold" {
+                                    td ."border" ."p-2" {}
+                                    td ."border" ."p-2" { (category.category.name) }
+                                    td ."border" ."p-2" { (category.target_weight) " g" }
+                                    td ."border" ."p-2" { (category.source_weight) " g" }
+                                    td ."border" ."p-2" { (delta(category.delta())) }
+                                }
+                                @for compared in &category.items {
+                                    tr
+                                        ."h-10"
+                                        ."bg-green-50"[compared.side == Side::Target]
+                                        ."bg-blue-50"[compared.side == Side::Source]
+                                    {
+                                        td ."border" ."p-2" ."text-center" {
+                                            @if compared.side != Side::Both {
+                                                input
+                                                    type="checkbox"
+                                                    name="item"
+                                                    value=(compared.item.id)
+                                                    aria-label={ "Copy " (compared.item.name) }
+                                                {}
+                                            }
+                                        }
+                                        td ."border" ."p-2" { (compared.item.name) }
+                                        td ."border" ."p-2" ."text-center" {
+                                            (picked(compared.side != Side::Source))
+                                        }
+                                        td ."border" ."p-2" ."text-center" {
+                                            (picked(compared.side != Side::Target))
+                                        }
+                                        td ."border" ."p-2" {
+                                            @if compared.delta() != 0 {
+                                                (delta(compared.delta()))
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            tr ."h-10" ."bg-gray-300" ."font-bold" {
+                                td ."border" ."p-2" {}
+                                td ."border" ."p-2" { "Total" }
+                                td ."border" ."p-2" { (comparison.target_weight()) " g" }
+                                td ."border" ."p-2" { (comparison.source_weight()) " g" }
+                                td ."border" ."p-2" { (delta(comparison.delta())) }
+                            }
+                        }
+                    }
+                    div ."flex" ."flex-row" ."items-center" ."gap-x-3" {
+                        button
+                            type="submit"
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."bg-blue-200"
+                            ."hover:bg-blue-300"
+                        {
+                            "Copy selected into " (target.name)
+                        }
+                        span ."text-gray-500" {
+                            "Selected items get the same picked state in "
+                            (target.name) " as in " (source.name) "."
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
@@ -1,5 +1,9 @@
 use crate::htmx;
 
+use framework::components::{
+    Donut, Render as _, StackedBars,
+    chart::{Bar, Slice},
+};
 use maud::{Markup, PreEscaped, html};
 use uuid::Uuid;
 
@@ -7,7 +11,9 @@ use serde_variant::to_variant_name;
 
 pub struct TripManager;
 
+pub mod compare;
 pub mod packagelist;
+pub mod pdf;
 pub mod types;
 
 use super::model;
@@ -15,8 +21,14 @@ use super::model;
 use crate::domains::{self, view::View};
 
 impl TripManager {
-    #[tracing::instrument]
-    pub fn build(trips: &[model::Trip]) -> Markup {
+    #[tracing::instrument(skip(trips, trip_types, distance_per_year))]
+    pub fn build(
+        page: &model::TripPage,
+        filter: &model::TripFilter,
+        trips: &[model::Trip],
+        trip_types: &[model::TripsType],
+        distance_per_year: &[domains::trips::tracks::YearDistance],
+    ) -> Markup {
         html!(
             div
                 ."p-8"
@@ -25,7 +37,10 @@ impl TripManager {
                 ."gap-8"
             {
                 h1 ."text-2xl" {"Trips"}
-                (TripTable::build(trips))
+                (TripList::build(page, filter, trip_types))
+                @if !distance_per_year.is_empty() {
+                    (domains::trips::tracks::view::DistancePerYear::build(distance_per_year))
+                }
                 (NewTrip::build(trips))
             }
         )
@@ -43,11 +58,193 @@ fn trip_state_icon(state: &model::TripState) -> &'static str {
     }
 }
 
+/// Filter, table and pagination of the trips. Everything in here is swapped
+/// together, so the filter form always reflects the current sort order.
+pub struct TripList;
+
+impl TripList {
+    #[tracing::instrument(skip(page, trip_types))]
+    pub fn build(
+        page: &model::TripPage,
+        filter: &model::TripFilter,
+        trip_types: &[model::TripsType],
+    ) -> Markup {
+        html!(
+            div #trip-list ."flex" ."flex-col" ."gap-5" {
+                (TripFilterForm::build(page, filter, trip_types))
+                @if page.trips.is_empty() {
+                    p ."text-lg" ."text-center" ."py-5" ."text-gray-400" { "[No trips found]" }
+                } @else {
+                    (TripTable::build(&page.trips, filter))
+                }
+                (TripPagination::build(page, filter))
+            }
+        )
+    }
+}
+
+/// Link that replaces the trip list and keeps the URL shareable
+fn trip_list_link(filter: &model::TripFilter, content: &Markup) -> Markup {
+    html!(
+        a
+            href={ "/trips/" (filter.url()) }
+            hx-get={ "/trips/" (filter.url()) }
+            hx-target="#trip-list"
+            hx-swap="outerHTML"
+            hx-push-url="true"
+        {
+            (content)
+        }
+    )
+}
+
+pub struct TripFilterForm;
+
+impl TripFilterForm {
+    #[tracing::instrument(skip(page, trip_types))]
+    pub fn build(
+        page: &model::TripPage,
+        filter: &model::TripFilter,
+        trip_types: &[model::TripsType],
+    ) -> Markup {
+        html!(
+            form
+                action="/trips/"
+                method="get"
+                hx-get="/trips/"
+                hx-target="#trip-list"
+                hx-swap="outerHTML"
+                hx-push-url="true"
+                hx-trigger="change, submit"
+                ."flex"
+                ."flex-row"
+                ."flex-wrap"
+                ."items-center"
+                ."gap-3"
+            {
+                input type="hidden" name="sort" value=(filter.sort.id()) {}
+                input type="hidden" name="direction" value=(filter.direction.id()) {}
+                @if filter.archived {
+                    input type="hidden" name="archived" value="true" {}
+                }
+                select
+                    name="state"
+                    aria-label="State"
+                    ."bg-blue-100"
+                    ."hover:bg-white"
+                    ."p-1"
+                {
+                    option value="" { "[All states]" }
+                    @for state in std::iter::successors(Some(model::TripState::new()), model::TripState::next) {
+                        option
+                            value=(state)
+                            selected[filter.state.as_ref() == Some(&state)]
+                        {
+                            (state)
+                        }
+                    }
+                }
+                select
+                    name="type"
+                    aria-label="Type"
+                    ."bg-blue-100"
+                    ."hover:bg-white"
+                    ."p-1"
+                {
+                    option value="" { "[All types]" }
+                    @for trip_type in trip_types {
+                        option
+                            value=(trip_type.id)
+                            selected[filter.trip_type == Some(trip_type.id)]
+                        {
+                            (trip_type.name)
+                        }
+                    }
+                }
+                label ."flex" ."flex-row" ."items-center" ."gap-1" {
+                    span { "From" }
+                    input
+                        type="date"
+                        name="from"
+                        value=[filter.from]
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {}
+                }
+                label ."flex" ."flex-row" ."items-center" ."gap-1" {
+                    span { "To" }
+                    input
+                        type="date"
+                        name="to"
+                        value=[filter.to]
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {}
+                }
+                input
+                    type="text"
+                    name="location"
+                    placeholder="Location"
+                    value=(filter.location)
+                    ."bg-blue-100"
+                    ."hover:bg-white"
+                    ."p-1"
+                {}
+                button
+                    type="submit"
+                    ."p-1"
+                    ."px-3"
+                    ."border-2"
+                    ."border-gray-500"
+                    ."hover:bg-gray-200"
+                {
+                    "Filter"
+                }
+                a href="/trips/" ."text-gray-500" ."hover:text-gray-700" { "Reset" }
+                @if page.archived > 0 || filter.archived {
+                    span ."ml-auto" ."text-gray-500" ."hover:text-gray-700" {
+                        (trip_list_link(&filter.with_archived(!filter.archived), &html!(
+                            span ."flex" ."flex-row" ."items-center" ."gap-2" {
+                                span ."mdi" ."mdi-archive-outline" ."text-xl" {}
+                                @if filter.archived {
+                                    span { "Hide archived trips" }
+                                } @else {
+                                    span { "Show archived trips (" (page.archived) ")" }
+                                }
+                            }
+                        )))
+                    }
+                }
+            }
+        )
+    }
+}
+
 pub struct TripTable;
 
 impl TripTable {
-    #[tracing::instrument]
-    pub fn build(trips: &[model::Trip]) -> Markup {
+    fn header(filter: &model::TripFilter, column: model::TripSortColumn, name: &str) -> Markup {
+        html!(
+            th ."border" ."p-2" {
+                (trip_list_link(&filter.with_sort(column), &html!(
+                    span ."flex" ."flex-row" ."items-center" ."justify-center" ."gap-1" {
+                        span { (name) }
+                        @if filter.sort == column {
+                            @match filter.direction {
+                                model::SortDirection::Asc => span ."mdi" ."mdi-arrow-up" {},
+                                model::SortDirection::Desc => span ."mdi" ."mdi-arrow-down" {},
+                            }
+                        }
+                    }
+                )))
+            }
+        )
+    }
+
+    #[tracing::instrument(skip(trips))]
+    pub fn build(trips: &[model::Trip], filter: &model::TripFilter) -> Markup {
         html!(
             table
                 ."table"
@@ -59,11 +256,12 @@ impl TripTable {
             {
                 thead ."bg-gray-200" {
                     tr ."h-10" {
-                        th ."border" ."p-2" { "Name" }
-                        th ."border" ."p-2" { "From" }
-                        th ."border" ."p-2" { "To" }
-                        th ."border" ."p-2" { "Nights" }
-                        th ."border" ."p-2" { "State" }
+                        (Self::header(filter, model::TripSortColumn::Name, "Name"))
+                        (Self::header(filter, model::TripSortColumn::Start, "From"))
+                        (Self::header(filter, model::TripSortColumn::End, "To"))
+                        (Self::header(filter, model::TripSortColumn::Nights, "Nights"))
+                        (Self::header(filter, model::TripSortColumn::Location, "Location"))
+                        (Self::header(filter, model::TripSortColumn::State, "State"))
                     }
                 }
                 tbody {
@@ -73,6 +271,7 @@ impl TripTable {
                             (TripTableRow::build(trip.id, trip.date.start.to_string()))
                             (TripTableRow::build(trip.id, trip.date.end.to_string()))
                             (TripTableRow::build(trip.id, (trip.date.end - trip.date.start).whole_days()))
+                            (TripTableRow::build(trip.id, trip.location.as_deref().unwrap_or_default()))
                             (TripTableRow::build(trip.id, html!(
                                 span .flex .flex-row .items-center {
                                     span ."mdi" .(trip_state_icon(&trip.state)) ."text-xl" ."mr-2" {}
@@ -87,6 +286,32 @@ impl TripTable {
     }
 }
 
+pub struct TripPagination;
+
+impl TripPagination {
+    #[tracing::instrument(skip(page))]
+    pub fn build(page: &model::TripPage, filter: &model::TripFilter) -> Markup {
+        let pages = page.pages();
+        html!(
+            div ."flex" ."flex-row" ."items-center" ."justify-center" ."gap-5" {
+                @if filter.page > 1 {
+                    (trip_list_link(&filter.with_page(filter.page - 1), &html!(
+                        span ."mdi" ."mdi-chevron-left" ."text-2xl" {}
+                    )))
+                }
+                span ."text-gray-500" {
+                    "Page " (filter.page) " of " (pages) " (" (page.total) " trips)"
+                }
+                @if filter.page < pages {
+                    (trip_list_link(&filter.with_page(filter.page + 1), &html!(
+                        span ."mdi" ."mdi-chevron-right" ."text-2xl" {}
+                    )))
+                }
+            }
+        )
+    }
+}
+
 pub struct TripTableRow;
 
 impl TripTableRow {
@@ -243,6 +468,8 @@ impl Trip {
         trip_edit_attribute: Option<&model::TripAttribute>,
         active_category: Option<&model::TripCategory>,
         edit_todo: Option<Uuid>,
+        weight_history: &[model::TripWeight],
+        review: Option<&domains::trips::review::Review>,
     ) -> Markup {
         html!(
             div ."p-8" ."flex" ."flex-col" ."gap-8" {
@@ -344,26 +571,275 @@ impl Trip {
                             }
                         }
                     }
-                    a
-                        href={"/trips/" (trip.id) "/packagelist/"}
-                        ."p-2"
-                        ."border-2"
-                        ."border-gray-500"
-                        ."bg-blue-200"
-                        ."hover:bg-blue-200"
+                    div
+                        ."flex"
+                        ."flex-row"
+                        ."items-stretch"
+                        ."gap-x-3"
                     {
-                        "Show Package List"
+                        a
+                            href={"/trips/" (trip.id) "/packagelist/"}
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."bg-blue-200"
+                            ."hover:bg-blue-200"
+                        {
+                            "Show Package List"
+                        }
+                        a
+                            href={"/trips/" (trip.id) "/compare/"}
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                        {
+                            span ."mdi" ."mdi-compare-horizontal" {}
+                            "Compare"
+                        }
+                        a
+                            href={"/trips/" (trip.id) "/export.md"}
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                        {
+                            span ."mdi" ."mdi-language-markdown-outline" {}
+                            "Markdown"
+                        }
+                        a
+                            href={"/trips/" (trip.id) "/export.txt"}
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                        {
+                            span ."mdi" ."mdi-text-box-outline" {}
+                            "Text"
+                        }
+                        form
+                            action={"/trips/" (trip.id) @if trip.archived { "/unarchive" } @else { "/archive" }}
+                            method="post"
+                            ."flex"
+                        {
+                            button
+                                type="submit"
+                                ."p-2"
+                                ."border-2"
+                                ."border-gray-500"
+                                ."hover:bg-gray-200"
+                                ."flex"
+                                ."flex-row"
+                                ."items-center"
+                                ."gap-1"
+                            {
+                                @if trip.archived {
+                                    span ."mdi" ."mdi-archive-arrow-up-outline" {}
+                                    "Unarchive"
+                                } @else {
+                                    span ."mdi" ."mdi-archive-arrow-down-outline" {}
+                                    "Archive"
+                                }
+                            }
+                        }
+                        form
+                            action={"/trips/" (trip.id) "/delete"}
+                            method="post"
+                            onsubmit="return confirm('Delete this trip? This cannot be undone.')"
+                            ."flex"
+                        {
+                            button
+                                type="submit"
+                                ."p-2"
+                                ."border-2"
+                                ."border-red-500"
+                                ."bg-red-200"
+                                ."hover:bg-red-300"
+                                ."flex"
+                                ."flex-row"
+                                ."items-center"
+                                ."gap-1"
+                            {
+                                span ."mdi" ."mdi-delete" {}
+                                "Delete Trip"
+                            }
+                        }
                     }
                 }
                 (TripInfo::build(trip_edit_attribute, trip))
-                (domains::trips::todos::List{todos: trip.todos(), trip}.build(domains::trips::todos::list::BuildInput { edit_todo}))
+                (TripWeightCharts::build(trip, weight_history))
+                @if let Some(review) = review {
+                    (domains::trips::review::view::ReviewSection::build(trip, review))
+                }
+                (domains::trips::stages::List{stages: trip.stages(), trip}.build(domains::trips::stages::list::BuildInput {}))
+                (domains::trips::tracks::view::TrackSection::build(trip))
+                (domains::trips::todos::List{todos: trip.todos(), participants: &trip.expenses().participants, trip}.build(domains::trips::todos::list::BuildInput { edit_todo}))
+                (domains::trips::expenses::List{expenses: trip.expenses(), trip}.build(domains::trips::expenses::list::BuildInput {}))
+                (domains::trips::containers::view::ContainerSection::build(trip))
+                (TripStateHistory::build(trip.id, trip.state_history()))
                 (TripComment::build(trip))
+                (NewTripItems::build(trip))
                 (TripItems::build(active_category, trip))
             }
         )
     }
 }
 
+/// Items that were added to the inventory after the trip was created, so the
+/// user can decide whether to take them along
+pub struct NewTripItems;
+
+impl NewTripItems {
+    #[tracing::instrument]
+    pub fn build(trip: &model::Trip) -> Markup {
+        let new_items = trip
+            .categories()
+            .iter()
+            .flat_map(|category| {
+                category
+                    .items
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .filter(|item| item.new)
+                    .map(move |item| (category, item))
+            })
+            .collect::<Vec<_>>();
+
+        html!(
+            @if !new_items.is_empty() {
+                div #new-items ."flex" ."flex-col" ."gap-5" {
+                    div ."flex" ."flex-row" ."items-center" ."gap-3" {
+                        h1 ."text-xl" ."grow" {
+                            "New items"
+                            span ."text-gray-500" ."ml-2" { "(" (new_items.len()) ")" }
+                        }
+                        form
+                            action={ "/trips/" (trip.id) "/items/new/pick" }
+                            method="post"
+                        {
+                            button
+                                type="submit"
+                                ."py-1"
+                                ."px-2"
+                                ."bg-green-200"
+                                ."hover:bg-green-300"
+                            {
+                                span ."mdi" ."mdi-check-all" ."mr-1" {}
+                                "Pick all"
+                            }
+                        }
+                        form
+                            action={ "/trips/" (trip.id) "/items/new/dismiss" }
+                            method="post"
+                        {
+                            button
+                                type="submit"
+                                ."py-1"
+                                ."px-2"
+                                ."bg-gray-200"
+                                ."hover:bg-gray-300"
+                            {
+                                span ."mdi" ."mdi-close" ."mr-1" {}
+                                "Dismiss all"
+                            }
+                        }
+                    }
+                    p ."text-gray-500" {
+                        "These items were added to the inventory after the trip was created."
+                    }
+                    table
+                        ."table"
+                        ."table-auto"
+                        ."border-collapse"
+                        ."border-spacing-0"
+                        ."border"
+                        ."w-full"
+                    {
+                        thead ."bg-gray-200" {
+                            tr ."h-10" {
+                                th ."border" ."p-2" { "Category" }
+                                th ."border" ."p-2" { "Name" }
+                                th ."border" ."p-2" { "Weight" }
+                                th ."border" ."p-2" ."w-8" {}
+                                th ."border" ."p-2" ."w-8" {}
+                            }
+                        }
+                        tbody {
+                            @for (category, item) in &new_items {
+                                tr ."h-10" {
+                                    td ."border" ."p-2" { (category.category.name) }
+                                    td ."border" ."p-2" {
+                                        a
+                                            href={ "/inventory/item/" (item.item.id) "/" }
+                                            ."hover:underline"
+                                        {
+                                            (item.item.name)
+                                        }
+                                    }
+                                    td ."border" ."p-2" { (item.item.weight) }
+                                    td ."border" ."p-0" {
+                                        form
+                                            action={ "/trips/" (trip.id) "/items/" (item.item.id) "/new/pick" }
+                                            method="post"
+                                            ."flex"
+                                            ."h-full"
+                                        {
+                                            button
+                                                type="submit"
+                                                title="Pick"
+                                                ."w-full"
+                                                ."h-full"
+                                                ."p-2"
+                                                ."bg-green-200"
+                                                ."hover:bg-green-300"
+                                            {
+                                                span ."mdi" ."mdi-check" ."text-xl" {}
+                                            }
+                                        }
+                                    }
+                                    td ."border" ."p-0" {
+                                        form
+                                            action={ "/trips/" (trip.id) "/items/" (item.item.id) "/new/dismiss" }
+                                            method="post"
+                                            ."flex"
+                                            ."h-full"
+                                        {
+                                            button
+                                                type="submit"
+                                                title="Dismiss"
+                                                ."w-full"
+                                                ."h-full"
+                                                ."p-2"
+                                                ."bg-gray-200"
+                                                ."hover:bg-gray-300"
+                                            {
+                                                span ."mdi" ."mdi-close" ."text-xl" {}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
 pub trait Input {
     fn input(&self, id: &str, form: &str) -> Markup;
 }
@@ -383,6 +859,7 @@ impl Input for Option<&String> {
 }
 
 impl Input for Option<&i32> {
+    #[allow(clippy::map_unwrap_or)]
     fn input(&self, id: &str, form: &str) -> Markup {
         html!(
             input ."m-auto" ."px-1" ."block" ."w-full" ."bg-blue-100" ."hover:bg-white"
@@ -476,11 +953,12 @@ where
         value: AttributeValue<'a, T>,
         attribute_key: model::TripAttribute,
         edit_attribute: Option<&model::TripAttribute>,
+        editable: bool,
     ) -> Markup
     where
         Option<&'a T>: Input,
     {
-        let edit = edit_attribute.is_some_and(|a| *a == attribute_key);
+        let edit = editable && edit_attribute.is_some_and(|a| *a == attribute_key);
         html!(
             @if edit {
                 form
@@ -559,26 +1037,47 @@ where
                 } @else {
                     td ."border" ."p-2" { (name) }
                     td ."border" ."p-2" { (value.0.map_or(String::new(), std::string::ToString::to_string)) }
-                    td
-                        ."border-none"
-                        ."bg-blue-100"
-                        ."hover:bg-blue-200"
-                        ."p-0"
-                        ."w-8"
-                        ."h-full"
-                    {
-                        a
-                            href={ "?edit=" (to_variant_name(&attribute_key).unwrap()) }
-                            ."flex"
-                            ."w-full"
+                    @if editable {
+                        td
+                            ."border-none"
+                            ."bg-blue-100"
+                            ."hover:bg-blue-200"
+                            ."p-0"
+                            ."w-8"
                             ."h-full"
                         {
-                            span
-                                ."m-auto"
-                                ."mdi"
-                                ."mdi-pencil"
-                                ."text-xl"
-                            {}
+                            a
+                                href={ "?edit=" (to_variant_name(&attribute_key).unwrap()) }
+                                ."flex"
+                                ."w-full"
+                                ."h-full"
+                            {
+                                span
+                                    ."m-auto"
+                                    ."mdi"
+                                    ."mdi-pencil"
+                                    ."text-xl"
+                                {}
+                            }
+                        }
+                    } @else {
+                        td
+                            ."border-none"
+                            ."bg-gray-100"
+                            ."p-0"
+                            ."w-8"
+                            ."h-full"
+                            title="Derived from the stages"
+                        {
+                            div ."flex" ."w-full" ."h-full" {
+                                span
+                                    ."m-auto"
+                                    ."mdi"
+                                    ."mdi-map-marker-path"
+                                    ."text-xl"
+                                    ."text-gray-500"
+                                {}
+                            }
                         }
                     }
                 }
@@ -605,11 +1104,100 @@ impl TripInfoTotalWeightRow {
     }
 }
 
+pub struct TripWeightCharts;
+
+impl TripWeightCharts {
+    #[tracing::instrument]
+    pub fn build(trip: &model::Trip, history: &[model::TripWeight]) -> Markup {
+        let categories = trip
+            .categories()
+            .iter()
+            .filter(|category| category.total_picked_weight() > 0)
+            .collect::<Vec<_>>();
+
+        let by_category = Donut {
+            title: "Picked weight by category",
+            unit: "g",
+            slices: categories
+                .iter()
+                .map(|category| Slice {
+                    label: category.category.name.clone(),
+                    value: category.total_picked_weight(),
+                })
+                .collect(),
+        };
+
+        let worn_weight = categories
+            .iter()
+            .map(|category| category.total_worn_weight())
+            .sum::<i32>();
+        let mut worn_and_carried = StackedBars {
+            title: "Carried and worn weight",
+            unit: "g",
+            series: vec!["Carried", "Worn"],
+            bars: categories
+                .iter()
+                .map(|category| Bar {
+                    label: category.category.name.clone(),
+                    values: vec![
+                        category.total_picked_weight() - category.total_worn_weight(),
+                        category.total_worn_weight(),
+                    ],
+                    highlight: false,
+                })
+                .collect(),
+        };
+        worn_and_carried.bars.push(Bar {
+            label: "Total".to_owned(),
+            values: vec![trip.total_picked_weight() - worn_weight, worn_weight],
+            highlight: true,
+        });
+
+        let comparison = StackedBars {
+            title: "Compared to past trips",
+            unit: "g",
+            series: vec!["Carried", "Worn"],
+            bars: history
+                .iter()
+                .map(|weight| Bar {
+                    label: weight.name.clone(),
+                    values: vec![weight.carried, weight.worn],
+                    highlight: weight.id == trip.id,
+                })
+                .collect(),
+        };
+
+        html!(
+            div
+                #weight-charts
+                hx-trigger={
+                    (htmx::Event::TripItemEdited.to_str()) " from:body"
+                }
+                hx-get={"/trips/" (trip.id) "/charts"}
+                hx-swap="outerHTML"
+                ."flex"
+                ."flex-col"
+                ."gap-5"
+            {
+                h1 ."text-xl" { "Weight" }
+                div ."grid" ."grid-cols-1" ."lg:grid-cols-2" ."gap-8" {
+                    (by_category.render())
+                    (worn_and_carried.render())
+                }
+                (comparison.render())
+            }
+        )
+    }
+}
+
 pub struct TripInfoStateRow;
 
 impl TripInfoStateRow {
     #[tracing::instrument]
-    pub fn build(trip_state: &model::TripState) -> Markup {
+    pub fn build(
+        trip_state: &model::TripState,
+        blocked: Option<(&model::TripState, &[model::GuardViolation])>,
+    ) -> Markup {
         let prev_state = trip_state.prev();
         let next_state = trip_state.next();
         html!(
@@ -620,6 +1208,41 @@ impl TripInfoStateRow {
                         span ."mdi" .(trip_state_icon(trip_state)) ."text-2xl" ."pl-2" {}
                         span ."pr-2" ."py-2" { (trip_state) }
                     }
+                    @if let Some((new_state, violations)) = blocked {
+                        div
+                            ."flex"
+                            ."flex-col"
+                            ."gap-1"
+                            ."p-2"
+                            ."bg-red-100"
+                        {
+                            span ."font-semibold" { "Cannot move to " (new_state) ":" }
+                            ul ."list-disc" ."pl-6" {
+                                @for violation in violations {
+                                    li { (violation.guard) }
+                                }
+                            }
+                            @if model::StateTransition::confirmable(violations) {
+                                form
+                                    hx-post={"./state/" (new_state) "?confirm=true"}
+                                    hx-target="closest tr"
+                                    hx-swap="outerHTML"
+                                    action={"./state/" (new_state) "?confirm=true"}
+                                    method="post"
+                                {
+                                    button
+                                        type="submit"
+                                        ."p-1"
+                                        ."border"
+                                        ."bg-yellow-100"
+                                        ."hover:bg-yellow-200"
+                                    {
+                                        "Move to " (new_state) " anyway"
+                                    }
+                                }
+                            }
+                        }
+                    }
                 }
 
                 td
@@ -729,7 +1352,10 @@ impl TripInfo {
                         (row)
                     }
 
-                    (TripInfoStateRow::build(&trip.state))
+                    (TripInfoStateRow::build(&trip.state, None))
+                    @if !trip.tracks().is_empty() {
+                        (domains::trips::tracks::view::TrackSummaryRow::build(trip.tracks()))
+                    }
                     tr .h-full {
                         td ."border" ."p-2" { "Types" }
                         td
@@ -851,6 +1477,49 @@ impl TripInfo {
     }
 }
 
+pub struct TripStateHistory;
+
+impl TripStateHistory {
+    #[tracing::instrument]
+    pub fn build(trip_id: Uuid, history: &[model::TripStateChange]) -> Markup {
+        html!(
+            div
+                hx-trigger={
+                    (htmx::Event::TripStateChanged.to_str()) " from:body"
+                }
+                hx-get={"/trips/" (trip_id) "/state/history"}
+                hx-swap="outerHTML"
+            {
+                h1 ."text-xl" ."mb-5" { "History" }
+                ol ."border-l-2" ."border-gray-300" ."flex" ."flex-col" ."gap-2" {
+                    @for change in history.iter().rev() {
+                        li ."flex" ."flex-row" ."items-center" ."gap-2" ."pl-2" {
+                            span ."mdi" .(trip_state_icon(&change.to)) ."text-xl" {}
+                            span {
+                                @if let Some(ref from) = change.from {
+                                    (from) " → "
+                                }
+                                (change.to)
+                            }
+                            span ."text-sm" ."text-gray-500" {
+                                (change.changed_at.date())
+                                " "
+                                (format!("{:02}:{:02}", change.changed_at.hour(), change.changed_at.minute()))
+                                @if change.automated {
+                                    " automatically"
+                                } @else {
+                                    " by "
+                                    (change.username)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
 pub struct TripComment;
 
 impl TripComment {
@@ -921,7 +1590,10 @@ impl TripItems {
                     @if let Some(active_category) = active_category {
                         (TripItemList::build(
                             trip.id,
-                            active_category.items.as_ref().unwrap()
+                            active_category.category.id,
+                            active_category.items.as_ref().unwrap(),
+                            trip.review_hints(),
+                            trip.containers(),
                             )
                         )
                     }
@@ -942,7 +1614,13 @@ impl TripCategoryListRow {
         biggest_category_weight: i32,
         htmx_swap: bool,
     ) -> Markup {
-        let has_new_items = category.items.as_ref().unwrap().iter().any(|item| item.new);
+        let new_items = category
+            .items
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter(|item| item.new)
+            .count();
         html!(
             tr
                 id={"category-" (category.category.id)}
@@ -991,12 +1669,13 @@ impl TripCategoryListRow {
                         {
                             (category.category.name.clone())
                         }
-                        @if has_new_items {
+                        @if new_items > 0 {
                             div
                                 ."mr-2"
                                 ."flex"
                                 ."flex-row"
                                 ."items-center"
+                                ."gap-1"
                             {
                                 p
                                     ."hidden"
@@ -1008,12 +1687,16 @@ impl TripCategoryListRow {
                                     "new items"
                                 }
                                 span
-                                    ."mdi"
-                                    ."mdi-exclamation-thick"
-                                    ."text-xl"
-                                    ."text-yellow-400"
+                                    title={ (new_items) " new items" }
+                                    ."rounded-full"
+                                    ."bg-yellow-400"
+                                    ."text-sm"
+                                    ."font-bold"
+                                    ."px-2"
                                     ."grow-0"
-                                {}
+                                {
+                                    (new_items)
+                                }
                             }
                         }
                     }
@@ -1098,13 +1781,56 @@ pub struct TripItemList;
 
 impl TripItemList {
     #[tracing::instrument]
-    pub fn build(trip_id: Uuid, items: &Vec<model::TripItem>) -> Markup {
-        let biggest_item_weight: i32 = items.iter().map(|item| item.item.weight).max().unwrap_or(1);
+    pub fn build(
+        trip_id: Uuid,
+        category_id: Uuid,
+        items: &Vec<model::TripItem>,
+        review_hints: &[domains::trips::review::ReviewHint],
+        containers: &[domains::trips::containers::Container],
+    ) -> Markup {
+        let biggest_item_weight: i32 = items.iter().map(model::TripItem::weight).max().unwrap_or(1);
 
         html!(
             @if items.is_empty() {
                 p ."text-lg" ."text-center" ."py-5" ."text-gray-400" { "[Empty]" }
             } @else {
+                form
+                    #bulk-items
+                    action={ "/trips/" (trip_id) "/items/bulk" }
+                    method="post"
+                    hx-post={ "/trips/" (trip_id) "/items/bulk" }
+                    hx-target="#trip-items"
+                    hx-swap="outerHTML"
+                    ."flex"
+                    ."flex-row"
+                    ."items-center"
+                    ."gap-2"
+                    ."mb-2"
+                {
+                    input type="hidden" name="category-id" value=(category_id) {}
+                    span ."text-sm" ."text-gray-500" ."grow" {
+                        "Selected items, or all of the category if none are selected:"
+                    }
+                    @for (action, icon, label) in [
+                        ("pick", "mdi-checkbox-marked-outline", "Pick"),
+                        ("unpick", "mdi-checkbox-blank-outline", "Unpick"),
+                        ("pack", "mdi-bag-personal-outline", "Pack"),
+                    ] {
+                        button
+                            type="submit"
+                            name="bulk-action"
+                            value=(action)
+                            ."py-1"
+                            ."px-2"
+                            ."border"
+                            ."border-gray-300"
+                            ."hover:bg-gray-200"
+                        {
+                            span ."mdi" .(icon) ."mr-1" {}
+                            (label)
+                        }
+                    }
+                }
                 table
                     ."table"
                     ."table-auto"
@@ -1116,6 +1842,8 @@ impl TripItemList {
                 {
                     thead ."bg-gray-200" {
                         tr ."h-10" {
+                            th ."border" ."p-2" ."w-8" {}
+                            th ."border" ."p-2" {}
                             th ."border" ."p-2" {}
                             th ."border" ."p-2" {}
                             th ."border" ."p-2" {}
@@ -1125,7 +1853,8 @@ impl TripItemList {
                     }
                     tbody {
                         @for item in items {
-                            (TripItemListRow::build(trip_id, item, biggest_item_weight))
+                            @let hint = review_hints.iter().find(|hint| hint.item_id == item.item.id);
+                            (TripItemListRow::build(trip_id, item, biggest_item_weight, hint, containers))
                         }
                     }
                 }
@@ -1138,9 +1867,24 @@ pub struct TripItemListRow;
 
 impl TripItemListRow {
     #[tracing::instrument]
-    pub fn build(trip_id: Uuid, item: &model::TripItem, biggest_item_weight: i32) -> Markup {
+    pub fn build(
+        trip_id: Uuid,
+        item: &model::TripItem,
+        biggest_item_weight: i32,
+        review_hint: Option<&domains::trips::review::ReviewHint>,
+        containers: &[domains::trips::containers::Container],
+    ) -> Markup {
         html!(
             tr ."h-10" {
+                td ."border" ."p-2" ."text-center" {
+                    input
+                        type="checkbox"
+                        form="bulk-items"
+                        name="item-id"
+                        value=(item.item.id)
+                        aria-label="Select"
+                    {}
+                }
                 td
                     ."border"
                     ."p-0"
@@ -1291,19 +2035,137 @@ impl TripItemListRow {
                         }
                     }
                 }
+                td
+                    ."border"
+                    ."p-0"
+                {
+                    @if item.picked {
+                        a
+                            href={
+                                "/trips/" (trip_id)
+                                "/items/" (item.item.id)
+                                "/" (if item.worn { "unwear" } else { "wear" }) }
+                            hx-post={
+                                "/trips/" (trip_id)
+                                "/items/" (item.item.id)
+                                "/" (if item.worn { "unwear" } else { "wear" }) }
+                            hx-target="closest tr"
+                            hx-swap="outerHTML"
+                            title=(if item.worn { "Worn" } else { "Carried" })
+                            ."inline-block"
+                            ."p-2"
+                            ."m-0"
+                            ."w-full"
+                            ."justify-center"
+                            ."content-center"
+                            ."flex"
+                            ."bg-green-200"[item.worn]
+                            ."hover:bg-green-100"[!item.worn]
+                        {
+                            @if item.worn {
+                                span
+                                    ."mdi"
+                                    ."mdi-tshirt-crew"
+                                    ."text-2xl"
+                                {}
+                            } @else {
+                                span
+                                    ."mdi"
+                                    ."mdi-tshirt-crew-outline"
+                                    ."text-2xl"
+                                {}
+                            }
+                        }
+                    } @else {
+                        div
+                            ."flex"
+                            ."justify-center"
+                            ."items-center"
+                        {
+                            span
+                                ."mdi"
+                                ."mdi-tshirt-crew-outline"
+                                ."text-2xl"
+                                ."text-gray-300"
+                            {}
+                        }
+                    }
+                }
                 td ."border" ."p-0" {
                     div
                         ."flex"
                         ."flex-row"
                         ."items-center"
                     {
-                        a
-                            ."p-2" ."w-full" ."inline-block"
-                            href=(
-                                format!("/inventory/item/{id}/", id=item.item.id)
-                            )
-                        {
-                            (item.item.name.clone())
+                        div ."flex" ."flex-col" ."w-full" {
+                            a
+                                ."p-2" ."pb-0" ."w-full" ."inline-block"
+                                href=(
+                                    format!("/inventory/item/{id}/", id=item.item.id)
+                                )
+                            {
+                                (item.item.name.clone())
+                            }
+                            div ."flex" ."flex-row" ."items-center" {
+                                form
+                                    action={ "/trips/" (trip_id) "/items/" (item.item.id) "/note" }
+                                    target="_self"
+                                    method="post"
+                                    hx-post={ "/trips/" (trip_id) "/items/" (item.item.id) "/note" }
+                                    hx-target="closest tr"
+                                    hx-swap="outerHTML"
+                                    hx-trigger="change"
+                                    ."grow"
+                                    ."px-2"
+                                    ."pb-1"
+                                {
+                                    input
+                                        type="text"
+                                        name="item-note"
+                                        placeholder="Add note"
+                                        value=[item.note.as_ref()]
+                                        autocomplete="off"
+                                        ."w-full"
+                                        ."text-sm"
+                                        ."text-gray-500"
+                                        ."bg-transparent"
+                                        ."hover:bg-white"
+                                        ."focus:bg-white"
+                                    {}
+                                }
+                                form
+                                    action={ "/trips/" (trip_id) "/items/" (item.item.id) "/quantity" }
+                                    target="_self"
+                                    method="post"
+                                    hx-post={ "/trips/" (trip_id) "/items/" (item.item.id) "/quantity" }
+                                    hx-target="closest tr"
+                                    hx-swap="outerHTML"
+                                    hx-trigger="change"
+                                    ."pr-2"
+                                    ."pb-1"
+                                {
+                                    input
+                                        type="number"
+                                        name="item-quantity"
+                                        min="1"
+                                        value=(item.quantity)
+                                        title="Quantity"
+                                        aria-label="Quantity"
+                                        ."w-14"
+                                        ."text-sm"
+                                        ."text-gray-500"
+                                        ."bg-transparent"
+                                        ."hover:bg-white"
+                                        ."focus:bg-white"
+                                    {}
+                                }
+                                @if item.picked && !containers.is_empty() {
+                                    (domains::trips::containers::view::ContainerSelect::build(trip_id, item, containers))
+                                }
+                            }
+                        }
+                        @if let Some(hint) = review_hint {
+                            (domains::trips::review::view::ReviewHintIcon::build(hint))
                         }
                         @if item.new {
                             div ."mr-2" {
@@ -1319,13 +2181,18 @@ impl TripItemListRow {
                     }
                 }
                 td ."border" ."p-2" style="position:relative;" {
-                    p { (item.item.weight.to_string()) }
+                    p {
+                        (item.weight().to_string())
+                        @if item.quantity > 1 {
+                            span ."text-sm" ."text-gray-500" { " (" (item.quantity) " × " (item.item.weight) ")" }
+                        }
+                    }
                     div ."bg-blue-600" ."h-1.5" style=(format!("
                     width: {width}%;
                     position:absolute;
                     left:0;
                     bottom:0;
-                    right:0;", width=(f64::from(item.item.weight) / f64::from(biggest_item_weight) * 100.0))) {}
+                    right:0;", width=(f64::from(item.weight()) / f64::from(biggest_item_weight) * 100.0))) {}
                 }
             }
         )
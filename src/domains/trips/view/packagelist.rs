@@ -1,4 +1,4 @@
-use maud::{html, Markup};
+use maud::{html, Markup, DOCTYPE};
 use uuid::Uuid;
 
 pub struct TripPackageListRowReady;
@@ -17,10 +17,20 @@ impl TripPackageListRowReady {
                 ."hover:bg-white"[!item.packed]
                 ."h-full"
             {
-                span
+                div
                     ."p-2"
+                    ."flex"
+                    ."flex-col"
                 {
-                    (item.item.name)
+                    span {
+                        @if item.quantity > 1 {
+                            (item.quantity) " × "
+                        }
+                        (item.item.name)
+                    }
+                    @if let Some(ref note) = item.note {
+                        span ."text-sm" ."text-gray-500" { (note) }
+                    }
                 }
                 @if item.packed {
                     a
@@ -94,10 +104,20 @@ impl TripPackageListRowUnready {
                 ."hover:bg-white"[!item.ready]
                 ."h-full"
             {
-                span
+                div
                     ."p-2"
+                    ."flex"
+                    ."flex-col"
                 {
-                    (item.item.name)
+                    span {
+                        @if item.quantity > 1 {
+                            (item.quantity) " × "
+                        }
+                        (item.item.name)
+                    }
+                    @if let Some(ref note) = item.note {
+                        span ."text-sm" ."text-gray-500" { (note) }
+                    }
                 }
                 @if item.ready {
                     a
@@ -172,6 +192,7 @@ impl TripPackageListCategoryBlockReady {
 
         html!(
             div
+                id={ "packagelist-category-" (category.category.id) }
                 ."inline-block"
                 ."w-full"
                 ."mb-5"
@@ -181,12 +202,41 @@ impl TripPackageListCategoryBlockReady {
                 ."opacity-30"[empty]
             {
                 div
+                    ."flex"
+                    ."flex-row"
+                    ."justify-between"
+                    ."items-center"
                     ."bg-gray-100"
                     ."border-b-2"
                     ."border-gray-300"
                     ."p-3"
                 {
                     h3 { (category.category.name) }
+                    @if category.items.as_ref().unwrap().iter().any(|item| item.picked && !item.packed) {
+                        form
+                            action={
+                                "/trips/" (trip.id)
+                                "/packagelist/category/"
+                                (category.category.id) "/pack"
+                            }
+                            method="post"
+                            hx-post={
+                                "/trips/" (trip.id)
+                                "/packagelist/category/"
+                                (category.category.id) "/pack"
+                            }
+                            hx-target={ "#packagelist-category-" (category.category.id) }
+                            hx-swap="outerHTML"
+                        {
+                            button
+                                type="submit"
+                                ."text-sm"
+                                ."underline"
+                            {
+                                "Pack all"
+                            }
+                        }
+                    }
                 }
                 @if empty {
                     div
@@ -232,6 +282,7 @@ impl TripPackageListCategoryBlockUnready {
 
         html!(
             div
+                id={ "packagelist-unready-" (category.category.id) }
                 ."inline-block"
                 ."w-full"
                 ."mb-5"
@@ -241,12 +292,41 @@ impl TripPackageListCategoryBlockUnready {
                 ."opacity-30"[empty]
             {
                 div
+                    ."flex"
+                    ."flex-row"
+                    ."justify-between"
+                    ."items-center"
                     ."bg-gray-100"
                     ."border-b-2"
                     ."border-gray-300"
                     ."p-3"
                 {
                     h3 { (category.category.name) }
+                    @if category.items.as_ref().unwrap().iter().any(|item| item.picked && !item.ready) {
+                        form
+                            action={
+                                "/trips/" (trip.id)
+                                "/packagelist/category/"
+                                (category.category.id) "/ready"
+                            }
+                            method="post"
+                            hx-post={
+                                "/trips/" (trip.id)
+                                "/packagelist/category/"
+                                (category.category.id) "/ready"
+                            }
+                            hx-target={ "#packagelist-unready-" (category.category.id) }
+                            hx-swap="outerHTML"
+                        {
+                            button
+                                type="submit"
+                                ."text-sm"
+                                ."underline"
+                            {
+                                "Ready all"
+                            }
+                        }
+                    }
                 }
                 @if empty {
                     div
@@ -274,11 +354,88 @@ impl TripPackageListCategoryBlockUnready {
         )
     }
 }
+/// Picked items packed into the given container, or into no container at all
+pub struct TripPackageListContainerBlock;
+
+impl TripPackageListContainerBlock {
+    #[tracing::instrument]
+    pub fn build(
+        trip: &super::super::model::Trip,
+        container: Option<&super::super::containers::Container>,
+    ) -> Markup {
+        let items = trip
+            .categories()
+            .iter()
+            .flat_map(|category| category.items.as_ref().unwrap())
+            .filter(|item| item.picked && item.container_id == container.map(|c| c.id))
+            .collect::<Vec<_>>();
+
+        html!(
+            div
+                ."inline-block"
+                ."w-full"
+                ."mb-5"
+                ."border"
+                ."border-2"
+                ."border-gray-300"
+                ."border-red-400"[container.is_some_and(|c| c.excess_weight().is_some())]
+            {
+                div
+                    ."flex"
+                    ."flex-row"
+                    ."justify-between"
+                    ."items-center"
+                    ."bg-gray-100"
+                    ."border-b-2"
+                    ."border-gray-300"
+                    ."p-3"
+                {
+                    @match container {
+                        Some(container) => {
+                            h3 { (container.name) }
+                            (super::super::containers::view::ContainerWeight::build(container))
+                        },
+                        None => {
+                            h3 { "Not in a container" }
+                            span { (items.iter().map(|item| item.weight()).sum::<i32>()) " g" }
+                        },
+                    }
+                }
+                @if items.is_empty() {
+                    div
+                        ."flex"
+                        ."p-1"
+                    {
+                        span
+                            ."text-sm"
+                            ."m-auto"
+                        {
+                            "no items"
+                        }
+                    }
+                } @else {
+                    ul
+                        ."flex"
+                        ."flex-col"
+                    {
+                        @for item in items {
+                            (TripPackageListRowReady::build(trip.id, item))
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
+
 pub struct TripPackageList;
 
 impl TripPackageList {
     #[tracing::instrument]
-    pub fn build(trip: &super::super::model::Trip) -> Markup {
+    pub fn build(
+        trip: &super::super::model::Trip,
+        grouping: super::super::packagelist::Grouping,
+    ) -> Markup {
         // let all_packed = trip.categories().iter().all(|category| {
         //     category
         //         .items
@@ -316,6 +473,41 @@ impl TripPackageList {
                             (trip.name)
                         }
                     }
+                    div
+                        ."flex"
+                        ."flex-row"
+                        ."gap-2"
+                    {
+                        a
+                            href={"/trips/" (trip.id) "/packagelist/print"}
+                            target="_blank"
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                        {
+                            span ."mdi" ."mdi-printer" {}
+                            "Print"
+                        }
+                        a
+                            href={"/trips/" (trip.id) "/packagelist/pdf"}
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."gap-1"
+                        {
+                            span ."mdi" ."mdi-file-pdf-box" {}
+                            "PDF"
+                        }
+                    }
                     a
                         href={"/trips/" (trip.id) "/packagelist/"}
                         // disabled[!all_packed]
@@ -348,13 +540,178 @@ impl TripPackageList {
                         }
                     }
                 }
-                p { "Pack the following things:" }
+                div
+                    ."flex"
+                    ."flex-row"
+                    ."justify-between"
+                    ."items-center"
+                {
+                    p { "Pack the following things:" }
+                    @if !trip.containers().is_empty() {
+                        div
+                            ."flex"
+                            ."flex-row"
+                            ."gap-2"
+                        {
+                            span { "Group by" }
+                            @for (group, name, label) in [
+                                (super::super::packagelist::Grouping::Category, "category", "Category"),
+                                (super::super::packagelist::Grouping::Container, "container", "Container"),
+                            ] {
+                                a
+                                    href={"/trips/" (trip.id) "/packagelist/?group=" (name)}
+                                    ."font-bold"[grouping == group]
+                                    ."underline"[grouping != group]
+                                {
+                                    (label)
+                                }
+                            }
+                        }
+                    }
+                }
                 div
                     ."columns-3"
                     ."gap-5"
                 {
-                    @for category in trip.categories() {
-                        (TripPackageListCategoryBlockReady::build(trip, category))
+                    @match grouping {
+                        super::super::packagelist::Grouping::Category => {
+                            @for category in trip.categories() {
+                                (TripPackageListCategoryBlockReady::build(trip, category))
+                            }
+                        },
+                        super::super::packagelist::Grouping::Container => {
+                            @for container in trip.containers() {
+                                (TripPackageListContainerBlock::build(trip, Some(container)))
+                            }
+                            (TripPackageListContainerBlock::build(trip, None))
+                        },
+                    }
+                }
+            }
+        )
+    }
+}
+
+/// A standalone, print-optimized version of the package list. It does not use the
+/// regular page layout, so there is no navigation to hide when printing.
+pub struct TripPackageListPrint;
+
+impl TripPackageListPrint {
+    #[tracing::instrument]
+    pub fn build(trip: &super::super::model::Trip) -> Markup {
+        html!(
+            (DOCTYPE)
+            html {
+                (crate::view::root::Header::build())
+                body ."p-8" ."print:p-0" ."text-sm" {
+                    div
+                        ."flex"
+                        ."flex-row"
+                        ."justify-end"
+                        ."gap-2"
+                        ."mb-4"
+                        ."print:hidden"
+                    {
+                        a
+                            href={"/trips/" (trip.id) "/packagelist/pdf"}
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."hover:bg-gray-200"
+                        {
+                            "Download PDF"
+                        }
+                        button
+                            type="button"
+                            onclick="window.print()"
+                            ."p-2"
+                            ."border-2"
+                            ."border-gray-500"
+                            ."bg-blue-200"
+                            ."hover:bg-blue-300"
+                        {
+                            "Print"
+                        }
+                    }
+                    header
+                        ."mb-6"
+                        ."pb-2"
+                        ."border-b-2"
+                        ."border-black"
+                    {
+                        h1 ."text-2xl" ."font-bold" { "Package list: " (trip.name) }
+                        p {
+                            (trip.date)
+                            @if let Some(ref location) = trip.location {
+                                " · " (location)
+                            }
+                            @match (trip.temp_min, trip.temp_max) {
+                                (Some(min), Some(max)) => { " · " (min) " – " (max) " °C" },
+                                (Some(min), None) => { " · ≥ " (min) " °C" },
+                                (None, Some(max)) => { " · ≤ " (max) " °C" },
+                                (None, None) => {},
+                            }
+                        }
+                    }
+                    div ."columns-2" ."gap-8" {
+                        @for category in trip.categories() {
+                            @let items = category
+                                .items
+                                .as_ref()
+                                .unwrap()
+                                .iter()
+                                .filter(|item| item.picked)
+                                .collect::<Vec<_>>();
+                            @if !items.is_empty() {
+                                section ."break-inside-avoid" ."mb-4" {
+                                    h2
+                                        ."flex"
+                                        ."flex-row"
+                                        ."justify-between"
+                                        ."font-bold"
+                                        ."border-b"
+                                        ."border-gray-400"
+                                        ."mb-1"
+                                    {
+                                        span { (category.category.name) }
+                                        span { (category.total_picked_weight()) " g" }
+                                    }
+                                    ul {
+                                        @for item in items {
+                                            li
+                                                ."flex"
+                                                ."flex-row"
+                                                ."items-center"
+                                                ."gap-2"
+                                                ."py-0.5"
+                                            {
+                                                span
+                                                    ."inline-block"
+                                                    ."shrink-0"
+                                                    ."w-4"
+                                                    ."h-4"
+                                                    ."border"
+                                                    ."border-black"
+                                                {}
+                                                span ."grow" {
+                                                    @if item.quantity > 1 {
+                                                        (item.quantity) " × "
+                                                    }
+                                                    (item.item.name)
+                                                    @if let Some(ref note) = item.note {
+                                                        span ."text-sm" ."text-gray-600" { " – " (note) }
+                                                    }
+                                                }
+                                                span ."text-gray-600" { (item.weight()) " g" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    p ."mt-6" ."font-bold" ."text-right" {
+                        "Total: " (trip.total_picked_weight()) " g"
                     }
                 }
             }
@@ -0,0 +1,349 @@
+//! PDF rendering of the package list.
+//!
+//! The PDF is written directly with `pdf-writer`, using the standard Helvetica
+//! fonts that every PDF viewer ships with. This way, no fonts need to be
+//! embedded and no browser is required to render the document.
+
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use super::super::model::Trip;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+const FONT_REGULAR: Name<'static> = Name(b"F1");
+const FONT_BOLD: Name<'static> = Name(b"F2");
+
+/// Width of a digit in Helvetica, in units of the font size. All digits have
+/// the same width, which makes it possible to right-align numbers without
+/// embedding font metrics.
+const DIGIT_WIDTH: f32 = 0.556;
+const SPACE_WIDTH: f32 = 0.278;
+const G_WIDTH: f32 = 0.556;
+
+/// Rough upper bound of characters of an item name that fit into one line
+const MAX_NAME_LENGTH: usize = 70;
+
+const TITLE_SIZE: f32 = 18.0;
+const SUBTITLE_SIZE: f32 = 10.0;
+const CATEGORY_SIZE: f32 = 12.0;
+const ITEM_SIZE: f32 = 10.0;
+const NOTE_SIZE: f32 = 8.0;
+const LINE_HEIGHT: f32 = 16.0;
+const NOTE_LINE_HEIGHT: f32 = 10.0;
+const CHECKBOX_SIZE: f32 = 9.0;
+
+/// Encodes text for the `WinAnsiEncoding` of the standard fonts. Characters that
+/// cannot be represented are replaced.
+fn encode(text: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '€' => encoded.push(0x80),
+            '…' => encoded.push(0x85),
+            '‘' => encoded.push(0x91),
+            '’' => encoded.push(0x92),
+            '“' => encoded.push(0x93),
+            '”' => encoded.push(0x94),
+            '•' => encoded.push(0x95),
+            '–' => encoded.push(0x96),
+            '—' => encoded.push(0x97),
+            '→' => encoded.extend_from_slice(b"->"),
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => {
+                encoded.push(u8::try_from(c).unwrap_or(b'?'));
+            }
+            _ => encoded.push(b'?'),
+        }
+    }
+    encoded
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() > MAX_NAME_LENGTH {
+        let mut truncated = text.chars().take(MAX_NAME_LENGTH - 1).collect::<String>();
+        truncated.push('…');
+        truncated
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Width of a weight as printed by [`Page::weight`]
+fn weight_width(weight: i32, size: f32) -> f32 {
+    let digits = weight.to_string().len();
+    #[expect(clippy::cast_precision_loss, reason = "weights have few digits")]
+    let digits = digits as f32;
+    digits.mul_add(DIGIT_WIDTH, SPACE_WIDTH + G_WIDTH) * size
+}
+
+struct Page {
+    content: Content,
+    y: f32,
+}
+
+impl Page {
+    fn new() -> Self {
+        Self {
+            content: Content::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn fits(&self, height: f32) -> bool {
+        self.y - height >= MARGIN
+    }
+
+    fn text(&mut self, font: Name, size: f32, x: f32, text: &str) {
+        self.content
+            .begin_text()
+            .set_font(font, size)
+            .next_line(x, self.y)
+            .show(Str(&encode(text)))
+            .end_text();
+    }
+
+    /// Prints a weight right-aligned at the right margin
+    fn weight(&mut self, font: Name, size: f32, weight: i32) {
+        let x = PAGE_WIDTH - MARGIN - weight_width(weight, size);
+        self.text(font, size, x, &format!("{weight} g"));
+    }
+
+    fn rule(&mut self, y: f32, width: f32) {
+        self.content
+            .set_line_width(width)
+            .move_to(MARGIN, y)
+            .line_to(PAGE_WIDTH - MARGIN, y)
+            .stroke();
+    }
+
+    fn checkbox(&mut self) {
+        self.content
+            .set_line_width(0.8)
+            .rect(MARGIN, self.y - 1.0, CHECKBOX_SIZE, CHECKBOX_SIZE)
+            .stroke();
+    }
+}
+
+pub struct TripPackageListPdf;
+
+impl TripPackageListPdf {
+    #[tracing::instrument]
+    pub fn build(trip: &Trip) -> Vec<u8> {
+        let mut pages = vec![];
+        let mut page = Page::new();
+
+        page.y -= TITLE_SIZE;
+        page.text(
+            FONT_BOLD,
+            TITLE_SIZE,
+            MARGIN,
+            &format!("Package list: {}", trip.name),
+        );
+
+        let temperature = match (trip.temp_min, trip.temp_max) {
+            (Some(min), Some(max)) => Some(format!("{min} – {max} °C")),
+            (Some(min), None) => Some(format!("≥ {min} °C")),
+            (None, Some(max)) => Some(format!("≤ {max} °C")),
+            (None, None) => None,
+        };
+        let subtitle = [
+            Some(trip.date.to_string()),
+            trip.location.clone(),
+            temperature,
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+        page.y -= LINE_HEIGHT;
+        page.text(FONT_REGULAR, SUBTITLE_SIZE, MARGIN, &subtitle);
+        page.y -= 8.0;
+        page.rule(page.y, 1.5);
+        page.y -= 8.0;
+
+        for category in trip.categories() {
+            let items = category
+                .items
+                .as_ref()
+                .unwrap()
+                .iter()
+                .filter(|item| item.picked)
+                .collect::<Vec<_>>();
+
+            if items.is_empty() {
+                continue;
+            }
+
+            // keep the category header together with at least its first item
+            if !page.fits(2.0f32.mul_add(LINE_HEIGHT, 12.0)) {
+                pages.push(std::mem::replace(&mut page, Page::new()));
+            }
+
+            page.y -= LINE_HEIGHT + 6.0;
+            page.text(FONT_BOLD, CATEGORY_SIZE, MARGIN, &category.category.name);
+            page.weight(FONT_BOLD, CATEGORY_SIZE, category.total_picked_weight());
+            page.rule(page.y - 4.0, 0.5);
+            page.y -= 4.0;
+
+            for item in items {
+                let height = if item.note.is_some() {
+                    LINE_HEIGHT + NOTE_LINE_HEIGHT
+                } else {
+                    LINE_HEIGHT
+                };
+                if !page.fits(height) {
+                    pages.push(std::mem::replace(&mut page, Page::new()));
+                }
+
+                let name = if item.quantity > 1 {
+                    format!("{} × {}", item.quantity, item.item.name)
+                } else {
+                    item.item.name.clone()
+                };
+
+                page.y -= LINE_HEIGHT;
+                page.checkbox();
+                page.text(
+                    FONT_REGULAR,
+                    ITEM_SIZE,
+                    MARGIN + CHECKBOX_SIZE + 7.0,
+                    &truncate(&name),
+                );
+                page.weight(FONT_REGULAR, ITEM_SIZE, item.weight());
+
+                if let Some(ref note) = item.note {
+                    page.y -= NOTE_LINE_HEIGHT;
+                    page.content.set_fill_gray(0.4);
+                    page.text(
+                        FONT_REGULAR,
+                        NOTE_SIZE,
+                        MARGIN + CHECKBOX_SIZE + 7.0,
+                        &truncate(note),
+                    );
+                    page.content.set_fill_gray(0.0);
+                }
+            }
+        }
+
+        if !page.fits(2.0 * LINE_HEIGHT) {
+            pages.push(std::mem::replace(&mut page, Page::new()));
+        }
+        page.y -= LINE_HEIGHT;
+        page.rule(page.y, 1.5);
+        page.y -= LINE_HEIGHT;
+        page.text(FONT_BOLD, CATEGORY_SIZE, MARGIN, "Total");
+        page.weight(FONT_BOLD, CATEGORY_SIZE, trip.total_picked_weight());
+        pages.push(page);
+
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let font_regular_id = Ref::new(3);
+        let font_bold_id = Ref::new(4);
+        let info_id = Ref::new(5);
+        let mut next_id = Ref::new(6);
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.document_info(info_id)
+            .title(TextStr(&format!("Package list: {}", trip.name)))
+            .producer(TextStr("Packager"));
+        pdf.type1_font(font_regular_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(font_bold_id)
+            .base_font(Name(b"Helvetica-Bold"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+        let mut page_ids = vec![];
+        let page_count = pages.len();
+        for (index, mut page) in pages.into_iter().enumerate() {
+            let page_id = next_id.bump();
+            let content_id = next_id.bump();
+            page_ids.push(page_id);
+
+            page.y = MARGIN / 2.0;
+            page.text(
+                FONT_REGULAR,
+                8.0,
+                MARGIN,
+                &format!("{} – page {} of {page_count}", trip.name, index + 1),
+            );
+
+            let mut pdf_page = pdf.page(page_id);
+            pdf_page
+                .parent(page_tree_id)
+                .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+                .contents(content_id);
+            pdf_page
+                .resources()
+                .fonts()
+                .pair(FONT_REGULAR, font_regular_id)
+                .pair(FONT_BOLD, font_bold_id);
+            pdf_page.finish();
+
+            pdf.stream(content_id, &page.content.finish());
+        }
+
+        pdf.pages(page_tree_id)
+            .count(i32::try_from(page_ids.len()).unwrap_or(i32::MAX))
+            .kids(page_ids);
+
+        pdf.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_ascii_and_latin1() {
+        assert_eq!(encode("Tent 2p"), b"Tent 2p");
+        assert_eq!(
+            encode("Müsli, Crème brûlée"),
+            b"M\xfcsli, Cr\xe8me br\xfbl\xe9e"
+        );
+        assert_eq!(encode("\u{a0}ÿ"), [0xa0, 0xff]);
+    }
+
+    #[test]
+    fn encode_winansi_punctuation() {
+        assert_eq!(encode("5 €"), b"5 \x80");
+        assert_eq!(
+            encode("“Gas” – ‘stove’ — …•"),
+            b"\x93Gas\x94 \x96 \x91stove\x92 \x97 \x85\x95"
+        );
+        assert_eq!(encode("Hut → Summit"), b"Hut -> Summit");
+    }
+
+    #[test]
+    fn encode_replaces_everything_else() {
+        // neither Latin-1 nor part of WinAnsi, one replacement per character
+        assert_eq!(encode("Łódź"), b"?\xf3d?");
+        assert_eq!(encode("寝袋"), b"??");
+        assert_eq!(encode("🏕"), b"?");
+        // control characters would break the text operators
+        assert_eq!(encode("a\tb\n"), b"a?b?");
+    }
+
+    #[test]
+    fn truncate_keeps_short_names() {
+        let name = "x".repeat(MAX_NAME_LENGTH);
+        assert_eq!(truncate(&name), name);
+        assert_eq!(truncate(""), "");
+    }
+
+    #[test]
+    fn truncate_long_names_by_characters() {
+        let name = "ü".repeat(MAX_NAME_LENGTH + 1);
+        let truncated = truncate(&name);
+
+        assert_eq!(truncated.chars().count(), MAX_NAME_LENGTH);
+        assert!(truncated.ends_with('…'));
+        assert!(truncated.starts_with(&"ü".repeat(MAX_NAME_LENGTH - 1)));
+        // the ellipsis is part of WinAnsi and survives the encoding
+        assert_eq!(encode(&truncated).last(), Some(&0x85));
+    }
+}
@@ -5,7 +5,7 @@ pub struct TypeList;
 
 impl TypeList {
     #[tracing::instrument]
-    pub fn build(state: &ClientState, trip_types: Vec<super::super::model::TripsType>) -> Markup {
+    pub fn build(state: &ClientState, trip_types: &[super::super::model::TripsType]) -> Markup {
         html!(
             div ."p-8" ."flex" ."flex-col" ."gap-8" {
                 h1 ."text-2xl" {"Trip Types"}
@@ -83,10 +83,16 @@ impl TypeList {
                                     }
                                 }
                             } @else {
-                                span
+                                div
                                     ."p-2"
+                                    ."grow"
+                                    ."flex"
+                                    ."flex-col"
+                                    ."gap-2"
                                 {
-                                    (trip_type.name)
+                                    span { (trip_type.name) }
+                                    (DefaultTodos::build(trip_type))
+                                    (MergeType::build(trip_type, trip_types))
                                 }
 
                                 div
@@ -109,6 +115,26 @@ impl TypeList {
                                         {}
                                     }
                                 }
+                                form
+                                    action={ (trip_type.id) "/delete" }
+                                    method="post"
+                                    onsubmit="return confirm('Delete this trip type? It will be removed from all trips.')"
+                                    ."flex"
+                                {
+                                    button
+                                        type="submit"
+                                        title="Delete trip type"
+                                        ."bg-red-200"
+                                        ."hover:bg-red-300"
+                                        ."w-8"
+                                    {
+                                        span
+                                            ."mdi"
+                                            ."mdi-delete"
+                                            ."text-xl"
+                                        {}
+                                    }
+                                }
                             }
                         }
                     }
@@ -160,3 +186,124 @@ impl TypeList {
         )
     }
 }
+
+pub struct MergeType;
+
+impl MergeType {
+    #[tracing::instrument]
+    pub fn build(
+        trip_type: &super::super::model::TripsType,
+        trip_types: &[super::super::model::TripsType],
+    ) -> Markup {
+        html!(
+            @if trip_types.len() > 1 {
+                form
+                    action={ (trip_type.id) "/merge" }
+                    method="post"
+                    onsubmit="return confirm('Merge this trip type? It will be replaced by the selected one on all trips.')"
+                    ."flex"
+                    ."flex-row"
+                    ."items-center"
+                    ."gap-2"
+                    ."pl-4"
+                    ."text-sm"
+                {
+                    label for={ "merge-into-" (trip_type.id) } { "Merge into" }
+                    select
+                        id={ "merge-into-" (trip_type.id) }
+                        name="merge-into-id"
+                        ."bg-blue-100"
+                        ."hover:bg-white"
+                        ."p-1"
+                    {
+                        @for other in trip_types.iter().filter(|other| other.id != trip_type.id) {
+                            option value=(other.id) { (other.name) }
+                        }
+                    }
+                    button
+                        type="submit"
+                        title="Merge trip type"
+                        ."px-2"
+                        ."bg-blue-200"
+                        ."hover:bg-blue-300"
+                    {
+                        span ."mdi" ."mdi-call-merge" {}
+                    }
+                }
+            }
+        )
+    }
+}
+
+pub struct DefaultTodos;
+
+impl DefaultTodos {
+    #[tracing::instrument]
+    pub fn build(trip_type: &super::super::model::TripsType) -> Markup {
+        html!(
+            ul ."flex" ."flex-col" ."gap-1" ."pl-4" ."text-sm" {
+                @for todo in trip_type.todos() {
+                    li ."flex" ."flex-row" ."items-center" ."gap-2" {
+                        span ."mdi" ."mdi-checkbox-blank-outline" {}
+                        span { (todo.description) }
+                        @if let Some(due) = todo.due {
+                            span ."text-gray-500" { "(" (due) ")" }
+                        }
+                        form
+                            action={ (trip_type.id) "/todos/" (todo.id) "/delete" }
+                            method="post"
+                        {
+                            button
+                                type="submit"
+                                title="Remove default todo"
+                                ."text-gray-500"
+                                ."hover:text-red-600"
+                            {
+                                span ."mdi" ."mdi-delete-outline" {}
+                            }
+                        }
+                    }
+                }
+                li {
+                    form
+                        action={ (trip_type.id) "/todos/new" }
+                        method="post"
+                        ."flex"
+                        ."flex-row"
+                        ."items-center"
+                        ."gap-2"
+                    {
+                        input
+                            type="text"
+                            name="new-type-todo-description"
+                            placeholder="Default todo"
+                            required
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                            ."p-1"
+                        {}
+                        input
+                            type="number"
+                            name="new-type-todo-days-before"
+                            placeholder="days before"
+                            title="Due days before the trip starts"
+                            ."bg-blue-100"
+                            ."hover:bg-white"
+                            ."p-1"
+                            ."w-28"
+                        {}
+                        button
+                            type="submit"
+                            title="Add default todo"
+                            ."px-2"
+                            ."bg-green-200"
+                            ."hover:bg-green-300"
+                        {
+                            span ."mdi" ."mdi-plus" {}
+                        }
+                    }
+                }
+            }
+        )
+    }
+}
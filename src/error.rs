@@ -1,4 +1,8 @@
-use std::{convert::Infallible, fmt, net::SocketAddr};
+use std::{
+    convert::Infallible,
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
 
 use crate::view;
 
@@ -12,9 +16,13 @@ pub use database::{Error as DatabaseError, QueryError};
 #[derive(Debug)]
 pub enum RequestError {
     EmptyFormElement { name: String },
+    InvalidFormElement { name: String, message: String },
+    InvalidQueryParameter { name: String, message: String },
     RefererNotFound,
     RefererInvalid { message: String },
     NotFound { message: String },
+    TransitionBlocked { message: String },
+    InvalidState { message: String },
     Auth { inner: AuthError },
     Transport { inner: hyper::Error },
 }
@@ -25,9 +33,17 @@ impl fmt::Display for RequestError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::EmptyFormElement { name } => write!(f, "Form element {name} cannot be empty"),
+            Self::InvalidFormElement { name, message } => {
+                write!(f, "Form element {name} is invalid: {message}")
+            }
+            Self::InvalidQueryParameter { name, message } => {
+                write!(f, "Query parameter {name} is invalid: {message}")
+            }
             Self::RefererNotFound => write!(f, "Referer header not found"),
             Self::RefererInvalid { message } => write!(f, "Referer header invalid: {message}"),
             Self::NotFound { message } => write!(f, "Not found: {message}"),
+            Self::TransitionBlocked { message } => write!(f, "Transition blocked: {message}"),
+            Self::InvalidState { message } => write!(f, "Invalid state: {message}"),
             Self::Auth { inner } => {
                 write!(f, "Authentication failed: {inner}")
             }
@@ -60,6 +76,13 @@ pub enum AuthError {
     AuthenticationUserNotFound { username: String },
     AuthenticationHeaderMissing,
     AuthenticationHeaderInvalid { message: String },
+    SessionMissing,
+    SessionInvalid,
+    InvalidCredentials { username: String },
+    UntrustedSource { source: Option<IpAddr> },
+    ProvisioningHeaderInvalid { header: String, message: String },
+    TokenInvalid,
+    TokenScopeInsufficient { username: String },
 }
 
 impl AuthError {
@@ -69,6 +92,16 @@ impl AuthError {
             Self::AuthenticationUserNotFound { username: _ } => "user_not_found",
             Self::AuthenticationHeaderMissing => "header_missing",
             Self::AuthenticationHeaderInvalid { message: _ } => "header_invalid",
+            Self::SessionMissing => "session_missing",
+            Self::SessionInvalid => "session_invalid",
+            Self::InvalidCredentials { username: _ } => "invalid_credentials",
+            Self::UntrustedSource { source: _ } => "untrusted_source",
+            Self::ProvisioningHeaderInvalid {
+                header: _,
+                message: _,
+            } => "provisioning_header_invalid",
+            Self::TokenInvalid => "token_invalid",
+            Self::TokenScopeInsufficient { username: _ } => "token_scope_insufficient",
         }
     }
 
@@ -83,6 +116,27 @@ impl AuthError {
             Self::AuthenticationHeaderInvalid { message } => {
                 tracing::info!(message, "auth failed, auth header invalid");
             }
+            Self::SessionMissing => {
+                tracing::info!("auth failed, no session");
+            }
+            Self::SessionInvalid => {
+                tracing::info!("auth failed, session invalid or expired");
+            }
+            Self::InvalidCredentials { username } => {
+                tracing::info!(username, "auth failed, invalid credentials");
+            }
+            Self::UntrustedSource { source } => {
+                tracing::warn!(?source, "auth failed, auth header sent by untrusted source");
+            }
+            Self::ProvisioningHeaderInvalid { header, message } => {
+                tracing::info!(header, message, "auth failed, provisioning header invalid");
+            }
+            Self::TokenInvalid => {
+                tracing::info!("auth failed, api token invalid or expired");
+            }
+            Self::TokenScopeInsufficient { username } => {
+                tracing::info!(username, "auth failed, api token is read-only");
+            }
         }
     }
 }
@@ -91,9 +145,21 @@ impl<'a> AuthError {
     #[must_use]
     pub fn to_prom_labels(&'a self) -> Vec<(&'static str, String)> {
         match self {
-            Self::AuthenticationUserNotFound { username } => vec![("username", username.clone())],
-            Self::AuthenticationHeaderMissing
-            | Self::AuthenticationHeaderInvalid { message: _ } => vec![],
+            Self::AuthenticationUserNotFound { username }
+            | Self::TokenScopeInsufficient { username } => vec![("username", username.clone())],
+            Self::ProvisioningHeaderInvalid { header, message: _ } => {
+                vec![("header", header.clone())]
+            }
+            // the username comes straight from the login form, anyone could
+            // create any number of series with it
+            Self::InvalidCredentials { username: _ }
+            // the address is logged instead, as it is different for every client
+            | Self::UntrustedSource { source: _ }
+            | Self::AuthenticationHeaderMissing
+            | Self::AuthenticationHeaderInvalid { message: _ }
+            | Self::SessionMissing
+            | Self::SessionInvalid
+            | Self::TokenInvalid => vec![],
         }
     }
 }
@@ -108,6 +174,20 @@ impl fmt::Display for AuthError {
             Self::AuthenticationHeaderInvalid { message } => {
                 write!(f, "Authentication header invalid: {message}")
             }
+            Self::SessionMissing => write!(f, "Not logged in"),
+            Self::SessionInvalid => write!(f, "Session invalid or expired"),
+            Self::InvalidCredentials { username: _ } => write!(f, "Invalid username or password"),
+            Self::UntrustedSource { source } => match source {
+                Some(source) => write!(f, "Authentication header not accepted from {source}"),
+                None => write!(f, "Authentication header not accepted from unknown source"),
+            },
+            Self::ProvisioningHeaderInvalid { header, message } => {
+                write!(f, "Header \"{header}\" invalid: {message}")
+            }
+            Self::TokenInvalid => write!(f, "API token invalid or expired"),
+            Self::TokenScopeInsufficient { username: _ } => {
+                write!(f, "API token does not allow changes")
+            }
         }
     }
 }
@@ -194,12 +274,32 @@ impl IntoResponse for RunError {
                     StatusCode::UNPROCESSABLE_ENTITY,
                     view::ErrorPage::build(&format!("empty form element: {name}")),
                 ),
+                RequestError::InvalidFormElement { name, message } => (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    view::ErrorPage::build(&format!("invalid form element {name}: {message}")),
+                ),
+                RequestError::InvalidQueryParameter { name, message } => (
+                    StatusCode::BAD_REQUEST,
+                    view::ErrorPage::build(&format!("invalid query parameter {name}: {message}")),
+                ),
                 RequestError::NotFound { message } => (
                     StatusCode::NOT_FOUND,
                     view::ErrorPage::build(&format!("not found: {message}")),
                 ),
+                RequestError::TransitionBlocked { message } => (
+                    StatusCode::CONFLICT,
+                    view::ErrorPage::build(&format!("transition blocked: {message}")),
+                ),
+                RequestError::InvalidState { message } => (
+                    StatusCode::CONFLICT,
+                    view::ErrorPage::build(&format!("invalid state: {message}")),
+                ),
                 RequestError::Auth { inner: e } => (
-                    StatusCode::UNAUTHORIZED,
+                    match e {
+                        // the token is valid, it just may not do this
+                        AuthError::TokenScopeInsufficient { username: _ } => StatusCode::FORBIDDEN,
+                        _ => StatusCode::UNAUTHORIZED,
+                    },
                     view::ErrorPage::build(&format!("authentication failed: {e}")),
                 ),
                 RequestError::Transport { inner } => (
@@ -271,6 +371,11 @@ pub enum CommandError {
     Start(StartError),
     Database(database::Error),
     UserExists { username: String },
+    UserNotFound { username: String },
+    Password { message: String },
+    TripNotFound { id: uuid::Uuid },
+    Output { path: std::path::PathBuf, message: String },
+    Run(RunError),
 }
 
 impl std::error::Error for CommandError {}
@@ -285,10 +390,29 @@ impl fmt::Display for CommandError {
             Self::UserExists { username } => {
                 write!(f, "user \"{username}\" already exists")
             }
+            Self::UserNotFound { username } => {
+                write!(f, "user \"{username}\" not found")
+            }
+            Self::Password { message } => {
+                write!(f, "setting the password failed: {message}")
+            }
+            Self::TripNotFound { id } => {
+                write!(f, "trip with id {id} not found")
+            }
+            Self::Output { path, message } => {
+                write!(f, "writing to {} failed: {message}", path.display())
+            }
+            Self::Run(run_error) => write!(f, "{run_error}"),
         }
     }
 }
 
+impl From<RunError> for CommandError {
+    fn from(value: RunError) -> Self {
+        Self::Run(value)
+    }
+}
+
 impl From<tokio::task::JoinError> for CommandError {
     fn from(value: tokio::task::JoinError) -> Self {
         Self::Start(value.into())
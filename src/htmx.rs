@@ -2,6 +2,7 @@ use axum::http::header::{HeaderMap, HeaderName, HeaderValue};
 
 pub enum Event {
     TripItemEdited,
+    TripStateChanged,
 }
 
 impl From<Event> for HeaderValue {
@@ -15,6 +16,7 @@ impl Event {
     pub fn to_str(&self) -> &'static str {
         match self {
             Self::TripItemEdited => "TripItemEdited",
+            Self::TripStateChanged => "TripStateChanged",
         }
     }
 }
@@ -22,6 +24,7 @@ impl Event {
 pub enum ResponseHeaders {
     Trigger,
     PushUrl,
+    Refresh,
 }
 
 impl From<ResponseHeaders> for HeaderName {
@@ -29,18 +32,21 @@ impl From<ResponseHeaders> for HeaderName {
         match val {
             ResponseHeaders::Trigger => Self::from_static("hx-trigger"),
             ResponseHeaders::PushUrl => Self::from_static("hx-push-url"),
+            ResponseHeaders::Refresh => Self::from_static("hx-refresh"),
         }
     }
 }
 
 pub enum RequestHeaders {
     HtmxRequest,
+    Target,
 }
 
 impl From<RequestHeaders> for HeaderName {
     fn from(val: RequestHeaders) -> Self {
         match val {
             RequestHeaders::HtmxRequest => Self::from_static("hx-request"),
+            RequestHeaders::Target => Self::from_static("hx-target"),
         }
     }
 }
@@ -51,3 +57,11 @@ pub fn is_htmx(headers: &HeaderMap) -> bool {
         .get::<HeaderName>(RequestHeaders::HtmxRequest.into())
         .is_some_and(|value| value == "true")
 }
+
+/// The id of the element that htmx swaps the response into, if any
+#[tracing::instrument]
+pub fn target(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get::<HeaderName>(RequestHeaders::Target.into())
+        .and_then(|value| value.to_str().ok())
+}
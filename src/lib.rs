@@ -21,6 +21,7 @@ pub struct AppState {
     pub database_pool: database::Pool,
     pub client_state: ClientState,
     pub auth_config: auth::Config,
+    pub transition_rules: domains::trips::TransitionRules,
 }
 
 #[derive(Clone, Debug)]
@@ -2,11 +2,13 @@ use std::fmt;
 use std::net::{IpAddr, SocketAddr};
 use std::process::ExitCode;
 use std::str::FromStr;
+use std::time::Duration;
 
 use packager::{
     AppState, ClientState, CommandError, DatabaseError, QueryError, StartError, auth, cli, models,
     routing, telemetry,
 };
+use packager::domains::{self, trips::TransitionRules};
 use tokio::net::TcpListener;
 
 enum MainError {
@@ -105,16 +107,48 @@ async fn main() -> MainResult {
                             {
                                 auth::Config::Disabled { assume_user }
                             } else {
-                                auth::Config::Enabled
+                                match serve_args.auth {
+                                    cli::AuthArg::Header => {
+                                        auth::Config::Enabled(auth::HeaderConfig {
+                                            username_header: serve_args.auth_header,
+                                            trusted_proxies: serve_args.trusted_proxies,
+                                            provisioning: if serve_args.auth_provision_users.into()
+                                            {
+                                                Some(auth::Provisioning {
+                                                    fullname_header: serve_args
+                                                        .auth_fullname_header,
+                                                })
+                                            } else {
+                                                None
+                                            },
+                                        })
+                                    }
+                                    cli::AuthArg::Password => auth::Config::Password {
+                                        session_lifetime: Duration::from_secs(
+                                            serve_args.session_lifetime,
+                                        ),
+                                    },
+                                }
+                            },
+                            transition_rules: TransitionRules {
+                                unpacked_items: serve_args.guard_unpacked_items.into(),
+                                open_todos: serve_args.guard_open_todos.into(),
                             },
                         };
 
+                        let mut join_set = tokio::task::JoinSet::new();
+
+                        if serve_args.enable_auto_state_progression.into() {
+                            join_set.spawn(domains::trips::progress_trip_states_periodically(
+                                state.database_pool.clone(),
+                                Duration::from_secs(serve_args.auto_state_progression_interval),
+                            ));
+                        }
+
                         // build our application with a route
                         let app = routing::router(state);
                         let app = telemetry::tracing::init_request_tracing(app);
 
-                        let mut join_set = tokio::task::JoinSet::new();
-
                         let app = if args.enable_prometheus.into() {
                             // we `require_if()` prometheus port & bind when `enable_prometheus` is set, so
                             // this cannot fail
@@ -152,7 +186,8 @@ async fn main() -> MainResult {
                                         addr,
                                         message: e.to_string(),
                                     })?,
-                                app,
+                                // the peer address is needed to check for trusted proxies
+                                app.into_make_service_with_connect_info::<SocketAddr>(),
                             )
                             .await
                             // Error = Infallible
@@ -215,8 +250,111 @@ async fn main() -> MainResult {
                                     &user.username, id
                                 );
                             }
+                            cli::UserCommand::SetPassword(user) => {
+                                let database_pool = match database::DB::init_database_pool(
+                                    &args.database_url,
+                                )
+                                .await
+                                {
+                                    Ok(pool) => pool,
+                                    Err(e) => return <_ as Into<StartError>>::into(e).into(),
+                                };
+
+                                let mut password = String::new();
+                                if let Err(e) = std::io::stdin().read_line(&mut password) {
+                                    return CommandError::Password {
+                                        message: format!("reading from stdin failed: {e}"),
+                                    }
+                                    .into();
+                                }
+                                let password = password.trim_end_matches(['\r', '\n']);
+                                if password.is_empty() {
+                                    return CommandError::Password {
+                                        message: "the password must not be empty".to_string(),
+                                    }
+                                    .into();
+                                }
+
+                                let password_hash = match auth::hash_password(password) {
+                                    Ok(hash) => hash,
+                                    Err(e) => {
+                                        return CommandError::Password {
+                                            message: e.to_string(),
+                                        }
+                                        .into();
+                                    }
+                                };
+
+                                match models::user::set_password_hash(
+                                    &database_pool,
+                                    &user.username,
+                                    &password_hash,
+                                )
+                                .await
+                                {
+                                    Ok(true) => {}
+                                    Ok(false) => {
+                                        return CommandError::UserNotFound {
+                                            username: user.username,
+                                        }
+                                        .into();
+                                    }
+                                    Err(e) => return CommandError::Database(e).into(),
+                                }
+
+                                println!("Password for user \"{}\" set", &user.username);
+                            }
                         },
                     },
+                    cli::Command::Export(export) => {
+                        let database_pool =
+                            match database::DB::init_database_pool(&args.database_url).await {
+                                Ok(pool) => pool,
+                                Err(e) => return <_ as Into<StartError>>::into(e).into(),
+                            };
+
+                        let user = match models::User::find_by_name(&database_pool, &export.username)
+                            .await
+                        {
+                            Ok(Some(user)) => user,
+                            Ok(None) => {
+                                return CommandError::UserNotFound {
+                                    username: export.username,
+                                }
+                                .into();
+                            }
+                            Err(e) => return CommandError::from(e).into(),
+                        };
+
+                        match domains::trips::export(
+                            &database_pool,
+                            user,
+                            export.trip_id,
+                            export.format.into(),
+                        )
+                        .await
+                        {
+                            Ok(Some(output)) => match export.output {
+                                Some(path) => {
+                                    if let Err(e) = std::fs::write(&path, output) {
+                                        return CommandError::Output {
+                                            path,
+                                            message: e.to_string(),
+                                        }
+                                        .into();
+                                    }
+                                }
+                                None => print!("{output}"),
+                            },
+                            Ok(None) => {
+                                return CommandError::TripNotFound {
+                                    id: export.trip_id,
+                                }
+                                .into();
+                            }
+                            Err(e) => return CommandError::from(e).into(),
+                        }
+                    }
                     cli::Command::Migrate => {
                         if let Err(e) = database::DB::migrate(&args.database_url).await {
                             return <_ as Into<StartError>>::into(e).into();
@@ -0,0 +1,295 @@
+//! Personal API tokens for scripts and other clients that cannot go through
+//! the login of the browser. Like sessions, only the hash of a token is
+//! stored.
+
+use std::fmt;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore as _};
+use base64::Engine as _;
+use http::Method;
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+use uuid::Uuid;
+
+use super::user::{Authentication, User};
+use crate::{Context, RunError};
+
+/// Makes tokens recognizable, e.g. for secret scanners
+const PREFIX: &str = "packager_";
+
+const TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "api_token_scope")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Read,
+    ReadWrite,
+}
+
+impl Scope {
+    pub const ALL: [Self; 2] = [Self::Read, Self::ReadWrite];
+
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::ReadWrite => "readwrite",
+        }
+    }
+
+    /// Read-only tokens may only use requests that do not change anything
+    #[must_use]
+    pub fn allows(self, method: &Method) -> bool {
+        match self {
+            Self::Read => method.is_safe(),
+            Self::ReadWrite => true,
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Read => "Read-only",
+                Self::ReadWrite => "Read & write",
+            }
+        )
+    }
+}
+
+/// The secret a client sends as `Authorization: Bearer <secret>`
+pub struct Secret(String);
+
+impl Secret {
+    fn generate() -> Self {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Self(format!(
+            "{PREFIX}{}",
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+        ))
+    }
+
+    #[must_use]
+    pub fn from_header(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn hash(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(&self.0))
+    }
+}
+
+// never print the secret
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    pub scope: Scope,
+    pub created_at: time::OffsetDateTime,
+    pub last_used_at: Option<time::OffsetDateTime>,
+    pub expires_at: Option<time::OffsetDateTime>,
+}
+
+impl ApiToken {
+    #[must_use]
+    pub fn expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= time::OffsetDateTime::now_utc())
+    }
+}
+
+struct ApiTokenRow {
+    id: Uuid,
+    name: String,
+    scope: Scope,
+    created_at: time::OffsetDateTime,
+    last_used_at: Option<time::OffsetDateTime>,
+    expires_at: Option<time::OffsetDateTime>,
+}
+
+impl TryFrom<ApiTokenRow> for ApiToken {
+    type Error = RunError;
+
+    fn try_from(row: ApiTokenRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            scope: row.scope,
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+            expires_at: row.expires_at,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct NewApiToken<'a> {
+    pub name: &'a str,
+    pub scope: Scope,
+    pub expires_at: Option<time::OffsetDateTime>,
+}
+
+impl ApiToken {
+    #[tracing::instrument]
+    pub async fn findall(ctx: &Context, pool: &database::Pool) -> Result<Vec<Self>, RunError> {
+        database::query_all!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::User,
+            },
+            pool,
+            ApiTokenRow,
+            Self,
+            RunError,
+            r#"
+                SELECT
+                    token.id AS id,
+                    token.name AS name,
+                    token.scope AS "scope: _",
+                    token.created_at AS created_at,
+                    token.last_used_at AS last_used_at,
+                    token.expires_at AS expires_at
+                FROM api_tokens AS token
+                WHERE token.user_id = $1
+                ORDER BY token.created_at DESC, token.name ASC
+            "#,
+            ctx.user.id
+        )
+        .await
+    }
+
+    /// Returns the secret of the new token. It cannot be retrieved later.
+    #[tracing::instrument]
+    pub async fn create(
+        ctx: &Context,
+        pool: &database::Pool,
+        new_token: NewApiToken<'_>,
+    ) -> Result<Secret, RunError> {
+        let secret = Secret::generate();
+
+        database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Insert,
+                component: crate::Component::User,
+            },
+            pool,
+            RunError,
+            "INSERT INTO api_tokens
+                (id, user_id, name, token_hash, scope, expires_at)
+            VALUES
+                ($1, $2, $3, $4, $5, $6)",
+            Uuid::new_v4(),
+            ctx.user.id,
+            new_token.name,
+            secret.hash(),
+            new_token.scope as _,
+            new_token.expires_at
+        )
+        .await?;
+
+        Ok(secret)
+    }
+
+    #[tracing::instrument]
+    pub async fn revoke(ctx: &Context, pool: &database::Pool, id: Uuid) -> Result<bool, RunError> {
+        let result = database::execute!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Delete,
+                component: crate::Component::User,
+            },
+            pool,
+            RunError,
+            "DELETE FROM api_tokens WHERE id = $1 AND user_id = $2",
+            id,
+            ctx.user.id
+        )
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+}
+
+/// The owner of a valid token, together with what the token may do
+#[derive(Debug)]
+pub struct Authenticated {
+    pub user: User,
+    pub scope: Scope,
+}
+
+struct AuthenticatedRow {
+    id: Uuid,
+    username: String,
+    fullname: String,
+    scope: Scope,
+}
+
+impl TryFrom<AuthenticatedRow> for Authenticated {
+    type Error = RunError;
+
+    fn try_from(row: AuthenticatedRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user: User {
+                id: row.id,
+                username: row.username,
+                fullname: row.fullname,
+                authentication: Authentication::Token(row.scope),
+            },
+            scope: row.scope,
+        })
+    }
+}
+
+/// Looks up an unexpired token and records that it was used
+#[tracing::instrument]
+pub async fn authenticate(
+    pool: &database::Pool,
+    secret: &Secret,
+) -> Result<Option<Authenticated>, RunError> {
+    database::query_one!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Update,
+            component: crate::Component::User,
+        },
+        pool,
+        AuthenticatedRow,
+        Authenticated,
+        RunError,
+        r#"
+            WITH token AS (
+                UPDATE api_tokens
+                    SET last_used_at = now()
+                WHERE
+                    token_hash = $1
+                    AND (expires_at IS NULL OR expires_at > now())
+                RETURNING user_id, scope
+            )
+            SELECT
+                users.id AS id,
+                users.username AS username,
+                users.fullname AS fullname,
+                token.scope AS "scope: _"
+            FROM token
+            INNER JOIN users
+                ON users.id = token.user_id
+        "#,
+        secret.hash()
+    )
+    .await
+}
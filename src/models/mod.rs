@@ -1,3 +1,5 @@
+pub mod api_token;
+pub mod session;
 pub mod user;
 
 pub use user::User;
@@ -0,0 +1,107 @@
+//! Login sessions of the built-in password authentication. The browser gets a
+//! random token, the database only stores its hash so a leaked database does
+//! not leak valid sessions.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore as _};
+use base64::Engine as _;
+use sha2::{Digest as _, Sha256};
+use uuid::Uuid;
+
+use crate::RunError;
+
+/// Name of the cookie carrying the session token
+pub const COOKIE: &str = "packager_session";
+
+const TOKEN_BYTES: usize = 32;
+
+/// The secret the browser presents to resume the session
+pub struct Token(String);
+
+impl Token {
+    fn generate() -> Self {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Self(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    #[must_use]
+    pub fn from_cookie(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The id of the session in the database
+    #[must_use]
+    pub fn session_id(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(&self.0))
+    }
+}
+
+// never print the token
+impl std::fmt::Debug for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Token(..)")
+    }
+}
+
+/// Starts a new session for the user. Sessions that expired in the meantime
+/// are cleaned up on the way.
+#[tracing::instrument]
+pub async fn create(
+    pool: &database::Pool,
+    user_id: Uuid,
+    expires_at: time::OffsetDateTime,
+) -> Result<Token, RunError> {
+    database::execute!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Delete,
+            component: crate::Component::User,
+        },
+        pool,
+        RunError,
+        "DELETE FROM sessions WHERE expires_at <= now()",
+    )
+    .await?;
+
+    let token = Token::generate();
+
+    database::execute!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Insert,
+            component: crate::Component::User,
+        },
+        pool,
+        RunError,
+        "INSERT INTO sessions
+            (id, user_id, expires_at)
+        VALUES
+            ($1, $2, $3)",
+        token.session_id(),
+        user_id,
+        expires_at
+    )
+    .await?;
+
+    Ok(token)
+}
+
+#[tracing::instrument]
+pub async fn delete(pool: &database::Pool, token: &Token) -> Result<(), RunError> {
+    database::execute!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Delete,
+            component: crate::Component::User,
+        },
+        pool,
+        RunError,
+        "DELETE FROM sessions WHERE id = $1",
+        token.session_id()
+    )
+    .await?;
+
+    Ok(())
+}
@@ -1,12 +1,27 @@
+use std::fmt;
+
 use uuid::Uuid;
 
+use super::api_token::Scope;
 use crate::{DatabaseError, RunError};
 
+/// How the user of the current request authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authentication {
+    /// Not by us: by the reverse proxy, or not at all if authentication is
+    /// disabled
+    External,
+    /// With username and password. The user can log out again.
+    Session,
+    Token(Scope),
+}
+
 #[derive(Debug, Clone)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     pub fullname: String,
+    pub authentication: Authentication,
 }
 
 #[derive(Debug)]
@@ -30,6 +45,7 @@ impl TryFrom<DbUserRow> for User {
             id: row.id,
             username: row.username,
             fullname: row.fullname,
+            authentication: Authentication::External,
         })
     }
 }
@@ -51,6 +67,31 @@ impl User {
         )
         .await
     }
+
+    /// Finds the user of an unexpired session
+    #[tracing::instrument(skip(session_id))]
+    pub async fn find_by_session(
+        pool: &database::Pool,
+        session_id: &str,
+    ) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::User,
+            },
+            pool,
+            DbUserRow,
+            Self,
+            RunError,
+            "SELECT users.id,users.username,users.fullname
+            FROM sessions
+            INNER JOIN users
+                ON users.id = sessions.user_id
+            WHERE sessions.id = $1 AND sessions.expires_at > now()",
+            session_id
+        )
+        .await
+    }
 }
 
 #[tracing::instrument]
@@ -76,3 +117,102 @@ pub async fn create(pool: &database::Pool, user: NewUser<'_>) -> Result<Uuid, Da
 
     Ok(id)
 }
+
+/// A user together with the hash of their password, used by the built-in
+/// authentication
+pub struct Credentials {
+    pub user: User,
+    pub password_hash: Option<String>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("user", &self.user)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct DbCredentialsRow {
+    id: Uuid,
+    username: String,
+    fullname: String,
+    password_hash: Option<String>,
+}
+
+impl TryFrom<DbCredentialsRow> for Credentials {
+    type Error = RunError;
+
+    fn try_from(row: DbCredentialsRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user: User {
+                id: row.id,
+                username: row.username,
+                fullname: row.fullname,
+                authentication: Authentication::External,
+            },
+            password_hash: row.password_hash,
+        })
+    }
+}
+
+impl Credentials {
+    #[tracing::instrument]
+    pub async fn find(pool: &database::Pool, username: &str) -> Result<Option<Self>, RunError> {
+        database::query_one!(
+            &database::QueryClassification {
+                query_type: database::QueryType::Select,
+                component: crate::Component::User,
+            },
+            pool,
+            DbCredentialsRow,
+            Self,
+            RunError,
+            "SELECT id,username,fullname,password_hash FROM users WHERE username = $1",
+            username
+        )
+        .await
+    }
+}
+
+/// Sets the password and logs the user out everywhere, so sessions of
+/// whoever knew the old password do not survive the change.
+///
+/// Returns `false` if the user does not exist
+#[tracing::instrument(skip(password_hash))]
+pub async fn set_password_hash(
+    pool: &database::Pool,
+    username: &str,
+    password_hash: &str,
+) -> Result<bool, DatabaseError> {
+    let mut transaction = pool.begin().await?;
+
+    let result = database::execute!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Update,
+            component: crate::Component::User,
+        },
+        &mut *transaction,
+        DatabaseError,
+        "UPDATE users SET password_hash = $2 WHERE username = $1",
+        username,
+        password_hash
+    )
+    .await?;
+
+    database::execute!(
+        &database::QueryClassification {
+            query_type: database::QueryType::Delete,
+            component: crate::Component::User,
+        },
+        &mut *transaction,
+        DatabaseError,
+        "DELETE FROM sessions WHERE user_id = (SELECT id FROM users WHERE username = $1)",
+        username
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(result.rows_affected() != 0)
+}
@@ -61,6 +61,68 @@ where
     input.deserialize_str(NoneVisitor)
 }
 
+/// Like [`uuid_or_empty`], but for dates as sent by `<input type="date">`
+pub fn date_or_empty<'de, D>(input: D) -> Result<Option<time::Date>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct NoneVisitor;
+
+    impl de::Visitor<'_> for NoneVisitor {
+        type Value = Option<time::Date>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "invalid input")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            if value.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(serde::Deserialize::deserialize(
+                    de::value::StrDeserializer::<E>::new(value),
+                )?))
+            }
+        }
+    }
+
+    input.deserialize_str(NoneVisitor)
+}
+
+/// Like [`uuid_or_empty`], but for everything that can be parsed from a string
+pub fn parse_or_empty<'de, D, T>(input: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    struct NoneVisitor<T>(std::marker::PhantomData<T>);
+
+    impl<T> de::Visitor<'_> for NoneVisitor<T>
+    where
+        T: std::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        type Value = Option<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "invalid input")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            if value.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(value.parse().map_err(|e| {
+                    E::custom(format!("parsing failed: {e}"))
+                })?))
+            }
+        }
+    }
+
+    input.deserialize_str(NoneVisitor(std::marker::PhantomData))
+}
+
 #[tracing::instrument]
 pub fn router(state: AppState) -> Router {
     Router::new()
@@ -82,13 +144,16 @@ pub fn router(state: AppState) -> Router {
             }),
         )
         .route("/debug", get(debug))
+        .merge(auth::router())
         .merge(
             // these are routes that require authentication
             Router::new()
                 .route("/", get(root))
+                .merge(crate::domains::dashboard::router())
                 .merge(crate::domains::trips::router())
                 .merge(crate::domains::inventory::router())
                 .merge(crate::domains::products::router())
+                .merge(crate::domains::settings::router())
                 .layer(middleware::from_fn_with_state(
                     state.clone(),
                     auth::authorize,
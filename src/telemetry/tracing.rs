@@ -30,19 +30,19 @@ pub enum TokioConsoleConfig {
     Disabled,
 }
 
-fn get_stdout_layer<
+fn get_stderr_layer<
     T: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
 >() -> impl tracing_subscriber::Layer<T> {
     // default is the Full format, there is no way to specify this, but it can be
     // overridden via builder methods
-    let stdout_format = Format::default()
+    let stderr_format = Format::default()
         .pretty()
         .with_ansi(true)
         .with_target(true)
         .with_level(true)
         .with_file(false);
 
-    let stdout_filter = Targets::new()
+    let stderr_filter = Targets::new()
         .with_default(LevelFilter::WARN)
         .with_targets(vec![
             (env!("CARGO_PKG_NAME"), LevelFilter::DEBUG),
@@ -51,12 +51,12 @@ fn get_stdout_layer<
             ("tokio", LevelFilter::OFF),
         ]);
 
-    let stdout_layer = Layer::default()
-        .event_format(stdout_format)
-        .with_writer(io::stdout)
-        .with_filter(stdout_filter);
+    let stderr_layer = Layer::default()
+        .event_format(stderr_format)
+        .with_writer(io::stderr)
+        .with_filter(stderr_filter);
 
-    stdout_layer.boxed()
+    stderr_layer.boxed()
 }
 
 fn get_opentelemetry_layer<
@@ -150,7 +150,7 @@ where
         TokioConsoleConfig::Disabled => None,
     };
 
-    let stdout_layer = get_stdout_layer();
+    let stderr_layer = get_stderr_layer();
 
     let opentelemetry_layer =
         get_opentelemetry_layer(&opentelemetry_config, &mut shutdown_functions);
@@ -163,7 +163,7 @@ where
     // just an example, you can actuall pass Options here for layers that might be
     // set/unset at runtime
 
-    let registry = registry.with(stdout_layer).with(None::<Layer<_>>);
+    let registry = registry.with(stderr_layer).with(None::<Layer<_>>);
 
     tracing::subscriber::set_global_default(registry).unwrap();
 
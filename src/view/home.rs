@@ -1,5 +1,7 @@
 use maud::{html, Markup};
 
+use crate::domains::dashboard::{self, Widget};
+
 pub struct Home;
 
 impl Home {
@@ -21,6 +23,17 @@ impl Home {
                 {
                     "Welcome!"
                 }
+                div
+                    #dashboard
+                    ."grid"
+                    ."grid-cols-1"
+                    ."lg:grid-cols-2"
+                    ."gap-4"
+                {
+                    @for widget in Widget::ALL {
+                        (dashboard::view::Placeholder::build(widget))
+                    }
+                }
                 section
                     ."border-2"
                     ."border-gray-200"
@@ -0,0 +1,134 @@
+use maud::{DOCTYPE, Markup, html};
+
+use super::root::Header;
+
+fn page(content: &Markup) -> Markup {
+    html!(
+        (DOCTYPE)
+        html {
+            (Header::build())
+            body {
+                div
+                    ."min-h-screen"
+                    ."flex"
+                    ."items-center"
+                    ."justify-center"
+                    ."bg-gray-100"
+                {
+                    div
+                        ."w-full"
+                        ."max-w-sm"
+                        ."p-8"
+                        ."bg-white"
+                        ."border-2"
+                        ."border-gray-200"
+                        ."flex"
+                        ."flex-col"
+                        ."gap-6"
+                    {
+                        div
+                            ."flex"
+                            ."flex-row"
+                            ."items-center"
+                            ."justify-center"
+                            ."gap-3"
+                        {
+                            img ."h-12" src="/assets/luggage.svg" {}
+                            span
+                                ."text-xl"
+                                ."font-semibold"
+                            { "Packager" }
+                        }
+                        (content)
+                    }
+                }
+            }
+        }
+    )
+}
+
+pub struct LoginPage;
+
+impl LoginPage {
+    #[tracing::instrument]
+    pub fn build(error: Option<&str>, redirect: &str) -> Markup {
+        page(&html!(
+            form
+                action="/login"
+                method="post"
+                ."flex"
+                ."flex-col"
+                ."gap-4"
+            {
+                input type="hidden" name="redirect" value=(redirect);
+                @if let Some(error) = error {
+                    p
+                        ."p-2"
+                        ."bg-red-100"
+                        ."text-red-700"
+                    { (error) }
+                }
+                label ."flex" ."flex-col" ."gap-1" {
+                    span { "Username" }
+                    input
+                        type="text"
+                        name="username"
+                        autocomplete="username"
+                        required
+                        autofocus
+                        ."p-2"
+                        ."border-2"
+                        ."border-gray-300"
+                        ."focus:border-gray-500"
+                        ."focus:outline-none";
+                }
+                label ."flex" ."flex-col" ."gap-1" {
+                    span { "Password" }
+                    input
+                        type="password"
+                        name="password"
+                        autocomplete="current-password"
+                        required
+                        ."p-2"
+                        ."border-2"
+                        ."border-gray-300"
+                        ."focus:border-gray-500"
+                        ."focus:outline-none";
+                }
+                button
+                    type="submit"
+                    ."py-2"
+                    ."bg-green-200"
+                    ."hover:bg-green-400"
+                    ."font-semibold"
+                { "Log in" }
+            }
+        ))
+    }
+}
+
+pub struct LogoutPage;
+
+impl LogoutPage {
+    #[tracing::instrument]
+    pub fn build() -> Markup {
+        page(&html!(
+            form
+                action="/logout"
+                method="post"
+                ."flex"
+                ."flex-col"
+                ."gap-4"
+            {
+                p ."text-center" { "Do you want to log out?" }
+                button
+                    type="submit"
+                    ."py-2"
+                    ."bg-red-200"
+                    ."hover:bg-red-400"
+                    ."font-semibold"
+                { "Log out" }
+            }
+        ))
+    }
+}
@@ -39,6 +39,7 @@ use maud::Markup;
 
 pub mod error;
 pub mod home;
+pub mod login;
 pub mod root;
 
 pub use error::ErrorPage;
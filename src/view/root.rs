@@ -1,4 +1,4 @@
-use crate::{Context, TopLevelPage};
+use crate::{Context, TopLevelPage, models::user::Authentication};
 
 use maud::{html, Markup, PreEscaped, DOCTYPE};
 
@@ -191,6 +191,47 @@ impl<'a> Component for Body<'a> {
                         {}
                         p { (context.user.fullname)}
                     }
+                    a
+                        ."flex"
+                        ."flex-row"
+                        ."items-center"
+                        ."px-5"
+                        ."bg-gray-200"
+                        ."hover:bg-gray-300"
+                        href="/settings/tokens/"
+                        title="Settings"
+                    {
+                        span
+                            ."mdi"
+                            ."mdi-cog"
+                            ."text-3xl"
+                        {}
+                    }
+                    @if context.user.authentication == Authentication::Session {
+                        // a form, so other sites cannot log the user out with a link
+                        form
+                            action="/logout"
+                            method="post"
+                            ."flex"
+                        {
+                            button
+                                type="submit"
+                                title="Log out"
+                                ."flex"
+                                ."flex-row"
+                                ."items-center"
+                                ."px-5"
+                                ."bg-gray-200"
+                                ."hover:bg-gray-300"
+                            {
+                                span
+                                    ."mdi"
+                                    ."mdi-logout"
+                                    ."text-3xl"
+                                {}
+                            }
+                        }
+                    }
                 }
                 (self.args.body)
             }
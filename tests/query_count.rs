@@ -0,0 +1,300 @@
+//! Asserts that rendering trip pages issues a bounded number of database
+//! queries, independent of the amount of data shown.
+//!
+//! Queries are counted through the `packager_database_queries_total` counter
+//! that `database::telemetry::track_query` increments for every query.
+//!
+//! The test needs a Postgres database, so it is ignored by default. Run it
+//! with the same `DATABASE_URL` the build needs for checking the queries:
+//!
+//! ```sh
+//! DATABASE_URL=postgresql://... cargo test --test query_count -- --ignored
+//! ```
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use axum::{Router, body::Body};
+use database::Database as _;
+use http::{Request, StatusCode};
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use tokio::runtime::Runtime;
+use tower::ServiceExt as _;
+use uuid::Uuid;
+
+use packager::{AppState, ClientState, auth, domains::trips::TransitionRules};
+
+/// Upper bound of queries for rendering a single trip, including the lookup
+/// of the user
+const MAX_TRIP_PAGE_QUERIES: u64 = 13;
+
+/// Upper bound of queries for rendering the trip list, including the lookup
+/// of the user
+const MAX_TRIP_LIST_QUERIES: u64 = 6;
+
+#[derive(Default)]
+struct QueryCounter {
+    queries: Arc<AtomicU64>,
+}
+
+impl Recorder for QueryCounter {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        if key.name() == "packager_database_queries_total" {
+            Counter::from_arc(Arc::clone(&self.queries))
+        } else {
+            Counter::noop()
+        }
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+/// Requests `uri` and returns the number of queries it took.
+///
+/// The runtime is single threaded, so every query of the request is recorded
+/// by the thread local recorder.
+fn count_queries(runtime: &Runtime, app: &Router, uri: &str) -> u64 {
+    let counter = QueryCounter::default();
+
+    let response = metrics::with_local_recorder(&counter, || {
+        runtime.block_on(
+            app.clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap()),
+        )
+    })
+    .unwrap();
+
+    let status = response.status();
+    let body = runtime
+        .block_on(axum::body::to_bytes(response.into_body(), usize::MAX))
+        .unwrap();
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "GET {uri}: {}",
+        String::from_utf8_lossy(&body)
+    );
+
+    counter.queries.load(Ordering::Relaxed)
+}
+
+struct Fixture {
+    pool: sqlx::PgPool,
+    user_id: Uuid,
+    username: String,
+}
+
+impl Fixture {
+    async fn new(url: &str) -> Self {
+        let pool = sqlx::PgPool::connect(url).await.unwrap();
+        let user_id = Uuid::new_v4();
+        let username = format!("query-count-{user_id}");
+
+        sqlx::query("INSERT INTO users (id, username, fullname) VALUES ($1, $2, $2)")
+            .bind(user_id)
+            .bind(&username)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        Self {
+            pool,
+            user_id,
+            username,
+        }
+    }
+
+    async fn trip(&self, name: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO trips (id, name, date, state, user_id)
+            VALUES ($1, $2, '[2026-07-01,2026-07-05]', 'planning', $3)",
+        )
+        .bind(id)
+        // trip names are unique across users
+        .bind(format!("{name} {}", self.user_id))
+        .bind(self.user_id)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    /// Adds `categories` categories with `items` items each to the inventory
+    /// and to the trip
+    async fn items(&self, trip_id: Uuid, categories: usize, items: usize) {
+        for category in 0..categories {
+            let category_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO inventory_items_categories (id, name, user_id) VALUES ($1, $2, $3)",
+            )
+            .bind(category_id)
+            .bind(format!("category {trip_id} {category}"))
+            .bind(self.user_id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+
+            for item in 0..items {
+                let item_id = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO inventory_items (id, name, weight, category_id, user_id)
+                    VALUES ($1, $2, 100, $3, $4)",
+                )
+                .bind(item_id)
+                .bind(format!("item {trip_id} {category} {item}"))
+                .bind(category_id)
+                .bind(self.user_id)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+
+                sqlx::query(
+                    "INSERT INTO trip_items (item_id, trip_id, pick, pack, ready, new, user_id)
+                    VALUES ($1, $2, true, false, false, false, $3)",
+                )
+                .bind(item_id)
+                .bind(trip_id)
+                .bind(self.user_id)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+            }
+        }
+    }
+
+    async fn todos(&self, trip_id: Uuid, count: usize) {
+        for todo in 0..count {
+            sqlx::query(
+                "INSERT INTO trip_todos (id, trip_id, description, done, position)
+                VALUES ($1, $2, $3, false, $4)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(trip_id)
+            .bind(format!("todo {todo}"))
+            .bind(i32::try_from(todo).unwrap())
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    async fn stages(&self, trip_id: Uuid, count: usize) {
+        for stage in 0..count {
+            sqlx::query(
+                "INSERT INTO trip_stages (id, trip_id, position, date, location)
+                VALUES ($1, $2, $3, '[2026-07-01,2026-07-02]', $4)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(trip_id)
+            .bind(i32::try_from(stage).unwrap())
+            .bind(format!("stage {stage}"))
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    async fn cleanup(&self) {
+        for statement in [
+            "DELETE FROM trip_todos WHERE trip_id IN (SELECT id FROM trips WHERE user_id = $1)",
+            "DELETE FROM trip_stages WHERE trip_id IN (SELECT id FROM trips WHERE user_id = $1)",
+            "DELETE FROM trip_items WHERE user_id = $1",
+            "DELETE FROM trips WHERE user_id = $1",
+            "DELETE FROM inventory_items WHERE user_id = $1",
+            "DELETE FROM inventory_items_categories WHERE user_id = $1",
+            "DELETE FROM users WHERE id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(self.user_id)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+#[test]
+#[ignore = "requires DATABASE_URL"]
+fn trip_pages_use_a_bounded_number_of_queries() {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(database::DB::migrate(&url)).unwrap();
+
+    let fixture = runtime.block_on(Fixture::new(&url));
+
+    let app = packager::routing::router(AppState {
+        database_pool: runtime
+            .block_on(database::DB::init_database_pool(&url))
+            .unwrap(),
+        client_state: ClientState::new(),
+        auth_config: auth::Config::Disabled {
+            assume_user: fixture.username.clone(),
+        },
+        transition_rules: TransitionRules::default(),
+    });
+
+    let (small, large) = runtime.block_on(async {
+        let small = fixture.trip("small").await;
+        fixture.items(small, 1, 1).await;
+        fixture.todos(small, 1).await;
+        fixture.stages(small, 1).await;
+
+        let large = fixture.trip("large").await;
+        fixture.items(large, 10, 10).await;
+        fixture.todos(large, 20).await;
+        fixture.stages(large, 5).await;
+
+        (small, large)
+    });
+
+    let small_trip = count_queries(&runtime, &app, &format!("/trips/{small}/"));
+    let large_trip = count_queries(&runtime, &app, &format!("/trips/{large}/"));
+
+    let few_trips = count_queries(&runtime, &app, "/trips/");
+    runtime.block_on(async {
+        for trip in 0..20 {
+            fixture.trip(&format!("trip {trip}")).await;
+        }
+    });
+    let many_trips = count_queries(&runtime, &app, "/trips/");
+
+    runtime.block_on(fixture.cleanup());
+
+    assert_eq!(
+        small_trip, large_trip,
+        "trip page queries depend on the trip"
+    );
+    assert!(
+        large_trip <= MAX_TRIP_PAGE_QUERIES,
+        "trip page took {large_trip} queries"
+    );
+
+    assert_eq!(
+        few_trips, many_trips,
+        "trip list queries depend on the trips"
+    );
+    assert!(
+        many_trips <= MAX_TRIP_LIST_QUERIES,
+        "trip list took {many_trips} queries"
+    );
+}
@@ -0,0 +1,198 @@
+//! Asserts that API tokens can only do what their scope allows, also on
+//! routes that change data although they are reachable with GET.
+//!
+//! Like `query_count`, the test needs a Postgres database and is ignored by
+//! default:
+//!
+//! ```sh
+//! DATABASE_URL=postgresql://... cargo test --test token_scope -- --ignored
+//! ```
+
+use std::time::Duration;
+
+use axum::{Router, body::Body};
+use base64::Engine as _;
+use database::Database as _;
+use http::{Method, Request, StatusCode, header};
+use sha2::{Digest as _, Sha256};
+use tower::ServiceExt as _;
+use uuid::Uuid;
+
+use packager::{AppState, ClientState, auth, domains::trips::TransitionRules};
+
+struct Fixture {
+    pool: sqlx::PgPool,
+    user_id: Uuid,
+}
+
+impl Fixture {
+    async fn new(url: &str) -> Self {
+        let pool = sqlx::PgPool::connect(url).await.unwrap();
+        let user_id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO users (id, username, fullname) VALUES ($1, $2, $2)")
+            .bind(user_id)
+            .bind(format!("token-scope-{user_id}"))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        Self { pool, user_id }
+    }
+
+    /// Returns the secret of a new token with the scope
+    async fn token(&self, scope: &str) -> String {
+        let secret = format!("packager_{}", Uuid::new_v4().simple());
+        let hash = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(&secret));
+
+        sqlx::query(
+            "INSERT INTO api_tokens (id, user_id, name, token_hash, scope)
+            VALUES ($1, $2, $3, $4, $3::api_token_scope)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(self.user_id)
+        .bind(scope)
+        .bind(hash)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+
+        secret
+    }
+
+    async fn cleanup(&self) {
+        for statement in [
+            "DELETE FROM api_tokens WHERE user_id = $1",
+            "DELETE FROM users WHERE id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(self.user_id)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+async fn status(app: &Router, method: Method, uri: &str, token: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+#[test]
+#[ignore = "requires DATABASE_URL"]
+fn tokens_are_limited_by_their_scope() {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(database::DB::migrate(&url)).unwrap();
+
+    let fixture = runtime.block_on(Fixture::new(&url));
+
+    let app = packager::routing::router(AppState {
+        database_pool: runtime
+            .block_on(database::DB::init_database_pool(&url))
+            .unwrap(),
+        client_state: ClientState::new(),
+        auth_config: auth::Config::Password {
+            session_lifetime: Duration::from_secs(3600),
+        },
+        transition_rules: TransitionRules::default(),
+    });
+
+    // the routes are checked before anything is looked up, so the ids do
+    // not have to exist
+    let id = Uuid::new_v4();
+    let mutating_get_routes = [
+        format!("/trips/{id}/items/{id}/pick"),
+        format!("/trips/{id}/items/{id}/unpick"),
+        format!("/trips/{id}/items/{id}/pack"),
+        format!("/trips/{id}/items/{id}/unpack"),
+        format!("/trips/{id}/items/{id}/ready"),
+        format!("/trips/{id}/items/{id}/unready"),
+        format!("/trips/{id}/items/{id}/wear"),
+        format!("/trips/{id}/items/{id}/unwear"),
+        format!("/trips/{id}/type/{id}/add"),
+        format!("/trips/{id}/type/{id}/remove"),
+        format!("/inventory/item/{id}/delete"),
+        format!("/products/{id}/comments/{id}/delete"),
+    ];
+
+    let (results, rejected) = runtime.block_on(async {
+        let read = fixture.token("read").await;
+        let read_write = fixture.token("readwrite").await;
+
+        let mut results = vec![];
+
+        results.push((
+            "read-only GET /trips/".to_owned(),
+            status(&app, Method::GET, "/trips/", &read).await,
+            StatusCode::OK,
+        ));
+
+        for route in &mutating_get_routes {
+            results.push((
+                format!("read-only GET {route}"),
+                status(&app, Method::GET, route, &read).await,
+                StatusCode::FORBIDDEN,
+            ));
+        }
+
+        // no token may manage tokens, whatever its scope
+        for token in [&read, &read_write] {
+            results.push((
+                "GET /settings/tokens/".to_owned(),
+                status(&app, Method::GET, "/settings/tokens/", token).await,
+                StatusCode::FORBIDDEN,
+            ));
+            results.push((
+                "POST /settings/tokens/".to_owned(),
+                status(&app, Method::POST, "/settings/tokens/", token).await,
+                StatusCode::FORBIDDEN,
+            ));
+            results.push((
+                format!("POST /settings/tokens/{id}/revoke"),
+                status(
+                    &app,
+                    Method::POST,
+                    &format!("/settings/tokens/{id}/revoke"),
+                    token,
+                )
+                .await,
+                StatusCode::FORBIDDEN,
+            ));
+        }
+
+        // read & write tokens get past the check, the ids just do not exist
+        let mut rejected = vec![];
+        for route in &mutating_get_routes {
+            if status(&app, Method::GET, route, &read_write).await == StatusCode::FORBIDDEN {
+                rejected.push(format!("read & write GET {route}"));
+            }
+        }
+
+        (results, rejected)
+    });
+
+    runtime.block_on(fixture.cleanup());
+
+    for (request, actual, expected) in results {
+        assert_eq!(actual, expected, "{request}");
+    }
+    assert!(rejected.is_empty(), "rejected: {rejected:?}");
+}